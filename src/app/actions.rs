@@ -1 +1,125 @@
+use crate::{
+    app::{config::Config, config_saver::ConfigSaver},
+    core::history::ChatHistory,
+    emotes::twitch_api::{EmoteApi, TwitchApiClient},
+    events::app_event::AppEvent,
+    features::{notifications, webhook},
+    models::message::ChatMessage,
+};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use twitch_oauth2::UserToken;
 
+/// A side effect requested by the reducer.
+///
+/// The reducer only mutates state and describes what should happen next; the UI
+/// carries these out with [`perform`]. Keeping I/O out of the reducer lets it be
+/// driven synchronously, e.g. from tests, without a runtime.
+#[derive(Debug)]
+pub enum Action {
+    SaveConfig(Box<Config>),
+    FetchGlobalEmotes {
+        client_id: String,
+        token: Arc<UserToken>,
+    },
+    FetchChannelEmotes {
+        client_id: String,
+        token: Arc<UserToken>,
+        channel: String,
+        broadcaster_id: String,
+    },
+    FetchCheermotes {
+        client_id: String,
+        token: Arc<UserToken>,
+        channel: String,
+        broadcaster_id: String,
+    },
+    AppendHistory {
+        channel: String,
+        message: ChatMessage,
+        max_lines: usize,
+    },
+    PlayMessageSound {
+        volume: f32,
+    },
+    PostWebhook {
+        url: String,
+        payload: webhook::WebhookPayload,
+        max_per_second: u32,
+    },
+}
+
+pub fn perform(action: Action, event_tx: &mpsc::Sender<AppEvent>, config_saver: &ConfigSaver) {
+    match action {
+        Action::SaveConfig(config_to_save) => config_saver.save(*config_to_save),
+        Action::FetchGlobalEmotes { client_id, token } => {
+            let twitch_api_client = TwitchApiClient::new(client_id);
+            let event_tx = event_tx.clone();
+            tokio::spawn(async move {
+                let emotes_result = twitch_api_client.get_global_emotes(&token).await;
+                let event = AppEvent::GlobalEmotesLoaded(emotes_result.map_err(|e| e.to_string()));
+                let _ = event_tx.send(event).await;
+            });
+        }
+        Action::FetchChannelEmotes {
+            client_id,
+            token,
+            channel,
+            broadcaster_id,
+        } => {
+            let twitch_api_client = TwitchApiClient::new(client_id);
+            let event_tx = event_tx.clone();
+            tokio::spawn(async move {
+                let result = twitch_api_client
+                    .get_channel_emotes(&broadcaster_id, &token)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = event_tx
+                    .send(AppEvent::ChannelEmotesLoaded { channel, result })
+                    .await;
+            });
+        }
+        Action::FetchCheermotes {
+            client_id,
+            token,
+            channel,
+            broadcaster_id,
+        } => {
+            let twitch_api_client = TwitchApiClient::new(client_id);
+            let event_tx = event_tx.clone();
+            tokio::spawn(async move {
+                let result = twitch_api_client
+                    .get_cheermotes(&broadcaster_id, &token)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = event_tx
+                    .send(AppEvent::CheermotesLoaded { channel, result })
+                    .await;
+            });
+        }
+        Action::AppendHistory {
+            channel,
+            message,
+            max_lines,
+        } => {
+            let Some(history) = ChatHistory::from_data_dir(max_lines) else {
+                return;
+            };
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = history.append(&channel, &message) {
+                    tracing::warn!("Failed to save chat history for {}: {}", channel, e);
+                }
+            });
+        }
+        Action::PlayMessageSound { volume } => {
+            notifications::play_message_sound(volume);
+        }
+        Action::PostWebhook {
+            url,
+            payload,
+            max_per_second,
+        } => {
+            webhook::send(url, payload, max_per_second);
+        }
+    }
+}
@@ -1,16 +1,191 @@
-use eyre::{Context, eyre};
+use crate::{
+    core::{auth, proxy, storage, tokenize::EmotePrecedence},
+    features::{status_line::StatusLineSource, webhook::WebhookSettings},
+    models::{emote::EmoteSource, message::SystemCategory},
+    utils::{
+        text_processing::AutolinkMode,
+        time::{self, DisplayTimezone},
+    },
+};
+use eyre::Context;
 use figment::{
     Figment,
     providers::{Format, Toml},
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 use tokio::io::AsyncWriteExt;
 
+pub const MAX_RECENT_CHANNELS: usize = 20;
+/// The JSON config file older versions kept where `app_config.toml` is now.
+pub const LEGACY_CONFIG_FILE: &str = "config.json";
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Profile {
     pub name: String,
     pub twitch_user_id: Option<String>,
+    /// Channels that were open when the app last closed.
+    #[serde(default)]
+    pub open_channels: Vec<String>,
+    /// Appearance settings that differ from the global ones while this profile is active.
+    #[serde(default, skip_serializing_if = "AppearanceOverrides::is_empty")]
+    pub appearance: AppearanceOverrides,
+}
+
+/// Appearance settings a profile can set for itself. Unset fields follow the
+/// global settings.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AppearanceOverrides {
+    pub font_size: Option<f32>,
+    pub emote_size: Option<f32>,
+    pub show_timestamps: Option<bool>,
+    pub timezone: Option<DisplayTimezone>,
+    pub collapse_emotes: Option<bool>,
+    pub group_consecutive: Option<bool>,
+    pub highlight_own_messages: Option<bool>,
+    pub own_message_style: Option<OwnMessageStyle>,
+    pub compact_header: Option<bool>,
+    pub inline_alignment: Option<InlineAlignment>,
+}
+
+impl AppearanceOverrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Copies every set field onto `config`.
+    pub fn apply_to(&self, config: &mut Config) {
+        fn set<T: Clone>(target: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *target = value.clone();
+            }
+        }
+        set(&mut config.font_size, &self.font_size);
+        set(&mut config.emote_size, &self.emote_size);
+        set(&mut config.show_timestamps, &self.show_timestamps);
+        set(&mut config.timezone, &self.timezone);
+        set(&mut config.collapse_emotes, &self.collapse_emotes);
+        set(&mut config.group_consecutive, &self.group_consecutive);
+        set(
+            &mut config.highlight_own_messages,
+            &self.highlight_own_messages,
+        );
+        set(&mut config.own_message_style, &self.own_message_style);
+        set(&mut config.compact_header, &self.compact_header);
+        set(&mut config.inline_alignment, &self.inline_alignment);
+    }
+
+    /// Records every appearance setting that differs between `before` and `after`
+    /// as an override, leaving the others as they were.
+    pub fn capture_changes(&mut self, before: &Config, after: &Config) {
+        fn capture<T: Clone + PartialEq>(target: &mut Option<T>, before: &T, after: &T) {
+            if before != after {
+                *target = Some(after.clone());
+            }
+        }
+        capture(&mut self.font_size, &before.font_size, &after.font_size);
+        capture(&mut self.emote_size, &before.emote_size, &after.emote_size);
+        capture(
+            &mut self.show_timestamps,
+            &before.show_timestamps,
+            &after.show_timestamps,
+        );
+        capture(&mut self.timezone, &before.timezone, &after.timezone);
+        capture(
+            &mut self.collapse_emotes,
+            &before.collapse_emotes,
+            &after.collapse_emotes,
+        );
+        capture(
+            &mut self.group_consecutive,
+            &before.group_consecutive,
+            &after.group_consecutive,
+        );
+        capture(
+            &mut self.highlight_own_messages,
+            &before.highlight_own_messages,
+            &after.highlight_own_messages,
+        );
+        capture(
+            &mut self.own_message_style,
+            &before.own_message_style,
+            &after.own_message_style,
+        );
+        capture(
+            &mut self.compact_header,
+            &before.compact_header,
+            &after.compact_header,
+        );
+        capture(
+            &mut self.inline_alignment,
+            &before.inline_alignment,
+            &after.inline_alignment,
+        );
+    }
+}
+
+/// The color scheme of the whole window.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Theme {
+    Dark,
+    Light,
+    /// Whichever the operating system uses.
+    #[default]
+    System,
+}
+
+/// Vertical placement of text relative to emotes within a chat row.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum InlineAlignment {
+    Top,
+    #[default]
+    Center,
+    Bottom,
+}
+
+/// How messages sent by the user's own accounts stand out in the chat log.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum OwnMessageStyle {
+    #[default]
+    Tint,
+    Border,
+}
+
+/// User-picked colors for system messages. Unset categories follow the theme.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SystemMessageColors {
+    pub subscription: Option<[u8; 3]>,
+    pub raid: Option<[u8; 3]>,
+    pub announcement: Option<[u8; 3]>,
+    pub bits_badge: Option<[u8; 3]>,
+    pub other: Option<[u8; 3]>,
+}
+
+impl SystemMessageColors {
+    pub fn get(&self, category: SystemCategory) -> Option<[u8; 3]> {
+        match category {
+            SystemCategory::Subscription => self.subscription,
+            SystemCategory::Raid => self.raid,
+            SystemCategory::Announcement => self.announcement,
+            SystemCategory::BitsBadge => self.bits_badge,
+            SystemCategory::Other => self.other,
+        }
+    }
+
+    pub fn get_mut(&mut self, category: SystemCategory) -> &mut Option<[u8; 3]> {
+        match category {
+            SystemCategory::Subscription => &mut self.subscription,
+            SystemCategory::Raid => &mut self.raid,
+            SystemCategory::Announcement => &mut self.announcement,
+            SystemCategory::BitsBadge => &mut self.bits_badge,
+            SystemCategory::Other => &mut self.other,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -22,7 +197,89 @@ pub struct Config {
     pub font_size: f32,
     pub emote_size: f32,
     pub show_timestamps: bool,
+    pub timezone: DisplayTimezone,
+    /// strftime pattern for chat timestamps. Invalid ones show the default instead.
+    pub timestamp_format: String,
     pub collapse_emotes: bool,
+    /// Skip the sender name on messages that directly follow one from the same sender.
+    pub group_consecutive: bool,
+    pub highlight_own_messages: bool,
+    /// Fit the top panel into a single row.
+    pub compact_header: bool,
+    /// Put the current channel and unread count in the window title.
+    pub show_channel_in_title: bool,
+    /// Window width below which the header is compacted, secondary controls move
+    /// into overflow menus and the user list is hidden.
+    pub narrow_layout_width: f32,
+    pub theme: Theme,
+    /// Used for senders without a chat color and for highlights instead of the
+    /// theme's own colors.
+    pub accent_color: Option<[u8; 3]>,
+    /// Name colors pinned to chatters, keyed by lowercase name, used over their own.
+    pub user_colors: HashMap<String, [u8; 3]>,
+    /// Lowercase names of chatters whose messages are never shown.
+    pub ignored_users: Vec<String>,
+    pub system_message_colors: SystemMessageColors,
+    pub own_message_style: OwnMessageStyle,
+    pub inline_alignment: InlineAlignment,
+    pub idle_repaint_interval_secs: f32,
+    pub keep_deleted_messages: bool,
+    pub enable_twitch_emotes: bool,
+    pub enable_bttv: bool,
+    pub enable_ffz: bool,
+    pub enable_7tv: bool,
+    /// Whether a third-party emote replaces a Twitch emote with the same code.
+    pub emote_precedence: EmotePrecedence,
+    /// Draw animated emotes as still images while chat is faster than
+    /// `busy_chat_threshold` messages per second.
+    pub pause_emote_animations_when_busy: bool,
+    pub busy_chat_threshold: f32,
+    /// Disk space emote images may take up between sessions; 0 turns the cache off.
+    pub emote_cache_mb: u64,
+    pub confirm_links: bool,
+    /// Send each line of a multi-line message on its own instead of joining them
+    /// with spaces.
+    pub send_lines_separately: bool,
+    /// Show where a chat link leads and ask before opening it.
+    pub confirm_link_clicks: bool,
+    pub autolink_urls: AutolinkMode,
+    /// Show the emotes a message will contain in a strip below the input.
+    pub show_emote_preview: bool,
+    pub redemption_queue_enabled: bool,
+    pub status_line_file: Option<String>,
+    pub status_line_command: Option<String>,
+    pub status_line_interval_secs: u64,
+    pub message_sound_enabled: bool,
+    pub message_sound_volume: f32,
+    pub message_sound_min_interval_secs: f32,
+    /// Per-channel overrides of `message_sound_enabled`, keyed by lowercase channel login.
+    pub message_sound_channels: HashMap<String, bool>,
+    /// Messages containing any of these words, ignoring case, are highlighted.
+    pub highlight_keywords: Vec<String>,
+    /// Most recently joined channels first, for the channel switcher.
+    pub recent_channels: Vec<String>,
+    /// Joined right after login unless a previous session is being restored.
+    pub default_channel: Option<String>,
+    /// Re-join the active profile's open channels after a silent login.
+    pub restore_session_on_launch: bool,
+    /// Messages kept on disk per channel and shown again when it is rejoined; 0
+    /// turns history off.
+    pub chat_history_lines: usize,
+    /// Messages kept in each channel's chat log; older ones are dropped as new ones
+    /// arrive.
+    pub max_chat_lines: usize,
+    /// Proxy for plain HTTP requests; falls back to `HTTP_PROXY` when unset.
+    pub http_proxy: Option<String>,
+    /// Proxy for HTTPS requests and the EventSub websocket; falls back to `HTTPS_PROXY`.
+    pub https_proxy: Option<String>,
+    /// Keep the raw EventSub JSON of recent messages for inspection.
+    pub developer_tools: bool,
+    /// Scopes to request on top of the default (or minimal) set, e.g. for scripts
+    /// sharing the login.
+    pub extra_scopes: Vec<String>,
+    /// Only request the scopes needed to read and send chat.
+    pub minimal_scopes: bool,
+    pub webhook: WebhookSettings,
     pub profiles: Vec<Profile>,
     pub active_profile_name: Option<String>,
 }
@@ -36,7 +293,57 @@ impl Default for Config {
             font_size: 14.0,
             emote_size: 14.0,
             show_timestamps: false,
+            timezone: DisplayTimezone::default(),
+            timestamp_format: time::DEFAULT_TIMESTAMP_FORMAT.to_string(),
             collapse_emotes: false,
+            group_consecutive: false,
+            highlight_own_messages: false,
+            compact_header: false,
+            show_channel_in_title: true,
+            narrow_layout_width: 420.0,
+            theme: Theme::default(),
+            accent_color: None,
+            user_colors: HashMap::new(),
+            ignored_users: Vec::new(),
+            system_message_colors: SystemMessageColors::default(),
+            own_message_style: OwnMessageStyle::default(),
+            inline_alignment: InlineAlignment::default(),
+            idle_repaint_interval_secs: 1.0,
+            keep_deleted_messages: false,
+            enable_twitch_emotes: true,
+            // Third-party emotes are opt-in, matching what the official client shows.
+            enable_bttv: false,
+            enable_ffz: false,
+            emote_precedence: EmotePrecedence::default(),
+            pause_emote_animations_when_busy: true,
+            busy_chat_threshold: 20.0,
+            emote_cache_mb: 256,
+            enable_7tv: false,
+            confirm_links: false,
+            send_lines_separately: false,
+            confirm_link_clicks: false,
+            autolink_urls: AutolinkMode::default(),
+            show_emote_preview: true,
+            redemption_queue_enabled: true,
+            status_line_file: None,
+            status_line_command: None,
+            status_line_interval_secs: 10,
+            message_sound_enabled: false,
+            message_sound_volume: 0.5,
+            message_sound_min_interval_secs: 5.0,
+            message_sound_channels: HashMap::new(),
+            highlight_keywords: Vec::new(),
+            recent_channels: Vec::new(),
+            default_channel: None,
+            restore_session_on_launch: false,
+            chat_history_lines: 500,
+            max_chat_lines: 200,
+            http_proxy: None,
+            https_proxy: None,
+            developer_tools: false,
+            extra_scopes: Vec::new(),
+            minimal_scopes: false,
+            webhook: WebhookSettings::default(),
             profiles: Vec::new(),
             active_profile_name: None,
         }
@@ -50,36 +357,160 @@ impl Config {
             None => None,
         }
     }
+
+    pub fn get_active_profile_mut(&mut self) -> Option<&mut Profile> {
+        let name = self.active_profile_name.as_ref()?;
+        self.profiles.iter_mut().find(|p| &p.name == name)
+    }
+
+    /// The settings the UI should follow: the global ones with the active profile's
+    /// appearance overrides applied on top.
+    pub fn effective(&self) -> Config {
+        let mut config = self.clone();
+        if let Some(profile) = self.get_active_profile() {
+            profile.appearance.apply_to(&mut config);
+        }
+        config
+    }
+
+    /// Whether the space between adjacent emotes is dropped, for the active profile.
+    pub fn collapse_emotes_for_active_profile(&self) -> bool {
+        self.get_active_profile()
+            .and_then(|profile| profile.appearance.collapse_emotes)
+            .unwrap_or(self.collapse_emotes)
+    }
+
+    /// The configured status line source. A file takes precedence over a command.
+    pub fn status_line_source(&self) -> Option<StatusLineSource> {
+        if let Some(path) = self.status_line_file.as_ref().filter(|p| !p.is_empty()) {
+            return Some(StatusLineSource::File(PathBuf::from(path)));
+        }
+        self.status_line_command
+            .as_ref()
+            .filter(|c| !c.is_empty())
+            .map(|c| StatusLineSource::Command(c.clone()))
+    }
+
+    /// The OAuth scopes a login should ask for.
+    pub fn requested_scopes(&self) -> Vec<twitch_oauth2::Scope> {
+        auth::requested_scopes(&self.extra_scopes, self.minimal_scopes)
+    }
+
+    /// Whether emotes from `source` are fetched, rendered in messages and offered in
+    /// the picker. Disabled emotes show as their plain text code.
+    pub fn emote_source_enabled(&self, source: &EmoteSource) -> bool {
+        match source {
+            EmoteSource::Twitch | EmoteSource::Cheer => self.enable_twitch_emotes,
+            EmoteSource::Bttv => self.enable_bttv,
+            EmoteSource::Ffz => self.enable_ffz,
+            EmoteSource::Stv => self.enable_7tv,
+        }
+    }
+
+    /// Whether messages from `user_name` are hidden, ignoring case.
+    pub fn is_ignored(&self, user_name: &str) -> bool {
+        self.ignored_users
+            .iter()
+            .any(|ignored| ignored.eq_ignore_ascii_case(user_name))
+    }
+
+    pub fn message_sound_enabled_for(&self, channel: &str) -> bool {
+        self.message_sound_channels
+            .get(&channel.to_lowercase())
+            .copied()
+            .unwrap_or(self.message_sound_enabled)
+    }
+}
+
+/// The settings carried over from a [`LEGACY_CONFIG_FILE`]. Anything else in it is
+/// ignored.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct LegacyConfig {
+    pub client_id: Option<String>,
+    pub font_size: Option<f32>,
+    pub enable_cjk_font: Option<bool>,
+}
+
+impl LegacyConfig {
+    /// Copies the settings the legacy file had into `config`.
+    pub fn apply_to(self, config: &mut Config) {
+        if let Some(client_id) = self.client_id.filter(|id| !id.is_empty()) {
+            config.client_id = Some(client_id);
+        }
+        if let Some(font_size) = self.font_size {
+            config.font_size = font_size;
+        }
+        if let Some(enable_cjk_font) = self.enable_cjk_font {
+            config.enable_cjk_font = enable_cjk_font;
+        }
+    }
 }
 
-fn get_config_path() -> Result<PathBuf, eyre::Report> {
-    let config_dir = dirs::config_dir()
-        .ok_or_else(|| eyre!("Could not find a config directory"))?
-        .join(env!("CARGO_PKG_NAME"));
+/// Imports the legacy config at `legacy_path` into `config`, writes the result to
+/// `config_path` and renames the legacy file to `config.json.bak` so it's only
+/// imported once. Returns whether there was anything to import.
+pub async fn migrate_legacy_config(
+    legacy_path: &Path,
+    config_path: &Path,
+    config: &mut Config,
+) -> Result<bool, eyre::Report> {
+    if !legacy_path.exists() {
+        return Ok(false);
+    }
+    let json = tokio::fs::read_to_string(legacy_path)
+        .await
+        .context("Failed to read legacy config")?;
+    let legacy: LegacyConfig =
+        serde_json::from_str(&json).context("Failed to parse legacy config")?;
+    legacy.apply_to(config);
+    save_to(config, config_path).await?;
+    tokio::fs::rename(legacy_path, legacy_path.with_extension("json.bak"))
+        .await
+        .context("Failed to rename legacy config")?;
+    Ok(true)
+}
 
-    Ok(config_dir.join("app_config.toml"))
+/// The user's config file, or `None` when running without a writable directory.
+fn get_config_path() -> Option<PathBuf> {
+    storage::data_dir().map(|dir| dir.join("app_config.toml"))
 }
 
 pub async fn load() -> Result<Config, eyre::Report> {
-    let user_config_path = get_config_path()?;
-    tracing::info!("Loading user config from {:?}", user_config_path);
-
     let base_config_path = "config/app_config.toml";
     tracing::info!("Loading base config from {:?}", base_config_path);
+    let mut figment = Figment::new().merge(Toml::file(base_config_path));
 
-    let config: Config = Figment::new()
-        .merge(Toml::file(base_config_path))
-        .merge(Toml::file(&user_config_path))
-        .extract()
-        .context("Could not load config")?;
+    let user_config_path = get_config_path();
+    match &user_config_path {
+        Some(path) => {
+            tracing::info!("Loading user config from {:?}", path);
+            figment = figment.merge(Toml::file(path));
+        }
+        None => tracing::warn!("No config directory; using defaults for this session"),
+    }
 
-    if !user_config_path.exists() {
-        if let Err(e) = save(&config).await {
-            tracing::warn!("Failed to save initial config: {}", e);
+    let mut config: Config = figment.extract().context("Could not load config")?;
+
+    if let Some(path) = &user_config_path {
+        let legacy_path = path.with_file_name(LEGACY_CONFIG_FILE);
+        match migrate_legacy_config(&legacy_path, path, &mut config).await {
+            Ok(true) => tracing::info!("Imported settings from {:?}", legacy_path),
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to import {:?}: {}", legacy_path, e),
         }
     }
 
+    if user_config_path.is_some_and(|path| !path.exists())
+        && let Err(e) = save(&config).await
+    {
+        tracing::warn!("Failed to save initial config: {}", e);
+    }
+
     log_config_status(&config, "Post-load");
+    if let Err(e) = proxy::configure(config.http_proxy.as_deref(), config.https_proxy.as_deref()) {
+        tracing::error!("{}; connecting without the configured proxy", e);
+    }
 
     Ok(config)
 }
@@ -95,7 +526,14 @@ pub fn log_config_status(config: &Config, context: &str) {
 }
 
 pub async fn save(config: &Config) -> Result<(), eyre::Report> {
-    let path = get_config_path()?;
+    let Some(path) = get_config_path() else {
+        tracing::debug!("Not saving config, running without a config directory");
+        return Ok(());
+    };
+    save_to(config, &path).await
+}
+
+async fn save_to(config: &Config, path: &Path) -> Result<(), eyre::Report> {
     tracing::info!("Saving config to {:?}", path);
     log_config_status(config, "Pre-save");
 
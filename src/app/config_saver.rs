@@ -0,0 +1,58 @@
+//! Writes the config file from a single background task, at most once per
+//! [`SAVE_INTERVAL`]. Settings change on every frame while a slider is dragged;
+//! only the latest config is written once the interval has passed.
+
+use crate::app::config::{self, Config};
+use eyre::Report;
+use std::{future::Future, time::Duration};
+use tokio::sync::watch;
+
+/// The shortest time between two writes of the config file.
+pub const SAVE_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct ConfigSaver {
+    pending: watch::Sender<Option<Config>>,
+}
+
+impl ConfigSaver {
+    /// Saves to the config file. Must be called from within the Tokio runtime.
+    pub fn new() -> Self {
+        Self::with_writer(SAVE_INTERVAL, |config| async move {
+            config::save(&config).await
+        })
+    }
+
+    /// Hands each config to `write`, waiting `interval` after every write before
+    /// taking the next one. Configs queued in the meantime replace each other.
+    pub fn with_writer<F, Fut>(interval: Duration, mut write: F) -> Self
+    where
+        F: FnMut(Config) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), Report>> + Send,
+    {
+        let (pending, mut rx) = watch::channel(None::<Config>);
+        tokio::spawn(async move {
+            // Ends once the saver is dropped and nothing is left to write.
+            while rx.changed().await.is_ok() {
+                let Some(config) = rx.borrow_and_update().clone() else {
+                    continue;
+                };
+                if let Err(e) = write(config).await {
+                    tracing::error!("Failed to save config: {}", e);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+        Self { pending }
+    }
+
+    /// Marks `config` as the one to write next.
+    pub fn save(&self, config: Config) {
+        self.pending.send_replace(Some(config));
+    }
+}
+
+impl Default for ConfigSaver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
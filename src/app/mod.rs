@@ -1,4 +1,5 @@
 pub mod actions;
 pub mod config;
+pub mod config_saver;
 pub mod reducer;
 pub mod state;
@@ -1,32 +1,79 @@
-use super::state::AppState;
+use super::{
+    actions::Action,
+    state::{AppState, ChannelTab, ConnectionStatus, Identity, MAX_RAW_EVENTS, PendingRaid},
+};
 use crate::{
     app::config::Config,
-    core::{auth::AuthMessage, chat::ChatClient},
-    emotes::twitch_api::TwitchApiClient,
+    core::{
+        auth::AuthMessage,
+        tokenize::{normalize_whitespace, resolve_cheermotes},
+    },
     events::app_event::{AppEvent, ChatEvent},
-    models::{message::MessageFragment, user::User},
+    features::webhook::WebhookPayload,
+    models::{
+        message::{ChatMessage, MessageKind, SystemCategory},
+        user::User,
+    },
+};
+use chrono::Local;
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use std::{collections::HashSet, sync::Arc};
-use tokio::sync::mpsc;
 use twitch_oauth2::UserToken;
 
-pub fn reduce(
+/// How many of the newest messages are checked for the one a duplicate event
+/// belongs to. The copies arrive right after each other, and checking the whole
+/// log would make every message cost more the longer it is.
+pub const DUPLICATE_LOOKBACK: usize = 50;
+
+/// Applies `event` to the state and config, returning the side effects the caller
+/// should carry out (see [`crate::app::actions::perform`]).
+pub fn reduce(state: &mut AppState, event: AppEvent, config: &mut Config) -> Vec<Action> {
+    reduce_batch(state, std::iter::once(event), config)
+}
+
+/// Applies several events at once, as the UI does with everything that arrived
+/// since the last frame. Work that only depends on the end result, like trimming
+/// the chat history, is done once for the whole batch instead of per event.
+pub fn reduce_batch(
+    state: &mut AppState,
+    events: impl IntoIterator<Item = AppEvent>,
+    config: &mut Config,
+) -> Vec<Action> {
+    let mut actions = Vec::new();
+    for event in events {
+        reduce_event(state, event, config, &mut actions);
+    }
+    trim_chat_history(state, config.max_chat_lines);
+    actions
+}
+
+fn trim_chat_history(state: &mut AppState, max_lines: usize) {
+    if let AppState::LoggedIn { tabs, .. } = state {
+        for tab in tabs {
+            let excess = tab.chat_messages.len().saturating_sub(max_lines);
+            tab.chat_messages.drain(..excess);
+        }
+    }
+}
+
+fn reduce_event(
     state: &mut AppState,
     event: AppEvent,
     config: &mut Config,
-    event_tx: mpsc::Sender<AppEvent>,
+    actions: &mut Vec<Action>,
 ) {
     match event {
         AppEvent::ConfigLoaded(config_result) => {
             handle_config_loaded(config_result, state, config);
         }
         AppEvent::SilentLoginComplete(result) => {
-            handle_silent_login_complete(state, result, config, event_tx.clone());
+            handle_silent_login_complete(state, result, config, actions);
         }
         AppEvent::ProfileSwitchSilentLoginComplete(result, profile_name) => match result {
-            Ok(token) => {
-                handle_successful_login(state, token, config, event_tx, Some(profile_name))
-            }
+            Ok(token) => handle_successful_login(state, token, config, actions, Some(profile_name)),
             Err(e) => {
                 tracing::warn!(
                     "Silent login for profile '{}' failed: {}. Proceeding to interactive flow.",
@@ -36,8 +83,16 @@ pub fn reduce(
                 *state = AppState::RequestingInteractiveLogin { profile_name };
             }
         },
+        AppEvent::TokenRefreshed(new_token) => {
+            if let AppState::LoggedIn { token, .. } = state {
+                *token = Arc::new(new_token);
+            }
+        }
+        AppEvent::IdentityLoaded(result, profile_name) => {
+            handle_identity_loaded(state, result, profile_name);
+        }
         AppEvent::Auth(auth_message) => {
-            handle_auth_message(state, auth_message, config, event_tx.clone());
+            handle_auth_message(state, auth_message, config, actions);
         }
         AppEvent::AuthCancel => {
             // This is now only used if the user closes the window during first time setup
@@ -54,11 +109,45 @@ pub fn reduce(
                 error: Some(err),
             };
         }
+        AppEvent::Chat(ChatEvent::ChannelNotFound(channel)) => {
+            handle_channel_not_found(state, channel, config, actions);
+        }
+        AppEvent::Chat(ChatEvent::ChannelLookupFailed { channel, error }) => {
+            if let AppState::LoggedIn {
+                tabs, last_error, ..
+            } = state
+            {
+                tracing::error!("Failed to look up channel '{}': {}", channel, error);
+                if let Some(tab) = tabs.iter_mut().find(|tab| tab.channel == channel) {
+                    tab.connection_status = ConnectionStatus::Disconnected;
+                    tab.reconnecting = None;
+                }
+                *last_error = Some(format!("Failed to look up #{}: {}", channel, error));
+            }
+        }
+        AppEvent::Chat(ChatEvent::ChannelJoined {
+            channel,
+            broadcaster_id,
+        }) => {
+            handle_channel_joined(state, channel, broadcaster_id, config, actions);
+        }
         AppEvent::Chat(chat_message) => {
-            handle_chat_message(state, chat_message);
+            handle_chat_message(state, None, chat_message, config, actions);
+        }
+        AppEvent::ChannelChat {
+            broadcaster_id,
+            event,
+        } => {
+            handle_chat_message(state, Some(&broadcaster_id), event, config, actions);
         }
         AppEvent::GlobalEmotesLoaded(result) => {
-            if let AppState::LoggedIn { global_emotes, .. } = state {
+            if let AppState::LoggedIn {
+                global_emotes,
+                global_emotes_loaded,
+                ..
+            } = state
+            {
+                *global_emotes_loaded = true;
                 match result {
                     Ok(emotes) => {
                         *global_emotes = emotes;
@@ -73,17 +162,79 @@ pub fn reduce(
                 }
             }
         }
+        AppEvent::ChannelEmotesLoaded { channel, result } => {
+            if let AppState::LoggedIn { tabs, .. } = state {
+                // A slow response for a channel the user already left is dropped.
+                let Some(tab) = tabs.iter_mut().find(|tab| tab.channel == channel) else {
+                    return;
+                };
+                match result {
+                    Ok(emotes) => {
+                        tracing::info!("Loaded {} emotes for #{}.", emotes.len(), channel);
+                        tab.channel_emotes = emotes;
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to load emotes for #{}: {}", channel, e);
+                    }
+                }
+            }
+        }
+        AppEvent::ThirdPartyEmotesLoaded { channel, emotes } => {
+            if let AppState::LoggedIn { tabs, .. } = state {
+                let Some(tab) = tabs.iter_mut().find(|tab| tab.channel == channel) else {
+                    return;
+                };
+                tracing::info!(
+                    "Loaded {} third-party emotes for #{}.",
+                    emotes.len(),
+                    channel
+                );
+                tab.third_party_emotes = emotes;
+            }
+        }
+        AppEvent::CheermotesLoaded { channel, result } => {
+            if let AppState::LoggedIn { tabs, .. } = state {
+                let Some(tab) = tabs.iter_mut().find(|tab| tab.channel == channel) else {
+                    return;
+                };
+                match result {
+                    Ok(cheermotes) => {
+                        tab.cheermotes = cheermotes;
+                        // Cheers that arrived while the images were being looked up.
+                        for message in tab.chat_messages.iter_mut() {
+                            resolve_cheermotes(&mut message.fragments, &tab.cheermotes);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to load cheermotes for #{}: {}", channel, e);
+                    }
+                }
+            }
+        }
+        AppEvent::StatusLineUpdated(line) => {
+            if let AppState::LoggedIn { status_line, .. } = state {
+                *status_line = line;
+            }
+        }
+        AppEvent::ChatLogSaved(result) => match result {
+            Ok(path) => tracing::info!("Saved chat log to {:?}", path),
+            Err(e) => {
+                if let AppState::LoggedIn { last_error, .. } = state {
+                    *last_error = Some(format!("Failed to save chat log: {}", e));
+                }
+            }
+        },
     }
 }
 
 fn handle_config_loaded(
-    result: Result<Config, eyre::Report>,
+    result: Result<Box<Config>, eyre::Report>,
     state: &mut AppState,
     config: &mut Config,
 ) {
     match result {
         Ok(loaded_config) => {
-            *config = loaded_config;
+            *config = *loaded_config;
             *state = AppState::Initializing {
                 task_spawned: false,
             };
@@ -106,11 +257,18 @@ fn handle_silent_login_complete(
     state: &mut AppState,
     result: Result<UserToken, eyre::Report>,
     config: &mut Config,
-    event_tx: mpsc::Sender<AppEvent>,
+    actions: &mut Vec<Action>,
 ) {
     match result {
         Ok(token) => {
-            handle_successful_login(state, token, config, event_tx, None);
+            handle_successful_login(state, token, config, actions, None);
+            if config.restore_session_on_launch
+                && let (AppState::LoggedIn { pending_joins, .. }, Some(profile)) =
+                    (&mut *state, config.get_active_profile())
+                && !profile.open_channels.is_empty()
+            {
+                *pending_joins = profile.open_channels.clone();
+            }
         }
         Err(e) => {
             tracing::info!("Silent login failed: {}", e);
@@ -138,13 +296,13 @@ fn handle_auth_message(
     state: &mut AppState,
     msg: AuthMessage,
     config: &mut Config,
-    event_tx: mpsc::Sender<AppEvent>,
+    actions: &mut Vec<Action>,
 ) {
     match msg {
         AuthMessage::Success(token) => {
             // The profile name should already be set in the config from the UI logic
             let profile_name = config.active_profile_name.clone();
-            handle_successful_login(state, token, config, event_tx, profile_name);
+            handle_successful_login(state, token, config, actions, profile_name);
         }
         AuthMessage::Error(err) => {
             // If auth fails, we go back to the first time setup screen to show the error.
@@ -163,7 +321,7 @@ fn handle_successful_login(
     state: &mut AppState,
     token: UserToken,
     config: &mut Config,
-    event_tx: mpsc::Sender<AppEvent>,
+    actions: &mut Vec<Action>,
     auth_profile_name: Option<String>,
 ) {
     let user_id = token.user_id.clone();
@@ -184,85 +342,494 @@ fn handle_successful_login(
         config.profiles.push(crate::app::config::Profile {
             name: new_profile_name.clone(),
             twitch_user_id: Some(user_id.to_string()),
+            open_channels: Vec::new(),
+            appearance: Default::default(),
         });
         config.active_profile_name = Some(new_profile_name);
     }
 
-    let config_to_save = config.clone();
-    tokio::spawn(async move {
-        if let Err(e) = crate::app::config::save(&config_to_save).await {
-            tracing::error!("Failed to save config after login: {}", e);
-        }
-    });
+    actions.push(Action::SaveConfig(Box::new(config.clone())));
 
     let token = Arc::new(token);
 
-    *state = AppState::LoggedIn {
-        token: token.clone(),
-        user_id,
-        user_login: user_login.to_string(),
-        channel_to_join: String::new(),
-        current_channel: None,
-        message_to_send: String::new(),
-        chat_messages: Vec::new(),
-        users: HashSet::new(),
-        global_emotes: Vec::new(),
-        chat_client: ChatClient::new(),
-        send_in_progress: false,
-        last_error: None,
-        eventsub_task: None,
+    *state = AppState::logged_in(token.clone());
+    if let (AppState::LoggedIn { pending_joins, .. }, Some(channel)) =
+        (&mut *state, config.default_channel.as_ref())
+    {
+        pending_joins.push(channel.clone());
+    }
+
+    let fetch_action = if !config.enable_twitch_emotes {
+        None
+    } else if let Some(client_id) = &config.client_id {
+        Some(Action::FetchGlobalEmotes {
+            client_id: client_id.clone(),
+            token,
+        })
+    } else {
+        tracing::error!("Client ID not found, cannot fetch global emotes.");
+        None
+    };
+    if let Some(action) = fetch_action {
+        actions.push(action);
+    } else if let AppState::LoggedIn {
+        global_emotes_loaded,
+        ..
+    } = state
+    {
+        *global_emotes_loaded = true;
+    }
+}
+
+/// Drops a channel that no longer exists from the saved session, so a renamed or
+/// banned channel doesn't fail again on every launch.
+fn handle_channel_not_found(
+    state: &mut AppState,
+    channel: String,
+    config: &mut Config,
+    actions: &mut Vec<Action>,
+) {
+    tracing::warn!("Channel '{}' not found", channel);
+    let index = match &*state {
+        AppState::LoggedIn { tabs, .. } => tabs.iter().position(|tab| tab.channel == channel),
+        _ => None,
+    };
+    if let Some(index) = index {
+        state.close_tab(index);
+    }
+    if let AppState::LoggedIn { last_error, .. } = state {
+        *last_error = Some(format!("Channel #{} not found", channel));
+    }
+    if let Some(profile) = config.get_active_profile_mut() {
+        let before = profile.open_channels.len();
+        profile.open_channels.retain(|c| c != &channel);
+        if profile.open_channels.len() != before {
+            actions.push(Action::SaveConfig(Box::new(config.clone())));
+        }
+    }
+}
+
+/// Records the id a tab's channel was looked up as, so events from its chat
+/// connection can be routed to it, and fetches the channel's emotes.
+fn handle_channel_joined(
+    state: &mut AppState,
+    channel: String,
+    broadcaster_id: String,
+    config: &Config,
+    actions: &mut Vec<Action>,
+) {
+    let AppState::LoggedIn { tabs, token, .. } = state else {
+        return;
+    };
+    let Some(tab) = tabs.iter_mut().find(|tab| tab.channel == channel) else {
+        return;
     };
+    tab.channel_emotes.clear();
+    tab.third_party_emotes.clear();
+    tab.cheermotes.clear();
+    tab.broadcaster_id = Some(broadcaster_id.clone());
+    if let Some(client_id) = config
+        .client_id
+        .as_ref()
+        .filter(|_| config.enable_twitch_emotes)
+    {
+        actions.push(Action::FetchChannelEmotes {
+            client_id: client_id.clone(),
+            token: token.clone(),
+            channel: channel.clone(),
+            broadcaster_id: broadcaster_id.clone(),
+        });
+        actions.push(Action::FetchCheermotes {
+            client_id: client_id.clone(),
+            token: token.clone(),
+            channel,
+            broadcaster_id,
+        });
+    }
+}
+
+fn handle_identity_loaded(
+    state: &mut AppState,
+    result: Result<UserToken, eyre::Report>,
+    profile_name: String,
+) {
+    if let AppState::LoggedIn {
+        identities,
+        send_as,
+        last_error,
+        ..
+    } = state
+    {
+        match result {
+            Ok(token) => {
+                tracing::info!("Added '{}' as a chat identity", profile_name);
+                identities.retain(|i| i.profile_name != profile_name);
+                identities.push(Identity {
+                    profile_name: profile_name.clone(),
+                    token: Arc::new(token),
+                });
+                *send_as = Some(profile_name);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load identity '{}': {}", profile_name, e);
+                *last_error = Some(format!(
+                    "Couldn't use '{}' to chat: {}. Log in with that profile once first.",
+                    profile_name, e
+                ));
+            }
+        }
+    }
+}
 
-    if let Some(client_id) = &config.client_id {
-        let twitch_api_client = TwitchApiClient::new(client_id.clone());
-        let token_clone = token.clone();
-        tokio::spawn(async move {
-            let emotes_result = twitch_api_client.get_global_emotes(&token_clone).await;
-            let event = AppEvent::GlobalEmotesLoaded(emotes_result.map_err(|e| e.to_string()));
-            let _ = event_tx.send(event).await;
+fn push_webhook(actions: &mut Vec<Action>, config: &Config, payload: Option<WebhookPayload>) {
+    if let (Some(url), Some(payload)) = (&config.webhook.url, payload) {
+        actions.push(Action::PostWebhook {
+            url: url.clone(),
+            payload,
+            max_per_second: config.webhook.max_per_second,
         });
+    }
+}
+
+/// Takes the messages a moderator removed out of the log, or only marks them when
+/// deleted messages are kept visible.
+fn remove_messages(tab: &mut ChannelTab, config: &Config, removed: impl Fn(&ChatMessage) -> bool) {
+    if config.keep_deleted_messages {
+        for message in tab.chat_messages.iter_mut().filter(|m| removed(m)) {
+            message.deleted = true;
+        }
     } else {
-        tracing::error!("Client ID not found, cannot fetch global emotes.");
+        tab.chat_messages.retain(|m| !removed(m));
     }
 }
 
-fn handle_chat_message(state: &mut AppState, msg: ChatEvent) {
+/// Applies a chat event to the tab it belongs to: the one whose connection it came
+/// from when `broadcaster_id` is set, otherwise the tab being shown. Events for a
+/// tab that was closed in the meantime only update what isn't tab-specific.
+fn handle_chat_message(
+    state: &mut AppState,
+    broadcaster_id: Option<&str>,
+    msg: ChatEvent,
+    config: &Config,
+    actions: &mut Vec<Action>,
+) {
     if let AppState::LoggedIn {
-        chat_messages,
-        users,
+        tabs,
+        active_tab,
         send_in_progress,
+        message_queue,
+        send_queued_until,
         last_error,
         message_to_send,
+        pending_raid,
+        user_login,
+        last_message_sound,
+        reauth_required,
+        session_expired,
+        unsent_message,
+        identities,
         ..
     } = state
     {
+        let index = match broadcaster_id {
+            Some(id) => tabs
+                .iter()
+                .position(|tab| tab.broadcaster_id.as_deref() == Some(id)),
+            None => Some(*active_tab).filter(|&i| i < tabs.len()),
+        };
+        let is_active = index == Some(*active_tab);
+        let tab = index.map(|i| &mut tabs[i]);
         match msg {
             ChatEvent::NewChatMessage(mut message) => {
-                if let Some(MessageFragment::Text(text)) = message.fragments.last_mut() {
-                    *text = text.trim_end().to_string();
+                let Some(tab) = tab else {
+                    return;
+                };
+                message.fragments = normalize_whitespace(
+                    message.fragments,
+                    config.collapse_emotes_for_active_profile(),
+                );
+                resolve_cheermotes(&mut message.fragments, &tab.cheermotes);
+
+                // Moderators get suspicious-user messages as a separate event with the
+                // same id as the regular one; merge rather than show it twice. The
+                // regular one has the emotes and color, so its content wins.
+                if let Some(existing) = tab
+                    .chat_messages
+                    .iter_mut()
+                    .rev()
+                    .take(DUPLICATE_LOOKBACK)
+                    .find(|m| m.id == message.id)
+                {
+                    let low_trust = existing.low_trust.or(message.low_trust);
+                    if message.low_trust.is_none() {
+                        *existing = message;
+                    }
+                    existing.low_trust = low_trust;
+                    return;
                 }
 
+                tab.activity.record(Instant::now());
+                // Ignored users' messages are kept but hidden, so they shouldn't
+                // draw attention either.
+                let is_ignored = config.is_ignored(&message.sender_name);
+                if !is_active && !is_ignored {
+                    tab.unread += 1;
+                }
+
+                tab.users.remove(&User {
+                    name: message.sender_name.clone(),
+                    color: None,
+                    has_spoken: false,
+                });
                 let user = User {
                     name: message.sender_name.clone(),
                     color: message.sender_color,
+                    has_spoken: true,
+                };
+                tab.users.insert(user);
+
+                let is_own_message = message.sender_name.eq_ignore_ascii_case(user_login)
+                    || identities.iter().any(|i| {
+                        message
+                            .sender_name
+                            .eq_ignore_ascii_case(i.token.login.as_str())
+                    });
+                if config.message_sound_enabled_for(&tab.channel) && !is_own_message && !is_ignored
+                {
+                    let min_interval =
+                        Duration::from_secs_f32(config.message_sound_min_interval_secs.max(0.0));
+                    if last_message_sound.is_none_or(|last| last.elapsed() >= min_interval) {
+                        *last_message_sound = Some(Instant::now());
+                        actions.push(Action::PlayMessageSound {
+                            volume: config.message_sound_volume,
+                        });
+                    }
+                }
+
+                if !is_ignored {
+                    let payload = config
+                        .webhook
+                        .payload_for_message(Some(&tab.channel), &message);
+                    push_webhook(actions, config, payload);
+                }
+
+                if config.chat_history_lines > 0 {
+                    actions.push(Action::AppendHistory {
+                        channel: tab.channel.clone(),
+                        message: message.clone(),
+                        max_lines: config.chat_history_lines,
+                    });
+                }
+
+                tab.chat_messages.push_back(message);
+            }
+            ChatEvent::MessageDeleted(message_id) => {
+                let Some(tab) = tab else {
+                    return;
+                };
+                let payload = config
+                    .webhook
+                    .payload_for_deletion(Some(&tab.channel), &message_id);
+                push_webhook(actions, config, payload);
+                remove_messages(tab, config, |m| m.id == message_id);
+            }
+            ChatEvent::UserMessagesCleared(user_id) => {
+                if let Some(tab) = tab {
+                    remove_messages(tab, config, |m| m.sender_id == user_id);
+                }
+            }
+            ChatEvent::ChatCleared => {
+                let Some(tab) = tab else {
+                    return;
                 };
-                users.insert(user);
-                chat_messages.push(message);
+                remove_messages(tab, config, |_| true);
+                tab.chat_messages.push_back(ChatMessage {
+                    id: String::new(),
+                    sender_id: String::new(),
+                    sender_name: String::new(),
+                    sender_color: None,
+                    fragments: Vec::new(),
+                    timestamp: Local::now(),
+                    deleted: false,
+                    is_action: false,
+                    kind: MessageKind::System {
+                        category: SystemCategory::Other,
+                        text: "Chat was cleared by a moderator".to_string(),
+                    },
+                    low_trust: None,
+                    reply_to: None,
+                });
             }
             ChatEvent::MessageSent => {
                 *send_in_progress = false;
+                *send_queued_until = None;
+                *unsent_message = None;
                 message_to_send.clear();
+                if let Some(tab) = tab {
+                    tab.replying_to = None;
+                }
             }
             ChatEvent::MessageSendError(err) => {
                 *send_in_progress = false;
+                *send_queued_until = None;
+                *last_error = Some(err);
+            }
+            ChatEvent::MessageQueued(wait) => {
+                let until = Instant::now() + wait;
+                message_queue.pause_until(until);
+                *send_queued_until = Some(until);
+            }
+            ChatEvent::RaidStarted(target) => {
+                *pending_raid = Some(PendingRaid {
+                    target,
+                    started_at: Instant::now(),
+                });
+            }
+            ChatEvent::RaidCancelled => {
+                *pending_raid = None;
+            }
+            ChatEvent::RaidError(err) => {
+                *pending_raid = None;
+                *last_error = Some(err);
+            }
+            ChatEvent::RaidCancelError(err) => {
+                *last_error = Some(err);
+            }
+            ChatEvent::RedemptionAdded(redemption) => {
+                if let Some(tab) = tab
+                    && !tab.redemptions.iter().any(|r| r.id == redemption.id)
+                {
+                    tab.redemptions.push(redemption);
+                }
+            }
+            ChatEvent::RedemptionResolved(redemption_id) => {
+                if let Some(tab) = tab {
+                    tab.redemptions.retain(|r| r.id != redemption_id);
+                }
+            }
+            ChatEvent::RedemptionError(err) => {
+                *last_error = Some(err);
+            }
+            ChatEvent::RawEvent { message_id, json } => {
+                if let Some(tab) = tab {
+                    if tab.raw_events.len() >= MAX_RAW_EVENTS {
+                        tab.raw_events.pop_front();
+                    }
+                    tab.raw_events.push_back((message_id, json));
+                }
+            }
+            ChatEvent::MessageHeld(message) => {
+                if let Some(tab) = tab
+                    && !tab.held_messages.iter().any(|m| m.id == message.id)
+                {
+                    tab.held_messages.push(message);
+                }
+            }
+            ChatEvent::HeldMessageResolved(message_id) => {
+                if let Some(tab) = tab {
+                    tab.held_messages.retain(|m| m.id != message_id);
+                }
+            }
+            ChatEvent::HeldMessageError(err) => {
                 *last_error = Some(err);
             }
             ChatEvent::EventSubError(err) => {
-                *last_error = Some(format!("Chat connection error: {}", err));
+                let channel = match tab {
+                    Some(tab) => {
+                        tab.reconnecting = None;
+                        format!(" in #{}", tab.channel)
+                    }
+                    None => String::new(),
+                };
+                *last_error = Some(format!("Chat connection error{}: {}", channel, err));
+            }
+            ChatEvent::Connecting => {
+                if let Some(tab) = tab {
+                    tab.connection_status = ConnectionStatus::Connecting;
+                }
+            }
+            ChatEvent::Connected => {
+                if let Some(tab) = tab {
+                    tab.connection_status = ConnectionStatus::Connected;
+                }
+            }
+            ChatEvent::Disconnected => {
+                if let Some(tab) = tab {
+                    tab.connection_status = ConnectionStatus::Disconnected;
+                }
+            }
+            ChatEvent::Reconnecting {
+                attempt,
+                max_attempts,
+            } => {
+                if let Some(tab) = tab {
+                    tab.reconnecting = Some((attempt, max_attempts));
+                    tab.connection_status = ConnectionStatus::Connecting;
+                }
+            }
+            ChatEvent::Reconnected => {
+                if let Some(tab) = tab {
+                    tab.reconnecting = None;
+                }
+            }
+            ChatEvent::ModeratorConfirmed => {
+                if let Some(tab) = tab {
+                    tab.is_moderator = true;
+                }
+            }
+            ChatEvent::UserBanned(user_name) => {
+                tracing::info!("Banned {}", user_name);
+            }
+            ChatEvent::ModerationError(err) => {
+                *last_error = Some(err);
+            }
+            ChatEvent::ChattersLoaded(chatters) => {
+                if let Some(tab) = tab {
+                    // People who have spoken stay listed after leaving; the rest is
+                    // replaced by the new list.
+                    tab.users.retain(|user| user.has_spoken);
+                    let spoken: HashSet<String> =
+                        tab.users.iter().map(|user| user.name.clone()).collect();
+                    tab.users.extend(
+                        chatters
+                            .into_iter()
+                            .filter(|name| !spoken.contains(name))
+                            .map(|name| User {
+                                name,
+                                color: None,
+                                has_spoken: false,
+                            }),
+                    );
+                }
+            }
+            ChatEvent::SessionExpired => {
+                *send_in_progress = false;
+                *send_queued_until = None;
+                *reauth_required = true;
+                *session_expired = true;
+                *last_error = Some("Your Twitch session expired, please log in again.".into());
+            }
+            ChatEvent::SubscriptionRevoked(reason) => {
+                let explanation = match reason.as_str() {
+                    "authorization_revoked" => {
+                        *reauth_required = true;
+                        "access to your Twitch account was revoked, please log in again"
+                    }
+                    "user_removed" => "the channel is no longer available",
+                    "moderator_removed" => "you are no longer a moderator of this channel",
+                    "version_removed" => "Twitch no longer supports this kind of subscription",
+                    _ => "Twitch ended the subscription",
+                };
+                *last_error = Some(format!(
+                    "Disconnected from chat: {} ({}).",
+                    explanation, reason
+                ));
+            }
+            ChatEvent::ChannelNotFound(_)
+            | ChatEvent::ChannelLookupFailed { .. }
+            | ChatEvent::ChannelJoined { .. } => {
+                // These name their channel and are handled before routing.
             }
-        }
-        if chat_messages.len() > 200 {
-            chat_messages.remove(0);
         }
     }
 }
@@ -1,14 +1,126 @@
 use crate::{
-    core::chat::ChatClient,
-    emotes::twitch_api::TwitchEmote,
-    models::{message::ChatMessage, user::User},
+    core::chat::{ChatApi, ChatClient, MessageQueue},
+    emotes::twitch_api::{Cheermote, TwitchEmote},
+    features::{activity::ActivityMeter, automod::HeldMessage, channel_points::Redemption},
+    models::{
+        emote::Emote,
+        message::{ChatMessage, ReplyInfo},
+        user::User,
+    },
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+    time::Instant,
 };
-use std::{collections::HashSet, sync::Arc};
 use tokio::task::JoinHandle;
 use twitch_oauth2::UserToken;
 use twitch_types::UserId;
 
+/// How long Twitch waits before carrying out a raid once it has been started.
+pub const RAID_COUNTDOWN_SECS: u64 = 90;
+
+/// How many raw events are kept for "Show raw event" when developer tools are on.
+pub const MAX_RAW_EVENTS: usize = 200;
+
+/// A raid that has been started but not yet carried out by Twitch.
+pub struct PendingRaid {
+    pub target: String,
+    pub started_at: Instant,
+}
+
+/// An additional account, loaded from another profile's saved token, that messages
+/// can be sent as while chat is still read through the primary login.
+pub struct Identity {
+    pub profile_name: String,
+    pub token: Arc<UserToken>,
+}
+
+/// Where a channel's chat connection is, for the indicator in the top panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConnectionStatus {
+    /// Joining, or reconnecting after the connection dropped.
+    #[default]
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+/// A joined channel, shown as a tab. Each tab has its own chat connection, and
+/// keeps receiving messages while another one is shown.
+#[derive(Default)]
+pub struct ChannelTab {
+    pub channel: String,
+    /// Set once the join has looked up the channel's id; events from its chat
+    /// connection are routed by it.
+    pub broadcaster_id: Option<String>,
+    pub chat_messages: VecDeque<ChatMessage>,
+    /// How fast messages are arriving, for pausing animated emotes in busy chat.
+    pub activity: ActivityMeter,
+    pub users: HashSet<User>,
+    /// Twitch emotes belonging to the channel.
+    pub channel_emotes: Vec<TwitchEmote>,
+    /// BetterTTV, FrankerFaceZ and 7TV emotes usable in the channel, in the order
+    /// [`crate::emotes::third_party_emotes`] returns them: later ones win a shared code.
+    pub third_party_emotes: Vec<Emote>,
+    /// Cheer prefixes usable in the channel, for drawing cheers as their images.
+    pub cheermotes: Vec<Cheermote>,
+    pub eventsub_task: Option<JoinHandle<()>>,
+    /// Whether the user moderates the channel; gates moderator-only UI.
+    pub is_moderator: bool,
+    /// `(message id, pretty-printed JSON)` of recent notifications, oldest first.
+    /// Only filled while developer tools are enabled.
+    pub raw_events: VecDeque<(String, String)>,
+    /// Messages AutoMod is holding for review; only filled for moderators.
+    pub held_messages: Vec<HeldMessage>,
+    pub redemptions: Vec<Redemption>,
+    pub connection_status: ConnectionStatus,
+    /// `(attempt, max attempts)` while a dropped chat connection is being retried.
+    pub reconnecting: Option<(u32, u32)>,
+    /// Messages that arrived since the tab was last shown.
+    pub unread: usize,
+    /// Only messages to and from this user are shown, after clicking their mention.
+    pub mention_filter: Option<String>,
+    /// The message the next one sent is a reply to, picked from its context menu.
+    pub replying_to: Option<ReplyInfo>,
+}
+
+impl ChannelTab {
+    pub fn new(channel: String) -> Self {
+        Self {
+            channel,
+            ..Self::default()
+        }
+    }
+
+    /// Everyone seen in chat, those who spoke most recently first and the rest in
+    /// alphabetical order.
+    pub fn recent_chatters(&self) -> Vec<&str> {
+        let mut chatters: Vec<&str> = Vec::new();
+        for message in self.chat_messages.iter().rev() {
+            let name = message.sender_name.as_str();
+            if !name.is_empty() && !chatters.contains(&name) {
+                chatters.push(name);
+            }
+        }
+        let mut others: Vec<&str> = self
+            .users
+            .iter()
+            .map(|user| user.name.as_str())
+            .filter(|name| !chatters.contains(name))
+            .collect();
+        others.sort_by_key(|name| name.to_lowercase());
+        others.dedup();
+        chatters.extend(others);
+        chatters
+    }
+}
+
 /// Represents the various states of the application's lifecycle.
+///
+/// Only one of these exists and it is replaced in place, so `LoggedIn` being far
+/// bigger than the other variants costs nothing worth boxing it for.
+#[allow(clippy::large_enum_variant)]
 pub enum AppState {
     Startup {
         task_spawned: bool,
@@ -36,14 +148,155 @@ pub enum AppState {
         user_id: UserId,
         user_login: String,
         channel_to_join: String,
-        current_channel: Option<String>,
+        /// Joined channels in the order their tabs are shown.
+        tabs: Vec<ChannelTab>,
+        /// Index into `tabs` of the one being shown; meaningless while there are none.
+        active_tab: usize,
+        /// Text typed into the search box above the chat log; empty shows everything.
+        chat_filter: String,
         message_to_send: String,
-        chat_messages: Vec<ChatMessage>,
-        users: HashSet<User>,
         global_emotes: Vec<TwitchEmote>,
-        chat_client: ChatClient,
+        /// Set once the global emote request finished, whether or not it succeeded.
+        global_emotes_loaded: bool,
+        chat_client: Arc<dyn ChatApi>,
         send_in_progress: bool,
+        /// Paces sends to stay under Twitch's chat rate limit.
+        message_queue: MessageQueue,
+        /// When a message being sent is held back for the rate limit, when it goes out.
+        send_queued_until: Option<Instant>,
         last_error: Option<String>,
-        eventsub_task: Option<JoinHandle<()>>,
+        pending_raid: Option<PendingRaid>,
+        last_message_sound: Option<Instant>,
+        reauth_required: bool,
+        /// Show the "session expired" prompt; cleared when dismissed, while
+        /// `reauth_required` keeps the error panel's log-in button around.
+        session_expired: bool,
+        status_line: Option<String>,
+        /// Text of the last send attempt, kept until it succeeds so it can be retried.
+        unsent_message: Option<String>,
+        identities: Vec<Identity>,
+        /// Profile name of the identity to send as; `None` sends as the primary login.
+        send_as: Option<String>,
+        /// Channels to join once the UI is up: the previous session's or the default.
+        pending_joins: Vec<String>,
     },
 }
+
+impl AppState {
+    /// A freshly logged-in state with no channel joined yet.
+    pub fn logged_in(token: Arc<UserToken>) -> Self {
+        Self::logged_in_with(token, Arc::new(ChatClient::new()))
+    }
+
+    /// Like [`AppState::logged_in`], but talking to Twitch through `chat_client`.
+    pub fn logged_in_with(token: Arc<UserToken>, chat_client: Arc<dyn ChatApi>) -> Self {
+        AppState::LoggedIn {
+            user_id: token.user_id.clone(),
+            user_login: token.login.to_string(),
+            token,
+            channel_to_join: String::new(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            chat_filter: String::new(),
+            message_to_send: String::new(),
+            global_emotes: Vec::new(),
+            global_emotes_loaded: false,
+            chat_client,
+            send_in_progress: false,
+            message_queue: MessageQueue::default(),
+            send_queued_until: None,
+            last_error: None,
+            pending_raid: None,
+            last_message_sound: None,
+            reauth_required: false,
+            session_expired: false,
+            status_line: None,
+            unsent_message: None,
+            identities: Vec::new(),
+            send_as: None,
+            pending_joins: Vec::new(),
+        }
+    }
+
+    /// The tab being shown, if logged in with any channel joined.
+    pub fn active_tab(&self) -> Option<&ChannelTab> {
+        match self {
+            AppState::LoggedIn {
+                tabs, active_tab, ..
+            } => tabs.get(*active_tab),
+            _ => None,
+        }
+    }
+
+    pub fn active_tab_mut(&mut self) -> Option<&mut ChannelTab> {
+        match self {
+            AppState::LoggedIn {
+                tabs, active_tab, ..
+            } => tabs.get_mut(*active_tab),
+            _ => None,
+        }
+    }
+
+    /// The login of the channel being shown.
+    pub fn current_channel(&self) -> Option<&str> {
+        self.active_tab().map(|tab| tab.channel.as_str())
+    }
+
+    /// Shows the tab for `channel`, opening a new one if it isn't joined yet.
+    /// Returns whether a tab was opened.
+    pub fn open_tab(&mut self, channel: &str) -> bool {
+        let AppState::LoggedIn {
+            tabs, active_tab, ..
+        } = self
+        else {
+            return false;
+        };
+        match tabs.iter().position(|tab| tab.channel == channel) {
+            Some(index) => {
+                self.select_tab(index);
+                false
+            }
+            None => {
+                tabs.push(ChannelTab::new(channel.to_string()));
+                *active_tab = tabs.len() - 1;
+                true
+            }
+        }
+    }
+
+    pub fn select_tab(&mut self, index: usize) {
+        if let AppState::LoggedIn {
+            tabs, active_tab, ..
+        } = self
+            && let Some(tab) = tabs.get_mut(index)
+        {
+            tab.unread = 0;
+            *active_tab = index;
+        }
+    }
+
+    /// Removes the tab at `index` and stops its chat connection. Closing the tab
+    /// being shown shows the one that took its place, or the new last one.
+    pub fn close_tab(&mut self, index: usize) -> Option<ChannelTab> {
+        let AppState::LoggedIn {
+            tabs, active_tab, ..
+        } = self
+        else {
+            return None;
+        };
+        if index >= tabs.len() {
+            return None;
+        }
+        let tab = tabs.remove(index);
+        if let Some(task) = &tab.eventsub_task {
+            task.abort();
+        }
+        if index < *active_tab || *active_tab >= tabs.len() {
+            *active_tab = active_tab.saturating_sub(1);
+        }
+        if let Some(tab) = tabs.get_mut(*active_tab) {
+            tab.unread = 0;
+        }
+        Some(tab)
+    }
+}
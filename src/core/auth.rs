@@ -1,5 +1,7 @@
-use crate::events::app_event::AppEvent;
-use dirs;
+use crate::{
+    core::{proxy, storage},
+    events::app_event::{AppEvent, ChatEvent},
+};
 use eyre::{Context, eyre};
 use http_body_util::Full;
 use hyper::{
@@ -12,7 +14,7 @@ use hyper_util::rt::TokioIo;
 use reqwest::Client as ReqwestClient;
 use serde::{Deserialize, Serialize};
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -21,14 +23,75 @@ use tokio::{
     net::TcpListener,
     sync::{mpsc, oneshot},
 };
-use twitch_oauth2::{AccessToken, RefreshToken, UserToken, UserTokenBuilder};
+use twitch_oauth2::{AccessToken, RefreshToken, Scope, TwitchToken, UserToken, UserTokenBuilder};
 use url::{Url, form_urlencoded};
 
 const APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 const TOKEN_FILE_NAME: &str = "token.json";
+/// How long before it expires a token is refreshed, or a new login asked for.
+const REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+/// Twitch asks apps to validate their tokens hourly, which also notices revoked ones.
+const VALIDATE_INTERVAL: Duration = Duration::from_secs(60 * 60);
 const REDIRECT_URI: &str = "http://localhost:3000";
 const HTML_LANDING_PAGE: &str = "<html><head><title>LiveNAC Authentication</title></head><body>Success! You can close this window now.</body></html>";
 
+/// Everything the app's features need.
+pub fn default_scopes() -> Vec<Scope> {
+    vec![
+        Scope::ChatRead,
+        Scope::ChatEdit,
+        Scope::UserReadChat,
+        Scope::UserWriteChat,
+        Scope::ModeratorManageAnnouncements,
+        Scope::ModeratorReadChatters,
+        Scope::UserReadEmotes,
+        Scope::ChannelManageRaids,
+        Scope::ChannelManageRedemptions,
+        Scope::ModeratorReadSuspiciousUsers,
+        Scope::ModeratorManageBannedUsers,
+        Scope::ModeratorManageChatMessages,
+        Scope::ModeratorManageAutoMod,
+        Scope::UserManageWhispers,
+    ]
+}
+
+/// Just enough to read and send chat; moderation and channel features won't work.
+pub fn minimal_scopes() -> Vec<Scope> {
+    vec![
+        Scope::ChatRead,
+        Scope::ChatEdit,
+        Scope::UserReadChat,
+        Scope::UserWriteChat,
+    ]
+}
+
+/// Parses a scope name such as `moderator:read:followers`, rejecting names
+/// `twitch_oauth2` doesn't know about.
+pub fn parse_scope(name: &str) -> Result<Scope, eyre::Report> {
+    match Scope::parse(name.trim().to_string()) {
+        Scope::Other(_) => Err(eyre!("Unknown scope '{}'", name.trim())),
+        scope => Ok(scope),
+    }
+}
+
+/// The scopes to ask for: the default or minimal set plus `extra`. Unknown extra
+/// scopes are skipped with a warning rather than failing the login.
+pub fn requested_scopes(extra: &[String], minimal: bool) -> Vec<Scope> {
+    let mut scopes = if minimal {
+        minimal_scopes()
+    } else {
+        default_scopes()
+    };
+    for name in extra.iter().filter(|name| !name.trim().is_empty()) {
+        match parse_scope(name) {
+            Ok(scope) if !scopes.contains(&scope) => scopes.push(scope),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Ignoring extra scope: {}", e),
+        }
+    }
+    scopes
+}
+
 #[derive(Debug)]
 pub enum AuthMessage {
     Success(UserToken),
@@ -44,10 +107,12 @@ struct StoredToken {
 #[derive(Clone)]
 pub struct AuthClient {
     reqwest_client: ReqwestClient,
-    scopes: Vec<twitch_oauth2::Scope>,
+    scopes: Vec<Scope>,
     client_id: twitch_oauth2::ClientId,
     client_secret: twitch_oauth2::ClientSecret,
-    data_path: PathBuf,
+    /// `None` when running without a writable config directory; tokens then only
+    /// last for the session.
+    data_path: Option<PathBuf>,
     // No longer needed for interactive flow, but might be useful for silent flow errors.
     ui_message_tx: mpsc::Sender<AppEvent>,
     active_profile_name: Option<String>,
@@ -59,30 +124,14 @@ impl AuthClient {
         client_secret: String,
         ui_message_tx: mpsc::Sender<AppEvent>,
         active_profile_name: Option<String>,
+        scopes: Vec<Scope>,
     ) -> Result<Self, eyre::Report> {
-        let reqwest_client = ReqwestClient::builder()
+        let reqwest_client = proxy::client_builder()
             .user_agent(APP_USER_AGENT)
             .timeout(Duration::from_secs(15))
             .build()?;
 
-        let scopes = vec![
-            twitch_oauth2::Scope::ChatRead,
-            twitch_oauth2::Scope::ChatEdit,
-            twitch_oauth2::Scope::UserReadChat,
-            twitch_oauth2::Scope::UserWriteChat,
-            twitch_oauth2::Scope::ModeratorManageAnnouncements,
-            twitch_oauth2::Scope::ModeratorReadChatters,
-            twitch_oauth2::Scope::UserReadEmotes,
-        ];
-
-        let data_path = dirs::config_dir()
-            .ok_or_else(|| eyre!("Could not find a config directory"))?
-            .join(env!("CARGO_PKG_NAME"));
-        if !data_path.exists() {
-            tokio::fs::create_dir_all(&data_path)
-                .await
-                .context("Failed to create config directory")?;
-        }
+        let data_path = storage::data_dir().map(Path::to_path_buf);
 
         Ok(Self {
             reqwest_client,
@@ -151,6 +200,10 @@ impl AuthClient {
             refresh_token: token.refresh_token.clone(),
         };
         let bytes = serde_json::to_vec_pretty(&stored_token)?;
+        if self.data_path.is_none() {
+            tracing::debug!("Not saving token, running without a config directory");
+            return Ok(());
+        }
         let path = self.get_token_path()?;
         if let Some(parent) = path.parent() {
             if !parent.exists() {
@@ -162,6 +215,64 @@ impl AuthClient {
         Ok(())
     }
 
+    /// Keeps `token` usable for the rest of the session: it is validated hourly and
+    /// refreshed a few minutes before it expires, each new token being saved and
+    /// sent as [`AppEvent::TokenRefreshed`]. Once that fails the user is asked to
+    /// log in again with [`ChatEvent::SessionExpired`], before chat stops working.
+    pub async fn watch_token(self, mut token: UserToken) {
+        loop {
+            let wait = token
+                .expires_in()
+                .saturating_sub(REFRESH_MARGIN)
+                .min(VALIDATE_INTERVAL);
+            tokio::time::sleep(wait).await;
+
+            let result = if token.expires_in() <= REFRESH_MARGIN {
+                match token.refresh_token(&self.reqwest_client).await {
+                    Ok(()) => {
+                        tracing::info!("Refreshed the Twitch token");
+                        if let Err(e) = self.save_token(&token).await {
+                            tracing::warn!("Failed to save refreshed token: {}", e);
+                        }
+                        let event = AppEvent::TokenRefreshed(token.clone());
+                        if self.ui_message_tx.send(event).await.is_err() {
+                            return;
+                        }
+                        Ok(())
+                    }
+                    Err(e) => Err(eyre!("Failed to refresh token: {}", e)),
+                }
+            } else {
+                token
+                    .validate_token(&self.reqwest_client)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| eyre!("Token no longer valid: {}", e))
+            };
+            if let Err(e) = result {
+                tracing::warn!("{}", e);
+                let _ = self
+                    .ui_message_tx
+                    .send(AppEvent::Chat(ChatEvent::SessionExpired))
+                    .await;
+                return;
+            }
+        }
+    }
+
+    /// Forgets the active profile's stored token, so it has to log in again.
+    pub async fn delete_token(&self) -> Result<(), eyre::Report> {
+        if self.data_path.is_none() {
+            return Ok(());
+        }
+        let path = self.get_token_path()?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("Failed to delete token {:?}", path)),
+        }
+    }
+
     async fn load_and_validate_token(&self) -> Result<UserToken, eyre::Report> {
         tracing::info!("Attempting to load token from disk...");
         let stored = self.load_token_from_disk().await?;
@@ -175,6 +286,20 @@ impl AuthClient {
         .await
         .context("Failed to validate and/or refresh token")?;
 
+        // A token from before the scope settings changed has to be replaced by a
+        // fresh login that asks for the new set.
+        let missing: Vec<&Scope> = self
+            .scopes
+            .iter()
+            .filter(|scope| !token.scopes().contains(scope))
+            .collect();
+        if !missing.is_empty() {
+            return Err(eyre!(
+                "Stored token is missing scopes {:?}, logging in again",
+                missing
+            ));
+        }
+
         tracing::info!("Token is valid.");
         Ok(token)
     }
@@ -184,8 +309,11 @@ impl AuthClient {
             .active_profile_name
             .as_ref()
             .ok_or_else(|| eyre!("Cannot get token path, no active profile"))?;
-        Ok(self
+        let data_path = self
             .data_path
+            .as_ref()
+            .ok_or_else(|| eyre!("No stored tokens, running without a config directory"))?;
+        Ok(data_path
             .join("profiles")
             .join(profile_name)
             .join(TOKEN_FILE_NAME))
@@ -1,19 +1,224 @@
-use eyre::Report;
-use reqwest::{Client as ReqwestClient, header};
-use twitch_api::helix::HelixClient;
+use crate::core::proxy;
+use async_trait::async_trait;
+use eyre::{Report, eyre};
+use reqwest::{Client as ReqwestClient, StatusCode, header};
+use serde::Deserialize;
+use std::{
+    fmt,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use twitch_api::helix::chat::get_chatters::GetChattersRequest;
 use twitch_api::helix::chat::send_chat_announcement::{
     SendChatAnnouncementBody, SendChatAnnouncementRequest,
 };
-use twitch_api::helix::chat::send_chat_message::{SendChatMessageBody, SendChatMessageRequest};
+use twitch_api::helix::chat::send_chat_message::SendChatMessageBody;
+use twitch_api::helix::moderation::{
+    BanUserBody, BanUserRequest, DeleteChatMessagesRequest, ManageHeldAutoModMessagesBody,
+    ManageHeldAutoModMessagesRequest,
+};
+use twitch_api::helix::points::{
+    CustomRewardRedemptionStatus, UpdateRedemptionStatusBody, UpdateRedemptionStatusRequest,
+};
+use twitch_api::helix::raids::{CancelARaidRequest, StartARaidRequest};
 use twitch_api::helix::users::GetUsersRequest;
-use twitch_oauth2::UserToken;
+use twitch_api::helix::whispers::{SendWhisperBody, SendWhisperRequest};
+use twitch_api::helix::{
+    ClientRequestError, EmptyBody, HelixClient, HelixRequestDeleteError, HelixRequestGetError,
+    HelixRequestPatchError, HelixRequestPostError, HelixRequestPutError,
+};
+use twitch_api::types::{RedemptionId, RewardId};
+use twitch_oauth2::{TwitchToken, UserToken};
 use twitch_types::{UserId, UserIdRef};
 
+/// Whether a failed call was rejected with 401, i.e. the token expired or was
+/// revoked.
+pub fn is_unauthorized(error: &Report) -> bool {
+    has_status(error, StatusCode::UNAUTHORIZED)
+}
+
+/// Whether a failed call, or anything it was wrapped around, got a response with
+/// `status`.
+pub fn has_status(error: &Report, status: StatusCode) -> bool {
+    error
+        .chain()
+        .any(|cause| response_status(cause) == Some(status.as_u16()))
+}
+
+/// The status of the response a request failed on, if it got one. Helix calls fail
+/// with [`ClientRequestError`], which has an error type per request method; calls
+/// made with reqwest directly fail with a [`reqwest::Error`].
+fn response_status(error: &(dyn std::error::Error + 'static)) -> Option<u16> {
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        return error.status().map(|status| status.as_u16());
+    }
+    let status = match error.downcast_ref::<ClientRequestError<reqwest::Error>>()? {
+        ClientRequestError::HelixRequestGetError(
+            HelixRequestGetError::Error { status, .. }
+            | HelixRequestGetError::InvalidResponse { status, .. },
+        )
+        | ClientRequestError::HelixRequestPutError(
+            HelixRequestPutError::Error { status, .. }
+            | HelixRequestPutError::InvalidResponse { status, .. },
+        )
+        | ClientRequestError::HelixRequestPostError(
+            HelixRequestPostError::Error { status, .. }
+            | HelixRequestPostError::InvalidResponse { status, .. },
+        )
+        | ClientRequestError::HelixRequestPatchError(
+            HelixRequestPatchError::Error { status, .. }
+            | HelixRequestPatchError::InvalidResponse { status, .. },
+        )
+        | ClientRequestError::HelixRequestDeleteError(
+            HelixRequestDeleteError::Error { status, .. }
+            | HelixRequestDeleteError::InvalidResponse { status, .. },
+        ) => status,
+        _ => return None,
+    };
+    Some(status.as_u16())
+}
+
+/// Twitch's whisper limit when the recipient has never whispered the sender.
+pub const MAX_NEW_WHISPER_CHARS: usize = 500;
+/// Twitch's whisper limit within an existing conversation.
+pub const MAX_WHISPER_CHARS: usize = 10_000;
+
+/// Rejects whispers Twitch would refuse for their length whatever the conversation.
+/// Whether the shorter limit for new conversations applies is only known to Twitch,
+/// so messages between the two limits are sent and left for Twitch to judge.
+pub fn check_whisper_length(message: &str) -> Result<(), Report> {
+    let length = message.chars().count();
+    if length == 0 {
+        return Err(eyre!("Can't send an empty whisper"));
+    }
+    if length > MAX_WHISPER_CHARS {
+        return Err(eyre!(
+            "Whispers can be at most {} characters long, this one has {}",
+            MAX_WHISPER_CHARS,
+            length
+        ));
+    }
+    Ok(())
+}
+
+/// Chat messages a user who doesn't moderate the channel may send per
+/// [`RATE_LIMIT_WINDOW`].
+pub const USER_MESSAGES_PER_WINDOW: u32 = 20;
+/// Chat messages moderators and the broadcaster may send per [`RATE_LIMIT_WINDOW`].
+pub const MODERATOR_MESSAGES_PER_WINDOW: u32 = 100;
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(30);
+/// Sends that would have to wait longer than this are refused rather than queued.
+pub const MAX_QUEUE_DELAY: Duration = Duration::from_secs(10);
+
+/// A token bucket following Twitch's chat rate limit, so a burst of sends waits its
+/// turn instead of being refused by Twitch. Sent messages count against the limit
+/// and drain away evenly over [`RATE_LIMIT_WINDOW`].
+#[derive(Clone, Debug, Default)]
+pub struct MessageQueue {
+    used: f64,
+    updated: Option<Instant>,
+    /// Set after Twitch refused a message, until its limit resets.
+    paused_until: Option<Instant>,
+}
+
+impl MessageQueue {
+    /// Takes a slot for a message sent at `now` under a limit of `per_window`
+    /// messages. Returns how long to hold the message back, or, when that would be
+    /// longer than [`MAX_QUEUE_DELAY`], the wait as an error and takes nothing.
+    pub fn reserve(&mut self, now: Instant, per_window: u32) -> Result<Duration, Duration> {
+        let per_second = f64::from(per_window) / RATE_LIMIT_WINDOW.as_secs_f64();
+        if let Some(updated) = self.updated {
+            let drained = now.saturating_duration_since(updated).as_secs_f64() * per_second;
+            self.used = (self.used - drained).max(0.0);
+        }
+        self.updated = Some(now);
+
+        let over = self.used + 1.0 - f64::from(per_window);
+        let mut delay = if over > 0.0 {
+            Duration::from_secs_f64(over / per_second)
+        } else {
+            Duration::ZERO
+        };
+        if let Some(until) = self.paused_until {
+            delay = delay.max(until.saturating_duration_since(now));
+        }
+        if delay > MAX_QUEUE_DELAY {
+            return Err(delay);
+        }
+        self.used += 1.0;
+        Ok(delay)
+    }
+
+    /// Holds back every message until `until`, after Twitch refused one.
+    pub fn pause_until(&mut self, until: Instant) {
+        self.paused_until = Some(until);
+    }
+}
+
+/// Twitch refused a message for going over the chat rate limit.
+#[derive(Debug)]
+pub struct RateLimited {
+    /// When Twitch accepts messages again, from the `Ratelimit-Reset` header.
+    pub reset_at: Option<SystemTime>,
+}
+
+impl RateLimited {
+    /// How long from `now` until the limit resets, if Twitch said.
+    pub fn retry_after(&self, now: SystemTime) -> Option<Duration> {
+        self.reset_at
+            .map(|reset_at| reset_at.duration_since(now).unwrap_or_default())
+    }
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sending messages too fast, wait a moment and try again")
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Reads a `Ratelimit-Reset` header, the Unix time in seconds when the limit resets.
+pub fn parse_ratelimit_reset(value: &str) -> Option<SystemTime> {
+    let secs = value.trim().parse::<u64>().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// The [`RateLimited`] error for a response Twitch refused with 429, or `None` for
+/// any other status.
+pub fn rate_limit_error(status: StatusCode, headers: &header::HeaderMap) -> Option<RateLimited> {
+    if status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let reset_at = headers
+        .get("Ratelimit-Reset")
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_ratelimit_reset);
+    Some(RateLimited { reset_at })
+}
+
+#[derive(Deserialize)]
+struct SendChatMessageResponse {
+    data: Vec<SentChatMessage>,
+}
+
+#[derive(Deserialize)]
+struct SentChatMessage {
+    is_sent: bool,
+    drop_reason: Option<DropReason>,
+}
+
+#[derive(Deserialize)]
+struct DropReason {
+    message: String,
+}
+
 const APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
 #[derive(Clone, Default)]
 pub struct ChatClient {
     helix_client: HelixClient<'static, ReqwestClient>,
+    /// For calls whose response headers are needed, which `helix_client` drops.
+    http_client: ReqwestClient,
 }
 
 #[derive(Debug, Clone)]
@@ -45,51 +250,182 @@ impl ChatClient {
             header::USER_AGENT,
             header::HeaderValue::from_static(APP_USER_AGENT),
         );
-        let client = ReqwestClient::builder()
+        let client = proxy::client_builder()
             .default_headers(headers)
             .build()
             .expect("Failed to build reqwest client");
 
         Self {
-            helix_client: HelixClient::with_client(client),
+            helix_client: HelixClient::with_client(client.clone()),
+            http_client: client,
         }
     }
+}
 
+/// The Helix calls the app makes on the user's behalf.
+///
+/// The UI holds this as `Arc<dyn ChatApi>` so the network-backed [`ChatClient`]
+/// can be swapped for [`crate::core::mock::MockChatApi`] in tests and `--mock` mode.
+#[async_trait]
+pub trait ChatApi: Send + Sync {
     /// Fetches a user's ID from their login name.
-    pub async fn get_user_id(
+    async fn get_user_id(&self, login: &str, token: &UserToken) -> Result<Option<UserId>, Report>;
+
+    /// Sends a regular chat message to a channel, as a reply to the message with id
+    /// `reply_parent_id` if set.
+    async fn send_chat_message(
+        &self,
+        broadcaster_id: &UserIdRef,
+        sender_id: &UserIdRef,
+        message: &str,
+        reply_parent_id: Option<&str>,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Sends an announcement message to a channel.
+    /// Requires broadcaster or moderator privileges.
+    async fn send_announcement(
+        &self,
+        broadcaster_id: &UserIdRef,
+        moderator_id: &UserIdRef,
+        message: &str,
+        color: Option<AnnouncementColor>,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Raids another channel from the broadcaster's channel.
+    async fn start_raid(
+        &self,
+        from_broadcaster_id: &UserIdRef,
+        to_broadcaster_id: &UserIdRef,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Cancels a pending raid before Twitch carries it out.
+    async fn cancel_raid(
         &self,
-        login: &str,
+        broadcaster_id: &UserIdRef,
         token: &UserToken,
-    ) -> Result<Option<UserId>, Report> {
+    ) -> Result<(), Report>;
+
+    /// Marks a channel points redemption as fulfilled or rejected (refunding the viewer).
+    async fn update_redemption_status(
+        &self,
+        broadcaster_id: &UserIdRef,
+        reward_id: &str,
+        redemption_id: &str,
+        fulfilled: bool,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Bans a user from the channel, or times them out for `duration_secs`.
+    async fn ban_user(
+        &self,
+        broadcaster_id: &UserIdRef,
+        moderator_id: &UserIdRef,
+        user_id: &UserIdRef,
+        reason: &str,
+        duration_secs: Option<u32>,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Allows or denies a message AutoMod is holding.
+    async fn resolve_held_message(
+        &self,
+        moderator_id: &UserIdRef,
+        message_id: &str,
+        allow: bool,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Deletes every message in the channel's chat.
+    async fn clear_chat(
+        &self,
+        broadcaster_id: &UserIdRef,
+        moderator_id: &UserIdRef,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Sends a private message to another user.
+    async fn send_whisper(
+        &self,
+        from_user_id: &UserIdRef,
+        to_user_id: &UserIdRef,
+        message: &str,
+        token: &UserToken,
+    ) -> Result<(), Report>;
+
+    /// Display names of everyone connected to the channel's chat.
+    /// Requires broadcaster or moderator privileges.
+    async fn get_chatters(
+        &self,
+        broadcaster_id: &UserIdRef,
+        moderator_id: &UserIdRef,
+        token: &UserToken,
+    ) -> Result<Vec<String>, Report>;
+}
+
+#[async_trait]
+impl ChatApi for ChatClient {
+    /// Fetches a user's ID from their login name.
+    async fn get_user_id(&self, login: &str, token: &UserToken) -> Result<Option<UserId>, Report> {
         let logins: &[&str] = &[login];
         let request = GetUsersRequest::logins(logins);
         let response = self.helix_client.req_get(request, token).await?.data;
         Ok(response.into_iter().next().map(|u| u.id))
     }
 
-    /// Sends a regular chat message to a channel using the Helix API.
-    pub async fn send_chat_message(
+    /// Sends a regular chat message to a channel using the Helix API. The request is
+    /// made directly so a rate-limited send can report when to retry, as
+    /// [`RateLimited`].
+    async fn send_chat_message(
         &self,
         broadcaster_id: &UserIdRef,
         sender_id: &UserIdRef,
         message: &str,
+        reply_parent_id: Option<&str>,
         token: &UserToken,
     ) -> Result<(), Report> {
-        let request = SendChatMessageRequest::new();
-        let body = SendChatMessageBody::new(
+        let mut body = SendChatMessageBody::new(
             broadcaster_id.to_string(),
             sender_id.to_string(),
             message.to_string(),
         );
+        if let Some(parent_id) = reply_parent_id {
+            body = body.reply_parent_message_id(parent_id.to_string());
+        }
 
-        let response = self.helix_client.req_post(request, body, token).await?;
-        tracing::info!("Message sent successfully: {:?}", response.data);
-        Ok(())
+        let response = self
+            .http_client
+            .post("https://api.twitch.tv/helix/chat/messages")
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", token.access_token.as_str()),
+            )
+            .header("Client-Id", token.client_id().as_str())
+            .json(&body)
+            .send()
+            .await?;
+        if let Some(limited) = rate_limit_error(response.status(), response.headers()) {
+            return Err(limited.into());
+        }
+        let response: SendChatMessageResponse = response.error_for_status()?.json().await?;
+        match response.data.into_iter().next() {
+            Some(sent) if !sent.is_sent => Err(eyre!(
+                "Twitch dropped the message: {}",
+                sent.drop_reason
+                    .map_or_else(|| "no reason given".to_string(), |reason| reason.message)
+            )),
+            _ => {
+                tracing::info!("Message sent successfully");
+                Ok(())
+            }
+        }
     }
 
     /// Sends an announcement message to a channel using the Helix API.
     /// Requires broadcaster or moderator privileges.
-    pub async fn send_announcement(
+    async fn send_announcement(
         &self,
         broadcaster_id: &UserIdRef,
         moderator_id: &UserIdRef,
@@ -109,4 +445,152 @@ impl ChatClient {
         tracing::info!("Announcement sent successfully: {:?}", response.data);
         Ok(())
     }
+
+    /// Raids another channel from the broadcaster's channel.
+    /// Twitch only allows this with the broadcaster's own token.
+    async fn start_raid(
+        &self,
+        from_broadcaster_id: &UserIdRef,
+        to_broadcaster_id: &UserIdRef,
+        token: &UserToken,
+    ) -> Result<(), Report> {
+        let request = StartARaidRequest::new(from_broadcaster_id, to_broadcaster_id);
+        let response = self
+            .helix_client
+            .req_post(request, EmptyBody, token)
+            .await?;
+        tracing::info!("Raid started: {:?}", response.data);
+        Ok(())
+    }
+
+    /// Cancels a pending raid before Twitch carries it out.
+    async fn cancel_raid(
+        &self,
+        broadcaster_id: &UserIdRef,
+        token: &UserToken,
+    ) -> Result<(), Report> {
+        let request = CancelARaidRequest::broadcaster_id(broadcaster_id);
+        self.helix_client.req_delete(request, token).await?;
+        tracing::info!("Raid cancelled");
+        Ok(())
+    }
+
+    /// Marks a channel points redemption as fulfilled or rejected (refunding the viewer).
+    /// Twitch only allows this for rewards created by the same client ID.
+    async fn update_redemption_status(
+        &self,
+        broadcaster_id: &UserIdRef,
+        reward_id: &str,
+        redemption_id: &str,
+        fulfilled: bool,
+        token: &UserToken,
+    ) -> Result<(), Report> {
+        let request = UpdateRedemptionStatusRequest::new(
+            broadcaster_id,
+            RewardId::from(reward_id.to_string()),
+            RedemptionId::from(redemption_id.to_string()),
+        );
+        let status = if fulfilled {
+            CustomRewardRedemptionStatus::Fulfilled
+        } else {
+            CustomRewardRedemptionStatus::Canceled
+        };
+        let body = UpdateRedemptionStatusBody::status(status);
+
+        let response = self.helix_client.req_patch(request, body, token).await?;
+        tracing::info!("Redemption status updated: {:?}", response.data);
+        Ok(())
+    }
+
+    /// Requires the moderator:manage:banned_users scope.
+    async fn ban_user(
+        &self,
+        broadcaster_id: &UserIdRef,
+        moderator_id: &UserIdRef,
+        user_id: &UserIdRef,
+        reason: &str,
+        duration_secs: Option<u32>,
+        token: &UserToken,
+    ) -> Result<(), Report> {
+        let request = BanUserRequest::new(broadcaster_id, moderator_id);
+        let body = BanUserBody::new(user_id, reason, duration_secs);
+        let response = self.helix_client.req_post(request, body, token).await?;
+        tracing::info!("User banned: {:?}", response.data);
+        Ok(())
+    }
+
+    /// Requires the moderator:manage:automod scope.
+    async fn resolve_held_message(
+        &self,
+        moderator_id: &UserIdRef,
+        message_id: &str,
+        allow: bool,
+        token: &UserToken,
+    ) -> Result<(), Report> {
+        let request = ManageHeldAutoModMessagesRequest::new();
+        let body = ManageHeldAutoModMessagesBody::new(moderator_id, message_id, allow);
+        let response = self.helix_client.req_post(request, body, token).await?;
+        tracing::info!("Held message resolved: {:?}", response.data);
+        Ok(())
+    }
+
+    /// Requires the moderator:manage:chat_messages scope.
+    async fn clear_chat(
+        &self,
+        broadcaster_id: &UserIdRef,
+        moderator_id: &UserIdRef,
+        token: &UserToken,
+    ) -> Result<(), Report> {
+        let request = DeleteChatMessagesRequest::new(broadcaster_id, moderator_id);
+        self.helix_client.req_delete(request, token).await?;
+        tracing::info!("Chat cleared");
+        Ok(())
+    }
+
+    /// Requires the user:manage:whispers scope and a verified phone number on the
+    /// sender's account.
+    async fn send_whisper(
+        &self,
+        from_user_id: &UserIdRef,
+        to_user_id: &UserIdRef,
+        message: &str,
+        token: &UserToken,
+    ) -> Result<(), Report> {
+        check_whisper_length(message)?;
+        let request = SendWhisperRequest::new(from_user_id, to_user_id);
+        let body = SendWhisperBody::new(message);
+        match self.helix_client.req_post(request, body, token).await {
+            Ok(_) => {
+                tracing::info!("Whisper sent to {}", to_user_id);
+                Ok(())
+            }
+            Err(e) if message.chars().count() > MAX_NEW_WHISPER_CHARS => Err(Report::new(e)
+                .wrap_err(format!(
+                    "Whispers to someone who hasn't whispered you are limited to {} characters",
+                    MAX_NEW_WHISPER_CHARS
+                ))),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Requires the moderator:read:chatters scope. Twitch pages the list 1000 at a
+    /// time; every page is fetched.
+    async fn get_chatters(
+        &self,
+        broadcaster_id: &UserIdRef,
+        moderator_id: &UserIdRef,
+        token: &UserToken,
+    ) -> Result<Vec<String>, Report> {
+        let request = GetChattersRequest::new(broadcaster_id, moderator_id).first(1000);
+        let mut response = self.helix_client.req_get(request, token).await?;
+        let mut chatters = Vec::new();
+        loop {
+            chatters.extend(response.data.iter().map(|c| c.user_name.to_string()));
+            match response.get_next(&self.helix_client, token).await? {
+                Some(next) => response = next,
+                None => break,
+            }
+        }
+        Ok(chatters)
+    }
 }
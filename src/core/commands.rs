@@ -0,0 +1,126 @@
+//! Slash commands typed into the chat bar. Twitch's Helix API doesn't interpret
+//! them, so anything other than `/me` has to be turned into its own API call.
+
+/// How long `/timeout` lasts when no duration is given, as in Twitch's own chat.
+pub const DEFAULT_TIMEOUT_SECS: u32 = 600;
+/// The longest timeout Twitch allows: two weeks.
+pub const MAX_TIMEOUT_SECS: u32 = 1_209_600;
+
+/// What the chat bar input asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatCommand {
+    /// Not a command; sent to chat as typed.
+    Message(String),
+    /// `/me <text>`, sent to chat with the prefix kept so it shows as an action.
+    Me(String),
+    Announce(String),
+    Ban {
+        user: String,
+        reason: String,
+    },
+    Timeout {
+        user: String,
+        duration_secs: u32,
+        reason: String,
+    },
+    /// Delete every message in the channel.
+    Clear,
+    Raid(String),
+    Unraid,
+    Whisper {
+        recipient: String,
+        message: String,
+    },
+    /// An unknown command or one used wrongly; holds the error to show instead.
+    Invalid(String),
+}
+
+/// The chat message `/me <text>` is sent as. Twitch shows it as an action as long
+/// as the prefix is kept.
+pub fn me_message(text: &str) -> String {
+    format!("/me {}", text)
+}
+
+/// Recognizes the commands LiveNAC supports. Logins lose a leading `@` or `#` and
+/// are lowercased.
+pub fn parse_command(input: &str) -> ChatCommand {
+    let input = input.trim();
+    let Some(command_line) = input.strip_prefix('/') else {
+        return ChatCommand::Message(input.to_string());
+    };
+    let (name, args) = command_line
+        .split_once(char::is_whitespace)
+        .map_or((command_line, ""), |(name, args)| (name, args.trim()));
+    let (first, rest) = args
+        .split_once(char::is_whitespace)
+        .map_or((args, ""), |(first, rest)| (first, rest.trim()));
+
+    match name.to_lowercase().as_str() {
+        "me" if !args.is_empty() => ChatCommand::Me(args.to_string()),
+        "announce" if !args.is_empty() => ChatCommand::Announce(args.to_string()),
+        "ban" if !first.is_empty() => ChatCommand::Ban {
+            user: login(first),
+            reason: rest.to_string(),
+        },
+        "timeout" if !first.is_empty() => {
+            let (duration, reason) = rest
+                .split_once(char::is_whitespace)
+                .map_or((rest, ""), |(duration, reason)| (duration, reason.trim()));
+            let (duration_secs, reason) = match parse_duration(duration) {
+                Some(secs) => (secs, reason.to_string()),
+                // Without a valid duration, everything after the user is the reason.
+                None => (DEFAULT_TIMEOUT_SECS, rest.to_string()),
+            };
+            if duration_secs == 0 || duration_secs > MAX_TIMEOUT_SECS {
+                return ChatCommand::Invalid(
+                    "Timeouts must be between 1 second and 2 weeks.".to_string(),
+                );
+            }
+            ChatCommand::Timeout {
+                user: login(first),
+                duration_secs,
+                reason,
+            }
+        }
+        "clear" => ChatCommand::Clear,
+        "raid" if !first.is_empty() => ChatCommand::Raid(login(first)),
+        "unraid" => ChatCommand::Unraid,
+        "w" | "whisper" if !first.is_empty() && !rest.is_empty() => ChatCommand::Whisper {
+            recipient: login(first),
+            message: rest.to_string(),
+        },
+        "me" | "announce" => usage(name, "message"),
+        "ban" => usage(name, "username [reason]"),
+        "timeout" => usage(name, "username [duration] [reason]"),
+        "raid" => usage(name, "channel"),
+        "w" | "whisper" => usage(name, "username message"),
+        _ => ChatCommand::Invalid(format!("Unknown command /{}", name)),
+    }
+}
+
+/// Parses a timeout duration: plain seconds, or a number with an `s`, `m`, `h`, `d`
+/// or `w` suffix such as `10m`.
+pub fn parse_duration(text: &str) -> Option<u32> {
+    let text = text.trim().to_lowercase();
+    let (number, unit) = match text.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&text[..i], c),
+        _ => (text.as_str(), 's'),
+    };
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    number.parse::<u32>().ok()?.checked_mul(multiplier)
+}
+
+fn login(name: &str) -> String {
+    name.trim_start_matches(['@', '#']).to_lowercase()
+}
+
+fn usage(name: &str, args: &str) -> ChatCommand {
+    ChatCommand::Invalid(format!("Usage: /{} {}", name, args))
+}
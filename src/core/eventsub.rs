@@ -1,29 +1,67 @@
 use crate::{
+    core::{
+        proxy,
+        tokenize::{EmoteRegistry, RawFragment, tokenize},
+    },
     events::app_event::{AppEvent, ChatEvent},
-    models::{
-        emote::{Emote, EmoteSource},
-        message::{ChatMessage, MessageFragment},
+    features::{automod::HeldMessage, channel_points::Redemption},
+    models::message::{
+        ChatMessage, LowTrustStatus, MessageFragment, MessageKind, ReplyInfo, SystemCategory,
     },
 };
-use chrono::Local;
+use chrono::{DateTime, Local};
 use eyre::eyre;
 use futures::StreamExt;
 use reqwest::Client as ReqwestClient;
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
+use std::{sync::Arc, time::Duration};
+use tokio::{net::TcpStream, sync::mpsc};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::Message as WsMessage};
 use twitch_api::{
     HelixClient,
     eventsub::{
-        Event, Message, Transport,
-        channel::ChannelChatMessageV1,
-        event::websocket::{EventsubWebsocketData, WelcomePayload},
+        Event, EventSubscription, Message, Transport,
+        automod::message::{AutomodMessageHoldV1, AutomodMessageUpdateV1},
+        channel::{
+            ChannelChatClearUserMessagesV1, ChannelChatClearV1, ChannelChatMessageDeleteV1,
+            ChannelChatMessageV1, ChannelChatMessageV1Payload, ChannelChatNotificationV1,
+            ChannelChatNotificationV1Payload, ChannelPointsCustomRewardRedemptionAddV1,
+            ChannelPointsCustomRewardRedemptionUpdateV1, ChannelSuspiciousUserMessageV1,
+            ChannelSuspiciousUserMessageV1Payload,
+            channel_points_custom_reward_redemption::RedemptionStatus,
+            chat::{
+                Fragment,
+                notification::{Chatter, Notification},
+            },
+            suspicious_user::LowTrustStatus as SuspiciousLowTrustStatus,
+        },
+        event::websocket::EventsubWebsocketData,
     },
     helix::eventsub::{CreateEventSubSubscriptionBody, CreateEventSubSubscriptionRequest},
 };
 use twitch_oauth2::UserToken;
 use twitch_types::UserId;
 
+const EVENTSUB_URL: &str = "wss://eventsub.wss.twitch.tv/ws";
+/// The sender name of anonymous gift subs.
+pub const ANONYMOUS_SENDER: &str = "AnAnonymousGifter";
+/// Twitch's keepalive interval is 10 seconds by default; this leaves plenty of slack.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_RECONNECT_ATTEMPTS: u32 = 6;
+/// Doubled after every failed attempt.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Why a connection stopped being read.
+enum Disconnect {
+    /// Twitch is moving the session to the given URL.
+    Reconnect(String),
+    /// The connection closed or broke without being asked to.
+    Dropped(eyre::Report),
+    /// Something went wrong that reconnecting won't fix.
+    Fatal(eyre::Report),
+}
+
 pub struct EventSubClient {
     helix_client: HelixClient<'static, ReqwestClient>,
     user_id: UserId,
@@ -31,6 +69,9 @@ pub struct EventSubClient {
     message_tx: mpsc::Sender<AppEvent>,
     broadcaster_id: UserId,
     session_id: Option<String>,
+    subscribe_redemptions: bool,
+    forward_raw_events: bool,
+    emote_registry: EmoteRegistry,
 }
 
 impl EventSubClient {
@@ -39,8 +80,11 @@ impl EventSubClient {
         token: Arc<UserToken>,
         message_tx: mpsc::Sender<AppEvent>,
         broadcaster_id: UserId,
+        subscribe_redemptions: bool,
+        forward_raw_events: bool,
+        emote_registry: EmoteRegistry,
     ) -> Self {
-        let reqwest_client = ReqwestClient::builder()
+        let reqwest_client = proxy::client_builder()
             .user_agent(concat!(
                 env!("CARGO_PKG_NAME"),
                 "/",
@@ -56,6 +100,9 @@ impl EventSubClient {
             message_tx,
             broadcaster_id,
             session_id: None,
+            subscribe_redemptions,
+            forward_raw_events,
+            emote_registry,
         }
     }
 
@@ -65,74 +112,320 @@ impl EventSubClient {
             &self.broadcaster_id
         );
 
-        let (ws_stream, _) = connect_async("wss://eventsub.wss.twitch.tv/ws").await?;
-        tracing::info!("WebSocket handshake has been successfully completed");
-        let (_write, mut read) = ws_stream.split();
+        self.send_chat_event(ChatEvent::Connecting).await;
+        let (mut stream, session_id) = match open_session(EVENTSUB_URL).await {
+            Ok(session) => session,
+            Err(e) => {
+                self.send_chat_event(ChatEvent::Disconnected).await;
+                self.send_chat_event(ChatEvent::EventSubError(format!(
+                    "Couldn't reach Twitch, check your connection and reconnect ({})",
+                    e
+                )))
+                .await;
+                return Err(e);
+            }
+        };
+        if let Err(e) = self.handle_welcome(session_id).await {
+            self.send_chat_event(ChatEvent::Disconnected).await;
+            self.send_chat_event(ChatEvent::EventSubError(e.to_string()))
+                .await;
+            return Err(e);
+        }
 
-        while let Some(msg) = read.next().await {
-            let msg = match msg {
-                Ok(msg) => msg,
-                Err(e) => {
-                    tracing::error!("Error reading message from websocket: {}", e);
-                    continue;
+        loop {
+            let error = match self.read_until_disconnect(&mut stream).await {
+                Disconnect::Reconnect(url) => match self.migrate(&url, &mut stream).await {
+                    Ok(new_stream) => {
+                        stream = new_stream;
+                        continue;
+                    }
+                    Err(e) => e.wrap_err("Failed to move to the new EventSub server"),
+                },
+                Disconnect::Dropped(e) => e,
+                Disconnect::Fatal(e) => {
+                    self.send_chat_event(ChatEvent::Disconnected).await;
+                    self.send_chat_event(ChatEvent::EventSubError(e.to_string()))
+                        .await;
+                    return Err(e);
                 }
             };
+            tracing::warn!("EventSub connection lost: {}", error);
+            self.send_chat_event(ChatEvent::Disconnected).await;
+            stream = self.reconnect_with_backoff(error).await?;
+        }
+    }
 
-            if let Err(e) = self.handle_message(msg).await {
-                // Send error back to UI
-                let _ = self
-                    .message_tx
-                    .send(AppEvent::Chat(ChatEvent::EventSubError(e.to_string())))
-                    .await;
-                return Err(e);
+    /// Opens a fresh session and subscribes to everything on it.
+    async fn connect(&mut self) -> Result<WsStream, eyre::Report> {
+        let (stream, session_id) = open_session(EVENTSUB_URL).await?;
+        self.handle_welcome(session_id).await?;
+        Ok(stream)
+    }
+
+    /// Retries [`Self::connect`] with exponential backoff. Twitch drops subscriptions
+    /// along with the old session, so each attempt subscribes again from scratch.
+    async fn reconnect_with_backoff(
+        &mut self,
+        mut error: eyre::Report,
+    ) -> Result<WsStream, eyre::Report> {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+            self.send_chat_event(ChatEvent::Reconnecting {
+                attempt,
+                max_attempts: MAX_RECONNECT_ATTEMPTS,
+            })
+            .await;
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+
+            match self.connect().await {
+                Ok(stream) => {
+                    tracing::info!("Reconnected to EventSub after {} attempt(s)", attempt);
+                    self.send_chat_event(ChatEvent::Reconnected).await;
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    tracing::warn!("EventSub reconnect attempt {} failed: {}", attempt, e);
+                    error = e;
+                }
             }
         }
 
-        Ok(())
+        self.send_chat_event(ChatEvent::Disconnected).await;
+        self.send_chat_event(ChatEvent::EventSubError(format!(
+            "Lost the chat connection and couldn't reconnect after {} attempts ({})",
+            MAX_RECONNECT_ATTEMPTS, error
+        )))
+        .await;
+        Err(error.wrap_err("Gave up reconnecting to EventSub"))
+    }
+
+    /// Follows a reconnect request from Twitch: connects to `url` and keeps handling
+    /// messages from the old connection until the new one is welcomed. Subscriptions
+    /// carry over to the new session, so nothing is subscribed again.
+    async fn migrate(&mut self, url: &str, old: &mut WsStream) -> Result<WsStream, eyre::Report> {
+        tracing::info!("Twitch asked to reconnect, moving to {}", url);
+        let opening = open_session(url);
+        tokio::pin!(opening);
+        loop {
+            tokio::select! {
+                result = &mut opening => {
+                    let (stream, session_id) = result?;
+                    tracing::info!("Moved to new EventSub session {}", session_id);
+                    self.session_id = Some(session_id);
+                    return Ok(stream);
+                }
+                Some(Ok(msg)) = old.next() => {
+                    if let Err(e) = self.handle_message(msg).await {
+                        tracing::warn!("Error on the old EventSub connection: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handles messages until the connection ends, one way or another.
+    async fn read_until_disconnect(&mut self, stream: &mut WsStream) -> Disconnect {
+        loop {
+            let msg = match tokio::time::timeout(READ_TIMEOUT, stream.next()).await {
+                Ok(Some(Ok(msg))) => msg,
+                Ok(Some(Err(e))) => return Disconnect::Dropped(e.into()),
+                Ok(None) => return Disconnect::Dropped(eyre!("WebSocket stream ended")),
+                // Twitch sends keepalives every few seconds, so silence means the
+                // connection is dead even if the socket hasn't noticed yet.
+                Err(_) => {
+                    return Disconnect::Dropped(eyre!(
+                        "No message from Twitch in {} seconds",
+                        READ_TIMEOUT.as_secs()
+                    ));
+                }
+            };
+
+            match self.handle_message(msg).await {
+                Ok(Some(disconnect)) => return disconnect,
+                Ok(None) => {}
+                Err(e) => return Disconnect::Fatal(e),
+            }
+        }
     }
 
-    async fn handle_message(&mut self, msg: WsMessage) -> Result<(), eyre::Report> {
+    async fn handle_message(&mut self, msg: WsMessage) -> Result<Option<Disconnect>, eyre::Report> {
         match msg {
             WsMessage::Text(s) => {
                 let data: EventsubWebsocketData = Event::parse_websocket(&s)?;
                 match data {
-                    EventsubWebsocketData::Welcome { payload, .. } => {
-                        self.handle_welcome(payload).await?;
+                    EventsubWebsocketData::Welcome { .. } => {
+                        tracing::debug!("Ignoring a welcome on an established session");
                     }
-                    EventsubWebsocketData::Notification { payload, .. } => {
-                        self.handle_notification(payload).await;
+                    EventsubWebsocketData::Notification { metadata, payload } => {
+                        if self.forward_raw_events {
+                            self.forward_raw_event(&s).await;
+                        }
+                        let sent_at = parse_timestamp(metadata.message_timestamp.as_str());
+                        self.handle_notification(payload, sent_at, &s).await;
                     }
                     EventsubWebsocketData::Keepalive { .. } => {
                         tracing::trace!("Keepalive received");
                     }
-                    EventsubWebsocketData::Reconnect { .. } => {
-                        tracing::warn!(
-                            "Reconnect message received. You should implement reconnection logic."
-                        );
+                    EventsubWebsocketData::Revocation { .. } => {
+                        self.handle_revocation(&s).await;
+                    }
+                    EventsubWebsocketData::Reconnect { payload, .. } => {
+                        return Ok(Some(match payload.session.reconnect_url {
+                            Some(url) => Disconnect::Reconnect(url.to_string()),
+                            None => Disconnect::Dropped(eyre!("Reconnect message without a URL")),
+                        }));
                     }
                     _ => {}
                 }
             }
             WsMessage::Close(c) => {
                 tracing::info!("Websocket closed: {:?}", c);
-                return Err(eyre!("WebSocket connection closed"));
+                return Ok(Some(Disconnect::Dropped(eyre!(
+                    "WebSocket connection closed"
+                ))));
             }
             _ => {}
         }
-        Ok(())
+        Ok(None)
     }
 
-    async fn handle_welcome(&mut self, welcome: WelcomePayload<'_>) -> Result<(), eyre::Report> {
-        let session_id = welcome.session.id.to_string();
+    async fn handle_welcome(&mut self, session_id: String) -> Result<(), eyre::Report> {
         tracing::info!("Received session ID: {}", session_id);
         self.session_id = Some(session_id.clone());
 
-        let transport = Transport::websocket(session_id);
-
-        let body = CreateEventSubSubscriptionBody::new(
+        self.subscribe(
             ChannelChatMessageV1::new(self.broadcaster_id.clone(), self.user_id.clone()),
-            transport,
-        );
+            &session_id,
+        )
+        .await?;
+
+        // Notices are extra decoration on top of chat, so don't fail the connection
+        // over them either.
+        if let Err(e) = self
+            .subscribe(
+                ChannelChatNotificationV1::new(self.broadcaster_id.clone(), self.user_id.clone()),
+                &session_id,
+            )
+            .await
+        {
+            tracing::warn!("Failed to subscribe to chat notifications: {}", e);
+        }
+
+        // Twitch only allows this for the channel's moderators, so success doubles
+        // as the signal that moderator tools should be shown.
+        match self
+            .subscribe(
+                ChannelSuspiciousUserMessageV1::new(
+                    self.broadcaster_id.clone(),
+                    self.user_id.clone(),
+                ),
+                &session_id,
+            )
+            .await
+        {
+            Ok(()) => {
+                self.send_chat_event(ChatEvent::ModeratorConfirmed).await;
+                self.subscribe_automod(&session_id).await;
+            }
+            Err(e) => {
+                tracing::debug!("Not subscribing to suspicious user messages: {}", e);
+            }
+        }
+
+        // Deletions only matter for keeping the log accurate, so a failure here
+        // shouldn't take the whole chat connection down.
+        if let Err(e) = self
+            .subscribe(
+                ChannelChatMessageDeleteV1::new(self.broadcaster_id.clone(), self.user_id.clone()),
+                &session_id,
+            )
+            .await
+        {
+            tracing::warn!("Failed to subscribe to message deletions: {}", e);
+        }
+        if let Err(e) = self
+            .subscribe(
+                ChannelChatClearUserMessagesV1::new(
+                    self.broadcaster_id.clone(),
+                    self.user_id.clone(),
+                ),
+                &session_id,
+            )
+            .await
+        {
+            tracing::warn!("Failed to subscribe to cleared user messages: {}", e);
+        }
+        if let Err(e) = self
+            .subscribe(
+                ChannelChatClearV1::new(self.broadcaster_id.clone(), self.user_id.clone()),
+                &session_id,
+            )
+            .await
+        {
+            tracing::warn!("Failed to subscribe to chat clears: {}", e);
+        }
+
+        // Redemption topics are only available to the broadcaster themselves.
+        if self.subscribe_redemptions && self.user_id == self.broadcaster_id {
+            if let Err(e) = self
+                .subscribe(
+                    ChannelPointsCustomRewardRedemptionAddV1::broadcaster_user_id(
+                        self.broadcaster_id.clone(),
+                    ),
+                    &session_id,
+                )
+                .await
+            {
+                tracing::warn!("Failed to subscribe to redemptions: {}", e);
+            }
+            if let Err(e) = self
+                .subscribe(
+                    ChannelPointsCustomRewardRedemptionUpdateV1::broadcaster_user_id(
+                        self.broadcaster_id.clone(),
+                    ),
+                    &session_id,
+                )
+                .await
+            {
+                tracing::warn!("Failed to subscribe to redemption updates: {}", e);
+            }
+        }
+
+        self.send_chat_event(ChatEvent::Connected).await;
+        Ok(())
+    }
+
+    /// AutoMod's queue is only shown to moderators, so this is only attempted once
+    /// moderator status is confirmed.
+    async fn subscribe_automod(&self, session_id: &str) {
+        if let Err(e) = self
+            .subscribe(
+                AutomodMessageHoldV1::new(self.broadcaster_id.clone(), self.user_id.clone()),
+                session_id,
+            )
+            .await
+        {
+            tracing::warn!("Failed to subscribe to AutoMod held messages: {}", e);
+            return;
+        }
+        if let Err(e) = self
+            .subscribe(
+                AutomodMessageUpdateV1::new(self.broadcaster_id.clone(), self.user_id.clone()),
+                session_id,
+            )
+            .await
+        {
+            tracing::warn!("Failed to subscribe to AutoMod message updates: {}", e);
+        }
+    }
+
+    async fn subscribe<E>(&self, subscription: E, session_id: &str) -> Result<(), eyre::Report>
+    where
+        E: EventSubscription + Send,
+    {
+        let transport = Transport::websocket(session_id.to_string());
+        let body = CreateEventSubSubscriptionBody::new(subscription, transport);
 
         let subscription = self
             .helix_client
@@ -151,58 +444,381 @@ impl EventSubClient {
         Ok(())
     }
 
-    async fn handle_notification(&self, notification: Event) {
-        if let Event::ChannelChatMessageV1(payload) = notification {
-            if let Message::Notification(event_data) = payload.message {
-                let chatter_display_name = event_data.chatter_user_name;
+    /// Twitch drops a subscription server-side when, for example, the user revokes the
+    /// app's authorization. The typed payload doesn't expose the reason uniformly across
+    /// subscription types, so it's read from the raw message instead.
+    async fn handle_revocation(&self, raw: &str) {
+        let json: serde_json::Value = serde_json::from_str(raw).unwrap_or_default();
+        let subscription = &json["payload"]["subscription"];
+        let reason = subscription["status"].as_str().unwrap_or("unknown");
+        let subscription_type = subscription["type"].as_str().unwrap_or("unknown");
+        tracing::warn!(
+            "EventSub subscription '{}' was revoked: {}",
+            subscription_type,
+            reason
+        );
 
-                let sender_color = if !event_data.color.as_str().is_empty()
-                    && event_data.color.as_str().len() == 7
-                    && event_data.color.as_str().starts_with('#')
-                {
-                    let r = u8::from_str_radix(&event_data.color.as_str()[1..3], 16).unwrap_or(255);
-                    let g = u8::from_str_radix(&event_data.color.as_str()[3..5], 16).unwrap_or(255);
-                    let b = u8::from_str_radix(&event_data.color.as_str()[5..7], 16).unwrap_or(255);
-                    Some((r, g, b))
-                } else {
-                    None
-                };
-
-                let mut fragments = Vec::new();
-                for fragment in &event_data.message.fragments {
-                    match fragment {
-                        twitch_api::eventsub::channel::chat::Fragment::Text { text } => {
-                            fragments.push(MessageFragment::Text(text.to_string()));
-                        }
-                        twitch_api::eventsub::channel::chat::Fragment::Emote { text, emote } => {
-                            let emote_url = format!(
-                                "https://static-cdn.jtvnw.net/emoticons/v2/{}/default/dark/1.0",
-                                emote.id
-                            );
-                            fragments.push(MessageFragment::Emote(Emote {
-                                name: text.to_string(),
-                                url: emote_url,
-                                source: EmoteSource::Twitch,
-                            }));
-                        }
-                        _ => {
-                            // TODO: Maybe log this
-                        }
+        self.send_chat_event(ChatEvent::SubscriptionRevoked(reason.to_string()))
+            .await;
+    }
+
+    /// `sent_at` is when Twitch sent the notification, which can be well before we
+    /// process it, e.g. under load or when Twitch replays messages after a reconnect.
+    /// `raw` is the notification's JSON, for the odd field `twitch_api` leaves out.
+    async fn handle_notification(&self, notification: Event, sent_at: DateTime<Local>, raw: &str) {
+        match notification {
+            Event::ChannelChatMessageV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    self.handle_chat_message(event_data, sent_at).await;
+                }
+            }
+            Event::ChannelChatNotificationV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    self.handle_chat_notification(event_data, sent_at).await;
+                }
+            }
+            Event::ChannelSuspiciousUserMessageV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    self.handle_suspicious_message(event_data, sent_at, raw)
+                        .await;
+                }
+            }
+            Event::ChannelChatMessageDeleteV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    self.send_chat_event(ChatEvent::MessageDeleted(
+                        event_data.message_id.to_string(),
+                    ))
+                    .await;
+                }
+            }
+            Event::ChannelChatClearV1(payload) => {
+                if let Message::Notification(_) = payload.message {
+                    self.send_chat_event(ChatEvent::ChatCleared).await;
+                }
+            }
+            Event::ChannelChatClearUserMessagesV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    self.send_chat_event(ChatEvent::UserMessagesCleared(
+                        event_data.target_user_id.to_string(),
+                    ))
+                    .await;
+                }
+            }
+            Event::AutomodMessageHoldV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    let message = HeldMessage {
+                        id: event_data.message_id.to_string(),
+                        user_id: event_data.user_id.to_string(),
+                        user_name: event_data.user_name.to_string(),
+                        text: event_data.message.text.to_string(),
+                        held_at: parse_timestamp(event_data.held_at.as_str()),
+                    };
+                    self.send_chat_event(ChatEvent::MessageHeld(message)).await;
+                }
+            }
+            // Updates are only sent once a held message has been dealt with.
+            Event::AutomodMessageUpdateV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    self.send_chat_event(ChatEvent::HeldMessageResolved(
+                        event_data.message_id.to_string(),
+                    ))
+                    .await;
+                }
+            }
+            Event::ChannelPointsCustomRewardRedemptionAddV1(payload) => {
+                if let Message::Notification(event_data) = payload.message {
+                    if event_data.status != RedemptionStatus::Unfulfilled {
+                        // Rewards that skip the request queue arrive already fulfilled.
+                        return;
                     }
+                    let redeemed_at = parse_timestamp(event_data.redeemed_at.as_str());
+                    let redemption = Redemption {
+                        id: event_data.id.to_string(),
+                        reward_id: event_data.reward.id.to_string(),
+                        reward_title: event_data.reward.title.to_string(),
+                        reward_cost: event_data.reward.cost,
+                        user_name: event_data.user_name.to_string(),
+                        user_input: event_data.user_input.to_string(),
+                        redeemed_at,
+                    };
+                    self.send_chat_event(ChatEvent::RedemptionAdded(redemption))
+                        .await;
                 }
+            }
+            Event::ChannelPointsCustomRewardRedemptionUpdateV1(payload) => {
+                if let Message::Notification(event_data) = payload.message
+                    && event_data.status != RedemptionStatus::Unfulfilled
+                {
+                    self.send_chat_event(ChatEvent::RedemptionResolved(event_data.id.to_string()))
+                        .await;
+                }
+            }
+            _ => {}
+        }
+    }
 
-                let message = ChatMessage {
-                    sender_name: chatter_display_name.to_string(),
-                    sender_color,
-                    fragments,
-                    timestamp: Local::now(),
-                };
+    async fn handle_chat_message(
+        &self,
+        event_data: ChannelChatMessageV1Payload,
+        sent_at: DateTime<Local>,
+    ) {
+        let mut fragments = convert_fragments(&event_data.message.fragments, &self.emote_registry);
+        let is_action = strip_action_markers(&mut fragments);
 
-                let msg = AppEvent::Chat(ChatEvent::NewChatMessage(message));
-                if self.message_tx.send(msg).await.is_err() {
-                    tracing::error!("Failed to send message to UI thread: channel is closed.");
-                }
+        let message = ChatMessage {
+            id: event_data.message_id.to_string(),
+            sender_id: event_data.chatter_user_id.to_string(),
+            sender_name: event_data.chatter_user_name.to_string(),
+            sender_color: parse_color(event_data.color.as_str()),
+            fragments,
+            timestamp: sent_at,
+            deleted: false,
+            is_action,
+            kind: MessageKind::Chat,
+            low_trust: None,
+            reply_to: event_data.reply.map(|reply| ReplyInfo {
+                parent_id: reply.parent_message_id.to_string(),
+                parent_user_name: reply.parent_user_name.to_string(),
+                parent_text: reply.parent_message_body,
+            }),
+        };
+
+        self.send_chat_event(ChatEvent::NewChatMessage(message))
+            .await;
+    }
+
+    /// Community notices (subs, gift subs, raids, announcements, ...) come with a
+    /// ready-made `system_message` from Twitch, which is shown as-is.
+    async fn handle_chat_notification(
+        &self,
+        event_data: ChannelChatNotificationV1Payload,
+        sent_at: DateTime<Local>,
+    ) {
+        let category = match &event_data.notification {
+            Notification::Subscription(_)
+            | Notification::Resubscription(_)
+            | Notification::SubGift(_)
+            | Notification::CommunitySubGift(_)
+            | Notification::GiftPaidUpgrade(_)
+            | Notification::PrimePaidUpgrade(_)
+            | Notification::PayItForward(_)
+            | Notification::SharedChatSub(_)
+            | Notification::SharedChatResub(_)
+            | Notification::SharedChatSubGift(_)
+            | Notification::SharedChatCommunitySubGift(_)
+            | Notification::SharedChatGiftPaidUpgrade(_)
+            | Notification::SharedChatPrimePaidUpgrade(_)
+            | Notification::SharedChatPayItForward(_) => SystemCategory::Subscription,
+            Notification::Raid(_) | Notification::SharedChatRaid(_) => SystemCategory::Raid,
+            Notification::Announcement(_) | Notification::SharedChatAnnouncement(_) => {
+                SystemCategory::Announcement
             }
+            Notification::BitsBadgeTier(_) => SystemCategory::BitsBadge,
+            _ => SystemCategory::Other,
+        };
+
+        let (sender_id, sender_name, sender_color) = notification_sender(&event_data.chatter);
+        let message = ChatMessage {
+            id: event_data.message_id.to_string(),
+            sender_id,
+            sender_name,
+            sender_color,
+            fragments: convert_fragments(&event_data.message.fragments, &self.emote_registry),
+            timestamp: sent_at,
+            deleted: false,
+            is_action: false,
+            kind: MessageKind::System {
+                category,
+                text: event_data.system_message.to_string(),
+            },
+            low_trust: None,
+            reply_to: None,
+        };
+
+        self.send_chat_event(ChatEvent::NewChatMessage(message))
+            .await;
+    }
+
+    /// Sent alongside (or, for restricted users, instead of) the regular chat message.
+    /// The reducer merges the two by message id, whichever arrives first.
+    async fn handle_suspicious_message(
+        &self,
+        event_data: ChannelSuspiciousUserMessageV1Payload,
+        sent_at: DateTime<Local>,
+        raw: &str,
+    ) {
+        let low_trust = match event_data.low_trust_status {
+            SuspiciousLowTrustStatus::ActiveMonitoring => LowTrustStatus::ActiveMonitoring,
+            SuspiciousLowTrustStatus::Restricted => LowTrustStatus::Restricted,
+            _ => return,
+        };
+        let Some(message_id) = suspicious_message_id(raw) else {
+            tracing::warn!("Suspicious user message without a message id");
+            return;
+        };
+
+        let message = ChatMessage {
+            id: message_id,
+            sender_id: event_data.user_id.to_string(),
+            sender_name: event_data.user_name.to_string(),
+            sender_color: None,
+            fragments: vec![MessageFragment::Text(event_data.message.text.to_string())],
+            timestamp: sent_at,
+            deleted: false,
+            is_action: false,
+            kind: MessageKind::Chat,
+            low_trust: Some(low_trust),
+            reply_to: None,
+        };
+
+        self.send_chat_event(ChatEvent::NewChatMessage(message))
+            .await;
+    }
+
+    /// Sends the notification's JSON to the UI, keyed by the chat message it is about.
+    /// Notifications that aren't about a message are skipped.
+    async fn forward_raw_event(&self, raw: &str) {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(raw) else {
+            return;
+        };
+        let Some(event) = json.pointer("/payload/event") else {
+            return;
+        };
+        let message_id = event
+            .get("message_id")
+            .or_else(|| event.pointer("/message/message_id"))
+            .and_then(|id| id.as_str());
+        if let Some(message_id) = message_id {
+            let json = serde_json::to_string_pretty(&json).unwrap_or_else(|_| raw.to_string());
+            self.send_chat_event(ChatEvent::RawEvent {
+                message_id: message_id.to_string(),
+                json,
+            })
+            .await;
+        }
+    }
+
+    async fn send_chat_event(&self, event: ChatEvent) {
+        let event = AppEvent::ChannelChat {
+            broadcaster_id: self.broadcaster_id.to_string(),
+            event,
+        };
+        if self.message_tx.send(event).await.is_err() {
+            tracing::error!("Failed to send message to UI thread: channel is closed.");
+        }
+    }
+}
+
+/// Connects to `url` and waits for Twitch's welcome, returning the connection and
+/// the id of the session it started.
+async fn open_session(url: &str) -> Result<(WsStream, String), eyre::Report> {
+    let mut stream = proxy::connect_websocket(url).await?;
+    tracing::info!("WebSocket handshake has been successfully completed");
+    loop {
+        let msg = tokio::time::timeout(READ_TIMEOUT, stream.next())
+            .await
+            .map_err(|_| eyre!("Twitch didn't send a welcome message"))?
+            .ok_or_else(|| eyre!("WebSocket closed before the welcome message"))??;
+        if let WsMessage::Text(s) = msg
+            && let EventsubWebsocketData::Welcome { payload, .. } = Event::parse_websocket(&s)?
+        {
+            return Ok((stream, payload.session.id.to_string()));
         }
     }
 }
+
+/// Converts an RFC 3339 timestamp from Twitch to local time, falling back to the
+/// receive time if it's missing or malformed.
+fn parse_timestamp(timestamp: &str) -> DateTime<Local> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Local))
+        .unwrap_or_else(|_| {
+            tracing::debug!("Unparseable Twitch timestamp '{}'", timestamp);
+            Local::now()
+        })
+}
+
+/// `/me` messages arrive wrapped in the IRC CTCP markers `\u{1}ACTION ...\u{1}`.
+/// Strips them from the text and reports whether they were present.
+fn strip_action_markers(fragments: &mut [MessageFragment]) -> bool {
+    let Some(MessageFragment::Text(first)) = fragments.first_mut() else {
+        return false;
+    };
+    let Some(rest) = first.strip_prefix("\u{1}ACTION ") else {
+        return false;
+    };
+    *first = rest.to_string();
+    if let Some(MessageFragment::Text(last)) = fragments.last_mut()
+        && let Some(rest) = last.strip_suffix('\u{1}')
+    {
+        *last = rest.to_string();
+    }
+    true
+}
+
+/// The id of the chat message in a suspicious-user notification's JSON. `twitch_api`
+/// leaves it out of the payload type, but it's what the message is merged on.
+pub fn suspicious_message_id(raw: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(raw).ok()?;
+    json.pointer("/payload/event/message/message_id")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// The id, name and color of whoever triggered a chat notification. Anonymous gift
+/// subs have no sender; they get the name Twitch's IRC uses for them and no id, so
+/// there is no one to moderate.
+pub fn notification_sender(chatter: &Chatter) -> (String, String, Option<(u8, u8, u8)>) {
+    match chatter {
+        Chatter::Chatter {
+            chatter_user_id,
+            chatter_user_name,
+            color,
+            ..
+        } => (
+            chatter_user_id.to_string(),
+            chatter_user_name.to_string(),
+            parse_color(color.as_str()),
+        ),
+        Chatter::Anonymous => (String::new(), ANONYMOUS_SENDER.to_string(), None),
+        _ => (String::new(), String::new(), None),
+    }
+}
+
+/// Parses a `#RRGGBB` chat color. Users who never picked one have an empty color.
+fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    if color.len() != 7 || !color.starts_with('#') {
+        return None;
+    }
+    let r = u8::from_str_radix(&color[1..3], 16).unwrap_or(255);
+    let g = u8::from_str_radix(&color[3..5], 16).unwrap_or(255);
+    let b = u8::from_str_radix(&color[5..7], 16).unwrap_or(255);
+    Some((r, g, b))
+}
+
+/// Maps Twitch's fragments onto the tokenizer's input, which doesn't depend on
+/// `twitch_api` types.
+fn convert_fragments(fragments: &[Fragment], registry: &EmoteRegistry) -> Vec<MessageFragment> {
+    let raw: Vec<RawFragment> = fragments
+        .iter()
+        .filter_map(|fragment| match fragment {
+            Fragment::Text { text } => Some(RawFragment::Text(text.to_string())),
+            Fragment::Emote { text, emote } => Some(RawFragment::TwitchEmote {
+                id: emote.id.to_string(),
+                text: text.to_string(),
+            }),
+            Fragment::Mention { mention, .. } => {
+                Some(RawFragment::Mention(mention.user_name.to_string()))
+            }
+            Fragment::Cheermote { cheermote, .. } => Some(RawFragment::Cheer {
+                prefix: cheermote.prefix.to_string(),
+                bits: i64::from(cheermote.bits),
+            }),
+            _ => {
+                // TODO: Maybe log this
+                None
+            }
+        })
+        .collect();
+    tokenize(&raw, registry)
+}
@@ -0,0 +1,151 @@
+//! Saving a channel's chat log for people to read, e.g. when reviewing a stream
+//! afterwards. Chat history and replay logs are JSON meant to be loaded back in;
+//! these are a plain text transcript or a self-contained HTML page.
+
+use crate::models::{
+    emote::EmoteScale,
+    message::{ChatMessage, MessageFragment, MessageKind},
+};
+use std::path::Path;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// One line per message, e.g. `[2024-05-01 20:15:03] someone: hi Kappa`. Emotes
+/// appear as their names.
+pub fn to_plaintext<'a>(messages: impl IntoIterator<Item = &'a ChatMessage>) -> String {
+    let mut text = String::new();
+    for message in messages {
+        text.push_str(&format!(
+            "[{}] ",
+            message.timestamp.format(TIMESTAMP_FORMAT)
+        ));
+        if matches!(message.kind, MessageKind::Chat) {
+            text.push_str(&message.sender_name);
+            text.push_str(": ");
+        }
+        text.push_str(&message.to_plain_text());
+        if message.deleted {
+            text.push_str(" (deleted)");
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// A standalone page with names in their chat colors and emotes as images loaded
+/// from their CDNs.
+pub fn to_html<'a>(channel: &str, messages: impl IntoIterator<Item = &'a ChatMessage>) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>#{channel}</title>\n\
+         <style>\n\
+         body {{ background: #18181b; color: #efeff1; font-family: sans-serif; }}\n\
+         .time {{ color: #808080; }}\n\
+         .system {{ color: #aaaaaa; font-style: italic; }}\n\
+         .deleted {{ color: #646464; text-decoration: line-through; }}\n\
+         img {{ height: 28px; vertical-align: middle; }}\n\
+         </style>\n</head>\n<body>\n<h1>#{channel}</h1>\n",
+        channel = escape(channel)
+    );
+    for message in messages {
+        let class = if message.deleted {
+            " class=\"deleted\""
+        } else {
+            ""
+        };
+        html.push_str(&format!(
+            "<div{}><span class=\"time\">[{}]</span> ",
+            class,
+            message.timestamp.format(TIMESTAMP_FORMAT)
+        ));
+        match &message.kind {
+            MessageKind::Chat => {
+                let style = message
+                    .sender_color
+                    .map(|(r, g, b)| format!(" style=\"color: #{:02x}{:02x}{:02x}\"", r, g, b))
+                    .unwrap_or_default();
+                html.push_str(&format!(
+                    "<b{}>{}</b>: ",
+                    style,
+                    escape(&message.sender_name)
+                ));
+            }
+            MessageKind::System { text, .. } => {
+                html.push_str(&format!("<span class=\"system\">{}</span> ", escape(text)));
+            }
+        }
+        if message.is_action {
+            html.push_str("<i>");
+        }
+        for fragment in &message.fragments {
+            push_fragment(&mut html, fragment);
+        }
+        if message.is_action {
+            html.push_str("</i>");
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// The log in the format the file name asks for: HTML for `.html`/`.htm`, plain
+/// text otherwise.
+pub fn render<'a>(
+    path: &Path,
+    channel: &str,
+    messages: impl IntoIterator<Item = &'a ChatMessage>,
+) -> String {
+    let is_html = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+    if is_html {
+        to_html(channel, messages)
+    } else {
+        to_plaintext(messages)
+    }
+}
+
+fn push_fragment(html: &mut String, fragment: &MessageFragment) {
+    match fragment {
+        MessageFragment::Text(text) => html.push_str(&escape(text)),
+        MessageFragment::Emote(emote) => {
+            push_image(html, &emote.image_url(true, EmoteScale::X2), &emote.name)
+        }
+        MessageFragment::Mention(name) => html.push_str(&format!("<b>@{}</b>", escape(name))),
+        MessageFragment::Cheer { prefix, bits } => {
+            html.push_str(&escape(&format!("{}{}", prefix.to_lowercase(), bits)))
+        }
+        MessageFragment::Cheermote {
+            prefix,
+            bits,
+            emote,
+        } => {
+            push_image(html, &emote.url, prefix);
+            html.push_str(&bits.to_string());
+        }
+    }
+}
+
+fn push_image(html: &mut String, url: &str, name: &str) {
+    html.push_str(&format!(
+        "<img src=\"{}\" alt=\"{name}\" title=\"{name}\">",
+        escape(url),
+        name = escape(name)
+    ));
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
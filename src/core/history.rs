@@ -0,0 +1,120 @@
+//! Per-channel chat scrollback kept on disk, so a channel's recent messages are
+//! back after a restart. Each channel has a file with one JSON-serialized
+//! `ChatMessage` per line, the same format the replay viewer reads.
+
+use crate::{core::storage, models::message::ChatMessage};
+use eyre::{Context, Report};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+/// Lines in each history file written so far, keyed by path. Holding the lock also
+/// keeps appends from interleaving.
+static LINE_COUNTS: Lazy<Mutex<HashMap<PathBuf, usize>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+pub struct ChatHistory {
+    dir: PathBuf,
+    max_lines: usize,
+}
+
+impl ChatHistory {
+    /// Keeps up to `max_lines` messages per channel in `dir`.
+    pub fn new(dir: PathBuf, max_lines: usize) -> Self {
+        Self { dir, max_lines }
+    }
+
+    /// History in the data directory, or `None` when it is disabled or nothing is
+    /// persisted.
+    pub fn from_data_dir(max_lines: usize) -> Option<Self> {
+        storage::data_dir()
+            .filter(|_| max_lines > 0)
+            .map(|dir| Self::new(dir.join("history"), max_lines))
+    }
+
+    pub fn path_for(&self, channel: &str) -> PathBuf {
+        // Logins are only letters, digits and underscores; anything else is dropped
+        // so a typed channel name can't point outside the directory.
+        let name: String = channel
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+        self.dir.join(format!("{}.jsonl", name))
+    }
+
+    /// Adds `message` to the channel's file. The file may grow to twice the cap
+    /// before it is cut back, so it isn't rewritten on every message.
+    pub fn append(&self, channel: &str, message: &ChatMessage) -> Result<(), Report> {
+        let path = self.path_for(channel);
+        let mut counts = LINE_COUNTS.lock();
+        let count = match counts.get(&path) {
+            Some(count) => *count,
+            None => self.trim(&path)?.len(),
+        };
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {:?}", self.dir))?;
+        let line = serde_json::to_string(message)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open chat history {:?}", path))?;
+        writeln!(file, "{}", line)?;
+
+        let count = if count + 1 > self.max_lines * 2 {
+            self.trim(&path)?.len()
+        } else {
+            count + 1
+        };
+        counts.insert(path, count);
+        Ok(())
+    }
+
+    /// The channel's most recent messages, oldest first. Unreadable lines are
+    /// skipped, and a channel without history has none.
+    pub fn load_tail(&self, channel: &str) -> Result<Vec<ChatMessage>, Report> {
+        let path = self.path_for(channel);
+        let mut counts = LINE_COUNTS.lock();
+        let lines = self.trim(&path)?;
+        counts.insert(path, lines.len());
+        Ok(lines
+            .iter()
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(message) => Some(message),
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable chat history line: {}", e);
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Cuts the file down to its last `max_lines` lines and returns them.
+    fn trim(&self, path: &Path) -> Result<Vec<String>, Report> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read chat history {:?}", path));
+            }
+        };
+        let lines: Vec<&str> = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let tail = &lines[lines.len().saturating_sub(self.max_lines)..];
+        if tail.len() < lines.len() {
+            let mut kept = tail.join("\n");
+            kept.push('\n');
+            fs::write(path, kept)
+                .with_context(|| format!("Failed to trim chat history {:?}", path))?;
+        }
+        Ok(tail.iter().map(|line| line.to_string()).collect())
+    }
+}
@@ -0,0 +1,376 @@
+use crate::{
+    core::chat::{AnnouncementColor, ChatApi, RateLimited},
+    emotes::twitch_api::emote_url,
+    events::app_event::{AppEvent, ChatEvent},
+    models::{
+        emote::{Emote, EmoteSource},
+        message::{ChatMessage, MessageFragment, MessageKind},
+    },
+};
+use async_trait::async_trait;
+use chrono::Local;
+use eyre::{Report, eyre};
+use parking_lot::Mutex;
+use reqwest::StatusCode;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use twitch_api::helix::{ClientRequestError, HelixRequestGetError};
+use twitch_oauth2::{AccessToken, ClientId, UserToken};
+use twitch_types::{UserId, UserIdRef, UserName};
+
+pub const MOCK_USER_LOGIN: &str = "mock_user";
+pub const MOCK_CHANNEL: &str = "mock_channel";
+pub const DEFAULT_RATE: f32 = 2.0;
+
+/// A fake chatter's name and name color.
+type MockChatter = (&'static str, Option<(u8, u8, u8)>);
+
+const MOCK_CHATTERS: &[MockChatter] = &[
+    ("Streamer", Some((145, 70, 255))),
+    ("lurker42", None),
+    ("PogFan", Some((0, 200, 120))),
+    ("さくら", Some((255, 105, 180))),
+    ("김민준", Some((30, 144, 255))),
+    ("小明", Some((255, 140, 0))),
+    ("a_very_long_username_for_layout", Some((200, 200, 0))),
+];
+
+const MOCK_EMOTES: &[(&str, &str)] = &[
+    ("25", "Kappa"),
+    ("425618", "LUL"),
+    ("305954156", "PogChamp"),
+    ("86", "BibleThump"),
+];
+
+const MOCK_TEXTS: &[&str] = &[
+    "hello chat",
+    "did you see that?",
+    "check out https://www.twitch.tv/directory",
+    "こんにちは！",
+    "안녕하세요 여러분",
+    "this is a much longer message that should wrap across several lines in a narrow window so the layout can be checked",
+    "docs at example.com/docs and more",
+    "",
+];
+
+/// A token that is never sent anywhere, used to boot straight into the logged-in UI.
+pub fn mock_token() -> UserToken {
+    UserToken::from_existing_unchecked(
+        AccessToken::new("mock".to_string()),
+        None,
+        ClientId::new("mock".to_string()),
+        None,
+        UserName::from(MOCK_USER_LOGIN),
+        UserId::from("0"),
+        None,
+        None,
+    )
+}
+
+/// Feeds synthetic chat messages to the UI at `messages_per_sec` until the app exits.
+/// The sequence is deterministic so screenshots and rendering checks are reproducible.
+pub async fn run_mock_chat(tx: mpsc::Sender<AppEvent>, messages_per_sec: f32) {
+    let mut ticker = tokio::time::interval(Duration::from_secs_f32(
+        1.0 / messages_per_sec.clamp(0.1, 1000.0),
+    ));
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let _ = tx.send(AppEvent::Chat(ChatEvent::Connected)).await;
+
+    for n in 0u64.. {
+        ticker.tick().await;
+        let message = mock_message(n, &mut seed);
+        if tx
+            .send(AppEvent::Chat(ChatEvent::NewChatMessage(message)))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn mock_message(n: u64, seed: &mut u64) -> ChatMessage {
+    let (sender_name, sender_color) = MOCK_CHATTERS[next(seed) % MOCK_CHATTERS.len()];
+
+    let mut fragments = Vec::new();
+    let text = MOCK_TEXTS[next(seed) % MOCK_TEXTS.len()];
+    if !text.is_empty() {
+        fragments.push(MessageFragment::Text(format!("{} ", text)));
+    }
+    for _ in 0..next(seed) % 4 {
+        let (id, name) = MOCK_EMOTES[next(seed) % MOCK_EMOTES.len()];
+        fragments.push(MessageFragment::Emote(Emote {
+            id: id.to_string(),
+            name: name.to_string(),
+            url: emote_url(id, true, "1.0"),
+            source: EmoteSource::Twitch,
+            large_url: None,
+        }));
+        fragments.push(MessageFragment::Text(" ".to_string()));
+    }
+    if fragments.is_empty() {
+        fragments.push(MessageFragment::Text("hi".to_string()));
+    }
+
+    ChatMessage {
+        id: format!("mock-{}", n),
+        sender_id: format!("mock-user-{}", sender_name),
+        sender_name: sender_name.to_string(),
+        sender_color,
+        fragments,
+        timestamp: Local::now(),
+        deleted: false,
+        is_action: false,
+        kind: MessageKind::Chat,
+        low_trust: None,
+        reply_to: None,
+    }
+}
+
+/// xorshift64; good enough for varied but repeatable fake chat.
+fn next(seed: &mut u64) -> usize {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    (*seed % 1_000_000) as usize
+}
+
+/// A call made against [`MockChatApi`], with the arguments worth asserting on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatApiCall {
+    GetUserId(String),
+    SendChatMessage {
+        broadcaster_id: String,
+        message: String,
+        reply_parent_id: Option<String>,
+    },
+    SendAnnouncement {
+        broadcaster_id: String,
+        message: String,
+    },
+    StartRaid {
+        to_broadcaster_id: String,
+    },
+    CancelRaid,
+    UpdateRedemptionStatus {
+        redemption_id: String,
+        fulfilled: bool,
+    },
+    BanUser {
+        user_id: String,
+        duration_secs: Option<u32>,
+    },
+    ResolveHeldMessage {
+        message_id: String,
+        allow: bool,
+    },
+    SendWhisper {
+        to_user_id: String,
+        message: String,
+    },
+    ClearChat,
+    GetChatters,
+}
+
+/// A [`ChatApi`] that never touches the network.
+///
+/// Every login resolves to the id `"id-<login>"` unless listed in `unknown_logins`,
+/// and every other call succeeds unless `fail_with` is set. With `expired_token`
+/// set, every call instead fails the way Helix refuses a revoked token. The first
+/// `rate_limited_sends` chat messages are refused as [`RateLimited`], with the limit
+/// resetting right away. Calls are recorded in order so tests can assert on what
+/// would have been sent.
+#[derive(Default)]
+pub struct MockChatApi {
+    pub unknown_logins: Vec<String>,
+    pub fail_with: Option<String>,
+    pub expired_token: bool,
+    pub rate_limited_sends: usize,
+    calls: Mutex<Vec<ChatApiCall>>,
+}
+
+impl MockChatApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calls(&self) -> Vec<ChatApiCall> {
+        self.calls.lock().clone()
+    }
+
+    fn record(&self, call: ChatApiCall) -> Result<(), Report> {
+        self.calls.lock().push(call);
+        if self.expired_token {
+            return Err(unauthorized());
+        }
+        match &self.fail_with {
+            Some(err) => Err(eyre!("{}", err)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The error Helix answers with once the token has expired or been revoked.
+fn unauthorized() -> Report {
+    Report::new(ClientRequestError::<reqwest::Error>::HelixRequestGetError(
+        HelixRequestGetError::Error {
+            error: "Unauthorized".to_string(),
+            status: StatusCode::UNAUTHORIZED,
+            message: "Invalid OAuth token".to_string(),
+            uri: Default::default(),
+        },
+    ))
+}
+
+#[async_trait]
+impl ChatApi for MockChatApi {
+    async fn get_user_id(&self, login: &str, _token: &UserToken) -> Result<Option<UserId>, Report> {
+        self.calls
+            .lock()
+            .push(ChatApiCall::GetUserId(login.to_string()));
+        if self.expired_token {
+            return Err(unauthorized());
+        }
+        if self
+            .unknown_logins
+            .iter()
+            .any(|l| l.eq_ignore_ascii_case(login))
+        {
+            return Ok(None);
+        }
+        Ok(Some(UserId::from(format!("id-{}", login.to_lowercase()))))
+    }
+
+    async fn send_chat_message(
+        &self,
+        broadcaster_id: &UserIdRef,
+        _sender_id: &UserIdRef,
+        message: &str,
+        reply_parent_id: Option<&str>,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        let sent_before = self
+            .calls()
+            .iter()
+            .filter(|call| matches!(call, ChatApiCall::SendChatMessage { .. }))
+            .count();
+        self.record(ChatApiCall::SendChatMessage {
+            broadcaster_id: broadcaster_id.to_string(),
+            message: message.to_string(),
+            reply_parent_id: reply_parent_id.map(str::to_string),
+        })?;
+        if sent_before < self.rate_limited_sends {
+            return Err(RateLimited {
+                reset_at: Some(SystemTime::now()),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    async fn send_announcement(
+        &self,
+        broadcaster_id: &UserIdRef,
+        _moderator_id: &UserIdRef,
+        message: &str,
+        _color: Option<AnnouncementColor>,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::SendAnnouncement {
+            broadcaster_id: broadcaster_id.to_string(),
+            message: message.to_string(),
+        })
+    }
+
+    async fn start_raid(
+        &self,
+        _from_broadcaster_id: &UserIdRef,
+        to_broadcaster_id: &UserIdRef,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::StartRaid {
+            to_broadcaster_id: to_broadcaster_id.to_string(),
+        })
+    }
+
+    async fn cancel_raid(
+        &self,
+        _broadcaster_id: &UserIdRef,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::CancelRaid)
+    }
+
+    async fn update_redemption_status(
+        &self,
+        _broadcaster_id: &UserIdRef,
+        _reward_id: &str,
+        redemption_id: &str,
+        fulfilled: bool,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::UpdateRedemptionStatus {
+            redemption_id: redemption_id.to_string(),
+            fulfilled,
+        })
+    }
+
+    async fn ban_user(
+        &self,
+        _broadcaster_id: &UserIdRef,
+        _moderator_id: &UserIdRef,
+        user_id: &UserIdRef,
+        _reason: &str,
+        duration_secs: Option<u32>,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::BanUser {
+            user_id: user_id.to_string(),
+            duration_secs,
+        })
+    }
+
+    async fn resolve_held_message(
+        &self,
+        _moderator_id: &UserIdRef,
+        message_id: &str,
+        allow: bool,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::ResolveHeldMessage {
+            message_id: message_id.to_string(),
+            allow,
+        })
+    }
+
+    async fn clear_chat(
+        &self,
+        _broadcaster_id: &UserIdRef,
+        _moderator_id: &UserIdRef,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::ClearChat)
+    }
+
+    async fn send_whisper(
+        &self,
+        _from_user_id: &UserIdRef,
+        to_user_id: &UserIdRef,
+        message: &str,
+        _token: &UserToken,
+    ) -> Result<(), Report> {
+        self.record(ChatApiCall::SendWhisper {
+            to_user_id: to_user_id.to_string(),
+            message: message.to_string(),
+        })
+    }
+
+    async fn get_chatters(
+        &self,
+        _broadcaster_id: &UserIdRef,
+        _moderator_id: &UserIdRef,
+        _token: &UserToken,
+    ) -> Result<Vec<String>, Report> {
+        self.record(ChatApiCall::GetChatters).map(|_| Vec::new())
+    }
+}
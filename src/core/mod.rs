@@ -1,3 +1,12 @@
 pub mod auth;
 pub mod chat;
+pub mod commands;
 pub mod eventsub;
+pub mod export;
+pub mod history;
+pub mod mock;
+pub mod proxy;
+pub mod replay;
+pub mod send;
+pub mod storage;
+pub mod tokenize;
@@ -0,0 +1,162 @@
+use eyre::{Context, eyre};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use reqwest::{ClientBuilder, Proxy};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, client_async_tls};
+use url::Url;
+
+/// The largest proxy `CONNECT` response we're willing to read.
+const MAX_CONNECT_RESPONSE_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Default)]
+struct ProxySettings {
+    http: Option<Url>,
+    https: Option<Url>,
+}
+
+/// Proxies from the config. Every client is built from these, so they're kept
+/// process-wide rather than threaded through each constructor.
+static SETTINGS: Lazy<RwLock<ProxySettings>> = Lazy::new(Default::default);
+
+/// Parses a proxy URL as entered in the config, e.g. `http://proxy.example:3128`.
+///
+/// The websocket tunnel speaks plain `CONNECT` without authentication, so proxies
+/// reached over TLS or needing a login are refused here rather than failing later.
+pub fn parse_proxy_url(url: &str) -> Result<Url, eyre::Report> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid proxy URL '{}'", url))?;
+    if parsed.scheme() == "https" {
+        return Err(eyre!(
+            "Unsupported proxy URL '{}': connecting to the proxy over https isn't \
+             supported, use http://",
+            url
+        ));
+    }
+    if parsed.scheme() != "http" || parsed.host_str().is_none() {
+        return Err(eyre!(
+            "Invalid proxy URL '{}': expected something like http://host:port",
+            url
+        ));
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        // Not echoing the URL, which holds the password.
+        return Err(eyre!(
+            "Unsupported proxy URL: proxies that need a username and password aren't supported"
+        ));
+    }
+    Ok(parsed)
+}
+
+/// Sets the proxies used by clients and connections created from now on. Empty
+/// values fall back to the `HTTP_PROXY`/`HTTPS_PROXY` environment variables.
+pub fn configure(http: Option<&str>, https: Option<&str>) -> Result<(), eyre::Report> {
+    let parse = |url: Option<&str>| {
+        url.map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(parse_proxy_url)
+            .transpose()
+    };
+    let settings = ProxySettings {
+        http: parse(http)?,
+        https: parse(https)?,
+    };
+    *SETTINGS.write() = settings;
+    Ok(())
+}
+
+fn env_proxy(names: &[&str]) -> Option<Url> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| match parse_proxy_url(&value) {
+            Ok(url) => Some(url),
+            Err(e) => {
+                tracing::warn!("Ignoring proxy environment variable: {}", e);
+                None
+            }
+        })
+}
+
+fn http_proxy() -> Option<Url> {
+    let configured = SETTINGS.read().http.clone();
+    configured.or_else(|| env_proxy(&["HTTP_PROXY", "http_proxy"]))
+}
+
+fn https_proxy() -> Option<Url> {
+    let configured = SETTINGS.read().https.clone();
+    configured.or_else(|| env_proxy(&["HTTPS_PROXY", "https_proxy"]))
+}
+
+/// A `reqwest` client builder that goes through the configured proxies.
+pub fn client_builder() -> ClientBuilder {
+    // Adding any proxy turns off reqwest's own environment lookup, so both are set
+    // explicitly, with the environment already folded in.
+    let mut builder = reqwest::Client::builder();
+    if let Some(url) = http_proxy() {
+        match Proxy::http(url.as_str()) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => tracing::warn!("Ignoring HTTP proxy {}: {}", url, e),
+        }
+    }
+    if let Some(url) = https_proxy() {
+        match Proxy::https(url.as_str()) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => tracing::warn!("Ignoring HTTPS proxy {}: {}", url, e),
+        }
+    }
+    builder
+}
+
+/// Opens a websocket to a `wss://` URL, tunneling through the HTTPS proxy if one is set.
+pub async fn connect_websocket(
+    url: &str,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, eyre::Report> {
+    let Some(proxy) = https_proxy() else {
+        let (ws_stream, _) = tokio_tungstenite::connect_async(url).await?;
+        return Ok(ws_stream);
+    };
+
+    let target = Url::parse(url)?;
+    let host = target
+        .host_str()
+        .ok_or_else(|| eyre!("No host in websocket URL {}", url))?;
+    let port = target.port_or_known_default().unwrap_or(443);
+    let proxy_host = proxy.host_str().unwrap_or_default();
+    let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+
+    let mut stream = TcpStream::connect((proxy_host, proxy_port))
+        .await
+        .with_context(|| format!("Could not reach proxy {}", proxy))?;
+    let request = format!(
+        "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n\r\n",
+        host = host,
+        port = port
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if stream.read(&mut byte).await? == 0 || response.len() > MAX_CONNECT_RESPONSE_BYTES {
+            return Err(eyre!("Proxy {} sent an invalid CONNECT response", proxy));
+        }
+        response.push(byte[0]);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(eyre!(
+            "Proxy {} refused to connect to {}: {}",
+            proxy,
+            host,
+            status_line
+        ));
+    }
+
+    let (ws_stream, _) = client_async_tls(url, stream).await?;
+    Ok(ws_stream)
+}
@@ -0,0 +1,28 @@
+use crate::models::message::ChatMessage;
+use eyre::Context;
+use std::path::Path;
+
+/// Reads a chat log written as one JSON-serialized `ChatMessage` per line.
+///
+/// Lines that fail to parse (e.g. a partially written last line) are skipped rather
+/// than failing the whole load. Messages are returned in timestamp order.
+pub async fn load_log(path: &Path) -> Result<Vec<ChatMessage>, eyre::Report> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read chat log {:?}", path))?;
+
+    let mut messages: Vec<ChatMessage> = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                tracing::warn!("Skipping unreadable chat log line: {}", e);
+                None
+            }
+        })
+        .collect();
+
+    messages.sort_by_key(|m| m.timestamp);
+    Ok(messages)
+}
@@ -0,0 +1,144 @@
+use crate::{
+    core::chat::{AnnouncementColor, ChatApi, MAX_QUEUE_DELAY, RateLimited},
+    events::app_event::{AppEvent, ChatEvent},
+};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::mpsc;
+use twitch_oauth2::UserToken;
+
+/// A batch of chat messages to send to one channel, already paced by the
+/// [`crate::core::chat::MessageQueue`].
+pub struct ChatSend {
+    pub channel: String,
+    /// Each message with how long after `start` it may go out.
+    pub messages: Vec<(String, Duration)>,
+    pub start: Instant,
+    /// The message the first one replies to, if any.
+    pub reply_parent_id: Option<String>,
+    pub is_announcement: bool,
+}
+
+/// Sends `send` as the owner of `token` and reports [`ChatEvent::MessageSent`] once
+/// all of it went out, or the error that stopped it.
+pub async fn send_chat(
+    chat_client: &dyn ChatApi,
+    token: &UserToken,
+    send: ChatSend,
+    tx: &mpsc::Sender<AppEvent>,
+) {
+    let broadcaster_id = match chat_client.get_user_id(&send.channel, token).await {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            let _ = tx
+                .send(AppEvent::Chat(ChatEvent::MessageSendError(
+                    "Channel not found".to_string(),
+                )))
+                .await;
+            return;
+        }
+        Err(e) => {
+            let event = ChatEvent::from_api_error(&e, |e| {
+                ChatEvent::MessageSendError(format!("Failed to look up the channel: {}", e))
+            });
+            let _ = tx.send(AppEvent::Chat(event)).await;
+            return;
+        }
+    };
+    for (i, (message, delay)) in send.messages.iter().enumerate() {
+        tokio::time::sleep_until((send.start + *delay).into()).await;
+        let reply_parent_id = send.reply_parent_id.as_deref().filter(|_| i == 0);
+        // A send Twitch refuses for the rate limit is tried once more when the
+        // limit resets, if that's soon enough to wait for.
+        let mut retried = false;
+        let result = loop {
+            let result = if send.is_announcement {
+                chat_client
+                    .send_announcement(
+                        broadcaster_id.as_ref(),
+                        token.user_id.as_ref(),
+                        message,
+                        Some(AnnouncementColor::Primary),
+                        token,
+                    )
+                    .await
+            } else {
+                chat_client
+                    .send_chat_message(
+                        broadcaster_id.as_ref(),
+                        token.user_id.as_ref(),
+                        message,
+                        reply_parent_id,
+                        token,
+                    )
+                    .await
+            };
+            let retry_after = result
+                .as_ref()
+                .err()
+                .and_then(|e| e.downcast_ref::<RateLimited>())
+                .and_then(|limited| limited.retry_after(SystemTime::now()))
+                .filter(|wait| !retried && *wait <= MAX_QUEUE_DELAY);
+            let Some(wait) = retry_after else {
+                break result;
+            };
+            retried = true;
+            let _ = tx
+                .send(AppEvent::Chat(ChatEvent::MessageQueued(wait)))
+                .await;
+            tokio::time::sleep(wait).await;
+        };
+        if let Err(e) = result {
+            let event = ChatEvent::from_api_error(&e, |e| {
+                ChatEvent::MessageSendError(format!("Failed to send: {}", e))
+            });
+            let _ = tx.send(AppEvent::Chat(event)).await;
+            return;
+        }
+    }
+    let _ = tx.send(AppEvent::Chat(ChatEvent::MessageSent)).await;
+}
+
+/// Whispers `message` to `recipient` as the owner of `token` and reports the
+/// outcome like [`send_chat`].
+pub async fn send_whisper(
+    chat_client: &dyn ChatApi,
+    token: &UserToken,
+    recipient: &str,
+    message: &str,
+    tx: &mpsc::Sender<AppEvent>,
+) {
+    let recipient_id = match chat_client.get_user_id(recipient, token).await {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            let _ = tx
+                .send(AppEvent::Chat(ChatEvent::MessageSendError(format!(
+                    "User '{}' not found",
+                    recipient
+                ))))
+                .await;
+            return;
+        }
+        Err(e) => {
+            let event = ChatEvent::from_api_error(&e, |e| {
+                ChatEvent::MessageSendError(format!("Failed to look up '{}': {}", recipient, e))
+            });
+            let _ = tx.send(AppEvent::Chat(event)).await;
+            return;
+        }
+    };
+    let event = match chat_client
+        .send_whisper(
+            token.user_id.as_ref(),
+            recipient_id.as_ref(),
+            message,
+            token,
+        )
+        .await
+    {
+        Ok(()) => ChatEvent::MessageSent,
+        Err(e) => ChatEvent::from_api_error(&e, |e| {
+            ChatEvent::MessageSendError(format!("Failed to whisper: {}", e))
+        }),
+    };
+    let _ = tx.send(AppEvent::Chat(event)).await;
+}
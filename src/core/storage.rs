@@ -0,0 +1,66 @@
+use once_cell::sync::{Lazy, OnceCell};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Set from `--config-dir` before anything reads the data directory.
+static OVERRIDE: OnceCell<PathBuf> = OnceCell::new();
+
+/// The directory settings and login tokens are stored in, resolved on first use.
+/// `None` means none of the candidates could be written to, and the app runs
+/// without persisting anything.
+static DATA_DIR: Lazy<Option<PathBuf>> = Lazy::new(resolve);
+
+/// Uses `path` instead of the platform config directory. Has no effect once the
+/// data directory has been resolved.
+pub fn set_override(path: PathBuf) {
+    if OVERRIDE.set(path).is_err() {
+        tracing::warn!("The config directory was already overridden");
+    }
+}
+
+/// Where settings and tokens live, or `None` in in-memory mode.
+pub fn data_dir() -> Option<&'static Path> {
+    DATA_DIR.as_deref()
+}
+
+/// Whether settings and logins are only kept for this session.
+pub fn is_in_memory() -> bool {
+    data_dir().is_none()
+}
+
+/// Tries, in order: the `--config-dir` override, the platform config directory,
+/// the XDG config directory and finally the working directory. The first one that
+/// can actually be written to wins.
+fn resolve() -> Option<PathBuf> {
+    let app_dir = |base: PathBuf| base.join(env!("CARGO_PKG_NAME"));
+    let candidates = [
+        OVERRIDE.get().cloned(),
+        dirs::config_dir().map(app_dir),
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+            .map(app_dir),
+        std::env::current_dir().ok().map(app_dir),
+    ];
+
+    for dir in candidates.into_iter().flatten() {
+        match ensure_writable(&dir) {
+            Ok(()) => {
+                tracing::info!("Storing settings in {:?}", dir);
+                return Some(dir);
+            }
+            Err(e) => tracing::warn!("Can't use {:?} for settings: {}", dir, e),
+        }
+    }
+    tracing::error!("No writable config directory found; settings and logins won't be saved");
+    None
+}
+
+fn ensure_writable(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(".write_test");
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
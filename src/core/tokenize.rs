@@ -0,0 +1,228 @@
+use crate::{
+    emotes::twitch_api::{Cheermote, emote_url},
+    models::{
+        emote::{Emote, EmoteSource},
+        message::MessageFragment,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A piece of a message as Twitch split it, before emotes from other sources are
+/// substituted in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RawFragment {
+    Text(String),
+    TwitchEmote { id: String, text: String },
+    Mention(String),
+    Cheer { prefix: String, bits: i64 },
+}
+
+/// Which emote a code means when a Twitch emote and a third-party emote share it.
+///
+/// Twitch tags the emotes a sender is allowed to use itself, so a colliding code
+/// only reaches text matching when it isn't a Twitch emote for that sender; there
+/// the third-party emote is always used. The precedence only decides what happens
+/// to codes Twitch did tag.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmotePrecedence {
+    /// Keep the emote Twitch tagged.
+    #[default]
+    Twitch,
+    /// Show the third-party emote instead, for channels that override a global code.
+    ThirdParty,
+}
+
+/// Emotes from sources other than Twitch, looked up by their exact code.
+#[derive(Clone, Debug, Default)]
+pub struct EmoteRegistry {
+    emotes: HashMap<String, Emote>,
+    precedence: EmotePrecedence,
+}
+
+impl EmoteRegistry {
+    pub fn new(precedence: EmotePrecedence) -> Self {
+        Self {
+            emotes: HashMap::new(),
+            precedence,
+        }
+    }
+
+    /// Adds `emote`, replacing any earlier emote with the same code.
+    pub fn insert(&mut self, emote: Emote) {
+        self.emotes.insert(emote.name.clone(), emote);
+    }
+
+    pub fn get(&self, code: &str) -> Option<&Emote> {
+        self.emotes.get(code)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.emotes.is_empty()
+    }
+}
+
+impl Extend<Emote> for EmoteRegistry {
+    /// Inserts in order, so later emotes replace earlier ones with the same code.
+    fn extend<I: IntoIterator<Item = Emote>>(&mut self, iter: I) {
+        for emote in iter {
+            self.insert(emote);
+        }
+    }
+}
+
+impl FromIterator<Emote> for EmoteRegistry {
+    fn from_iter<I: IntoIterator<Item = Emote>>(iter: I) -> Self {
+        let mut registry = Self::default();
+        registry.extend(iter);
+        registry
+    }
+}
+
+/// Turns Twitch's fragments into the fragments the chat log renders. Words in text
+/// fragments that exactly match a code in `registry` become emotes; everything else,
+/// URLs included, stays text for the renderer to deal with. Adjacent text is merged.
+/// Emotes Twitch tagged are replaced by a third-party one with the same code only
+/// under [`EmotePrecedence::ThirdParty`].
+pub fn tokenize(fragments: &[RawFragment], registry: &EmoteRegistry) -> Vec<MessageFragment> {
+    let mut tokens = Vec::new();
+    for fragment in fragments {
+        match fragment {
+            RawFragment::Text(text) => tokenize_text(text, registry, &mut tokens),
+            RawFragment::TwitchEmote { id, text } => {
+                let emote = match registry.get(text) {
+                    Some(emote) if registry.precedence == EmotePrecedence::ThirdParty => {
+                        emote.clone()
+                    }
+                    _ => Emote {
+                        id: id.clone(),
+                        name: text.clone(),
+                        url: emote_url(id, true, "1.0"),
+                        source: EmoteSource::Twitch,
+                        large_url: None,
+                    },
+                };
+                tokens.push(MessageFragment::Emote(emote));
+            }
+            RawFragment::Mention(name) => tokens.push(MessageFragment::Mention(name.clone())),
+            RawFragment::Cheer { prefix, bits } => tokens.push(MessageFragment::Cheer {
+                prefix: prefix.clone(),
+                bits: *bits,
+            }),
+        }
+    }
+    tokens
+}
+
+/// Swaps cheers for their tier image where `cheermotes` has one for the prefix,
+/// which is matched ignoring case like Twitch does.
+pub fn resolve_cheermotes(fragments: &mut [MessageFragment], cheermotes: &[Cheermote]) {
+    for fragment in fragments {
+        let MessageFragment::Cheer { prefix, bits } = fragment else {
+            continue;
+        };
+        let emote = cheermotes
+            .iter()
+            .find(|cheermote| cheermote.prefix.eq_ignore_ascii_case(prefix))
+            .and_then(|cheermote| cheermote.emote_for(*bits));
+        if let Some(emote) = emote {
+            *fragment = MessageFragment::Cheermote {
+                prefix: std::mem::take(prefix),
+                bits: *bits,
+                emote,
+            };
+        }
+    }
+}
+
+/// Normalizes the spacing of a tokenized message so it renders without odd gaps:
+/// runs of whitespace inside text become a single space and the message is trimmed
+/// at both ends. The single space between two emotes is kept unless
+/// `collapse_emotes` is set, in which case the emotes are drawn flush together.
+pub fn normalize_whitespace(
+    fragments: Vec<MessageFragment>,
+    collapse_emotes: bool,
+) -> Vec<MessageFragment> {
+    let mut merged = Vec::with_capacity(fragments.len());
+    for fragment in fragments {
+        match fragment {
+            MessageFragment::Text(text) => push_text(&mut merged, &text),
+            other => merged.push(other),
+        }
+    }
+
+    let last = merged.len().saturating_sub(1);
+    let mut normalized: Vec<MessageFragment> = Vec::with_capacity(merged.len());
+    for (i, fragment) in merged.iter().enumerate() {
+        let MessageFragment::Text(text) = fragment else {
+            normalized.push(fragment.clone());
+            continue;
+        };
+        let mut text = collapse_spaces(text);
+        if i == 0 {
+            text = text.trim_start().to_string();
+        }
+        if i == last {
+            text = text.trim_end().to_string();
+        }
+        let between_emotes = text == " "
+            && matches!(normalized.last(), Some(MessageFragment::Emote(_)))
+            && matches!(merged.get(i + 1), Some(MessageFragment::Emote(_)));
+        if text.is_empty() || (collapse_emotes && between_emotes) {
+            continue;
+        }
+        normalized.push(MessageFragment::Text(text));
+    }
+    normalized
+}
+
+/// Replaces every run of whitespace in `text` with a single space.
+fn collapse_spaces(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    collapsed
+}
+
+fn tokenize_text(text: &str, registry: &EmoteRegistry, tokens: &mut Vec<MessageFragment>) {
+    if registry.is_empty() {
+        push_text(tokens, text);
+        return;
+    }
+    let mut rest = text;
+    while !rest.is_empty() {
+        let word_start = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        push_text(tokens, &rest[..word_start]);
+        rest = &rest[word_start..];
+
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..word_end];
+        match registry.get(word) {
+            Some(emote) => tokens.push(MessageFragment::Emote(emote.clone())),
+            None => push_text(tokens, word),
+        }
+        rest = &rest[word_end..];
+    }
+}
+
+fn push_text(tokens: &mut Vec<MessageFragment>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match tokens.last_mut() {
+        Some(MessageFragment::Text(previous)) => previous.push_str(text),
+        _ => tokens.push(MessageFragment::Text(text.to_string())),
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    core::proxy,
+    models::emote::{Emote, EmoteSource},
+};
+use eyre::Report;
+use parking_lot::Mutex;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const API_BASE: &str = "https://api.betterttv.net/3/cached";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BttvEmote {
+    id: String,
+    code: String,
+    /// `png`, `gif` or `webp`. All three are served as is; WebP is decoded through
+    /// egui_extras' `webp` feature.
+    image_type: String,
+}
+
+impl BttvEmote {
+    fn to_emote(&self) -> Emote {
+        Emote {
+            id: self.id.clone(),
+            name: self.code.clone(),
+            url: format!(
+                "https://cdn.betterttv.net/emote/{}/1x.{}",
+                self.id, self.image_type
+            ),
+            source: EmoteSource::Bttv,
+            large_url: Some(format!(
+                "https://cdn.betterttv.net/emote/{}/3x.{}",
+                self.id, self.image_type
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChannelEmotesResponse {
+    #[serde(default)]
+    channel_emotes: Vec<BttvEmote>,
+    /// Emotes the channel added from other users' uploads.
+    #[serde(default)]
+    shared_emotes: Vec<BttvEmote>,
+}
+
+/// Fetches BetterTTV emotes. Results are kept for the rest of the session, so
+/// switching back to a channel doesn't look its emotes up again.
+pub struct BttvClient {
+    client: reqwest::Client,
+    global_emotes: Mutex<Option<Vec<Emote>>>,
+    /// Keyed by Twitch broadcaster id.
+    channel_emotes: Mutex<HashMap<String, Vec<Emote>>>,
+}
+
+impl Default for BttvClient {
+    fn default() -> Self {
+        Self {
+            client: proxy::client_builder()
+                .build()
+                .expect("Failed to build reqwest client"),
+            global_emotes: Mutex::new(None),
+            channel_emotes: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl BttvClient {
+    pub async fn get_global_emotes(&self) -> Result<Vec<Emote>, Report> {
+        if let Some(emotes) = self.global_emotes.lock().as_ref() {
+            return Ok(emotes.clone());
+        }
+        let emotes: Vec<Emote> = self
+            .client
+            .get(format!("{}/emotes/global", API_BASE))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Vec<BttvEmote>>()
+            .await?
+            .iter()
+            .map(BttvEmote::to_emote)
+            .collect();
+        *self.global_emotes.lock() = Some(emotes.clone());
+        Ok(emotes)
+    }
+
+    /// The channel's own and shared emotes. Channels that never set up BetterTTV
+    /// simply have none.
+    pub async fn get_channel_emotes(&self, broadcaster_id: &str) -> Result<Vec<Emote>, Report> {
+        if let Some(emotes) = self.channel_emotes.lock().get(broadcaster_id) {
+            return Ok(emotes.clone());
+        }
+        let response = self
+            .client
+            .get(format!("{}/users/twitch/{}", API_BASE, broadcaster_id))
+            .send()
+            .await?;
+        let emotes: Vec<Emote> = if response.status() == StatusCode::NOT_FOUND {
+            Vec::new()
+        } else {
+            let channel = response
+                .error_for_status()?
+                .json::<ChannelEmotesResponse>()
+                .await?;
+            channel
+                .channel_emotes
+                .iter()
+                .chain(&channel.shared_emotes)
+                .map(BttvEmote::to_emote)
+                .collect()
+        };
+        self.channel_emotes
+            .lock()
+            .insert(broadcaster_id.to_string(), emotes.clone());
+        Ok(emotes)
+    }
+}
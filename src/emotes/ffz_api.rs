@@ -0,0 +1,148 @@
+use crate::{
+    core::proxy,
+    models::emote::{Emote, EmoteSource},
+};
+use eyre::Report;
+use parking_lot::Mutex;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const API_BASE: &str = "https://api.frankerfacez.com/v1";
+/// Image scales in order of preference; 2x stays crisp at the usual emote sizes,
+/// like 7TV's.
+const PREFERRED_SCALES: [&str; 3] = ["2", "4", "1"];
+
+/// The emote sets in FrankerFaceZ's global and room responses.
+#[derive(Debug, Deserialize)]
+pub struct FfzSets {
+    /// Only in the global response: the sets everyone sees. Other sets in it belong
+    /// to specific users.
+    #[serde(default)]
+    pub default_sets: Option<Vec<u64>>,
+    /// Keyed by set id.
+    #[serde(default)]
+    pub sets: HashMap<String, FfzSet>,
+}
+
+impl FfzSets {
+    pub fn to_emotes(&self) -> Vec<Emote> {
+        let mut ids: Vec<&String> = self
+            .sets
+            .keys()
+            .filter(|id| {
+                self.default_sets
+                    .as_ref()
+                    .is_none_or(|defaults| id.parse::<u64>().is_ok_and(|id| defaults.contains(&id)))
+            })
+            .collect();
+        // Map order is arbitrary; sort so a code in two sets always resolves the same.
+        ids.sort();
+        ids.into_iter()
+            .flat_map(|id| &self.sets[id].emoticons)
+            .filter_map(FfzEmote::to_emote)
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FfzSet {
+    #[serde(default)]
+    pub emoticons: Vec<FfzEmote>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FfzEmote {
+    pub id: u64,
+    pub name: String,
+    /// Image URLs keyed by scale (`"1"`, `"2"`, `"4"`); not every emote has all.
+    #[serde(default)]
+    pub urls: HashMap<String, String>,
+    /// The same for animated emotes, whose `urls` are their first frame.
+    #[serde(default)]
+    pub animated: Option<HashMap<String, String>>,
+}
+
+impl FfzEmote {
+    /// `None` for an emote without any image.
+    pub fn to_emote(&self) -> Option<Emote> {
+        let urls = self.animated.as_ref().unwrap_or(&self.urls);
+        let https = |url: &String| match url.strip_prefix("//") {
+            Some(rest) => format!("https://{}", rest),
+            None => url.clone(),
+        };
+        let url = PREFERRED_SCALES.iter().find_map(|scale| urls.get(*scale))?;
+        Some(Emote {
+            id: self.id.to_string(),
+            name: self.name.clone(),
+            url: https(url),
+            source: EmoteSource::Ffz,
+            large_url: urls.get("4").map(https),
+        })
+    }
+}
+
+/// Fetches FrankerFaceZ emotes. Like BetterTTV's, results are kept for the rest of
+/// the session.
+pub struct FfzClient {
+    client: reqwest::Client,
+    global_emotes: Mutex<Option<Vec<Emote>>>,
+    /// Keyed by Twitch broadcaster id.
+    channel_emotes: Mutex<HashMap<String, Vec<Emote>>>,
+}
+
+impl Default for FfzClient {
+    fn default() -> Self {
+        Self {
+            client: proxy::client_builder()
+                .build()
+                .expect("Failed to build reqwest client"),
+            global_emotes: Mutex::new(None),
+            channel_emotes: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl FfzClient {
+    pub async fn get_global_emotes(&self) -> Result<Vec<Emote>, Report> {
+        if let Some(emotes) = self.global_emotes.lock().as_ref() {
+            return Ok(emotes.clone());
+        }
+        let emotes = self
+            .client
+            .get(format!("{}/set/global", API_BASE))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<FfzSets>()
+            .await?
+            .to_emotes();
+        *self.global_emotes.lock() = Some(emotes.clone());
+        Ok(emotes)
+    }
+
+    /// The emotes of the channel's FrankerFaceZ room. Channels without one have none.
+    pub async fn get_channel_emotes(&self, broadcaster_id: &str) -> Result<Vec<Emote>, Report> {
+        if let Some(emotes) = self.channel_emotes.lock().get(broadcaster_id) {
+            return Ok(emotes.clone());
+        }
+        let response = self
+            .client
+            .get(format!("{}/room/id/{}", API_BASE, broadcaster_id))
+            .send()
+            .await?;
+        let emotes = if response.status() == StatusCode::NOT_FOUND {
+            Vec::new()
+        } else {
+            response
+                .error_for_status()?
+                .json::<FfzSets>()
+                .await?
+                .to_emotes()
+        };
+        self.channel_emotes
+            .lock()
+            .insert(broadcaster_id.to_string(), emotes.clone());
+        Ok(emotes)
+    }
+}
@@ -0,0 +1,307 @@
+//! Emote images kept on disk, so channels full of emotes don't download them all
+//! again after a restart. [`EmoteImageLoader`] sits in front of egui's own HTTP
+//! loader: every `http(s)` image URL is served from the cache when it can be, and
+//! fetched and stored otherwise. The least recently used files are deleted once
+//! the cache grows past its size cap.
+
+use crate::core::{proxy, storage};
+use eframe::egui::{
+    self,
+    load::{Bytes, BytesLoadResult, BytesLoader, BytesPoll, LoadError},
+};
+use eyre::{Context, Report};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+/// Eviction goes below the cap by this fraction, so a full cache isn't scanned
+/// again on every new image.
+const EVICT_TO: f64 = 0.9;
+
+/// Image files named by a hash of their URL.
+#[derive(Debug)]
+pub struct EmoteImageCache {
+    dir: PathBuf,
+    max_bytes: AtomicU64,
+    /// Size of everything in `dir`, counted on the first write.
+    total_bytes: Mutex<Option<u64>>,
+}
+
+impl EmoteImageCache {
+    /// Keeps up to `max_bytes` of images in `dir`; 0 turns the cache off.
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            max_bytes: AtomicU64::new(max_bytes),
+            total_bytes: Mutex::new(None),
+        }
+    }
+
+    /// A cache in the data directory, or `None` when nothing is persisted.
+    pub fn from_data_dir(max_bytes: u64) -> Option<Self> {
+        storage::data_dir().map(|dir| Self::new(dir.join("emote_cache"), max_bytes))
+    }
+
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Applies a changed size cap; a smaller one takes effect on the next write.
+    pub fn set_max_bytes(&self, max_bytes: u64) {
+        self.max_bytes.store(max_bytes, Ordering::Relaxed);
+    }
+
+    pub fn path_for(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}", fnv1a(url.as_bytes())))
+    }
+
+    /// The cached image for `url`, marking it as just used.
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let path = self.path_for(url);
+        let bytes = fs::read(&path).ok()?;
+        if let Err(e) = touch(&path) {
+            tracing::debug!("Failed to mark {:?} as used: {}", path, e);
+        }
+        Some(bytes)
+    }
+
+    /// Stores the image for `url`, then deletes the least recently used images if
+    /// that took the cache over its cap.
+    pub fn put(&self, url: &str, bytes: &[u8]) -> Result<(), Report> {
+        let max_bytes = self.max_bytes();
+        if max_bytes == 0 {
+            return Ok(());
+        }
+        let mut total = self.total_bytes.lock();
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {:?}", self.dir))?;
+        let path = self.path_for(url);
+        let replaced = fs::metadata(&path).map_or(0, |m| m.len());
+        fs::write(&path, bytes).with_context(|| format!("Failed to cache {:?}", path))?;
+
+        let size = match *total {
+            Some(size) => size.saturating_sub(replaced) + bytes.len() as u64,
+            None => self.scan()?.iter().map(|(_, _, len)| len).sum(),
+        };
+        *total = Some(if size > max_bytes {
+            self.evict((max_bytes as f64 * EVICT_TO) as u64)?
+        } else {
+            size
+        });
+        Ok(())
+    }
+
+    /// Deletes images, least recently used first, until at most `target` bytes
+    /// remain. Returns the size left.
+    fn evict(&self, target: u64) -> Result<u64, Report> {
+        let mut files = self.scan()?;
+        let mut size: u64 = files.iter().map(|(_, _, len)| len).sum();
+        files.sort_by_key(|(_, used, _)| *used);
+        for (path, _, len) in files {
+            if size <= target {
+                break;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => size -= len,
+                Err(e) => tracing::warn!("Failed to evict {:?} from the emote cache: {}", path, e),
+            }
+        }
+        Ok(size)
+    }
+
+    /// Every cached file with when it was last used and its size.
+    fn scan(&self) -> Result<Vec<(PathBuf, SystemTime, u64)>, Report> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {:?}", self.dir)),
+        };
+        Ok(entries
+            .flatten()
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+                let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                Some((entry.path(), used, metadata.len()))
+            })
+            .collect())
+    }
+}
+
+/// Last use is tracked as the modification time, which needs no index file.
+fn touch(path: &Path) -> std::io::Result<()> {
+    File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now())
+}
+
+/// A hash that stays the same across Rust releases, unlike `DefaultHasher`, so
+/// cached files keep their names.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The image format, from the first bytes of the file. egui's image loaders use
+/// it to pick a decoder, as they would the `Content-Type` of a download.
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"GIF8") {
+        Some("image/gif")
+    } else if bytes.starts_with(b"\x89PNG") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+enum Entry {
+    Pending,
+    Ready(Arc<[u8]>),
+    Failed(String),
+}
+
+/// Serves `http(s)` image URLs through an [`EmoteImageCache`]. Loaded images are
+/// also kept in memory for the session, as egui's HTTP loader does.
+pub struct EmoteImageLoader {
+    cache: Arc<EmoteImageCache>,
+    client: reqwest::Client,
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl EmoteImageLoader {
+    pub const ID: &'static str = egui::generate_loader_id!(EmoteImageLoader);
+
+    pub fn new(cache: Arc<EmoteImageCache>) -> Self {
+        Self {
+            cache,
+            client: proxy::client_builder()
+                .build()
+                .expect("Failed to build reqwest client"),
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl BytesLoader for EmoteImageLoader {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn load(&self, ctx: &egui::Context, uri: &str) -> BytesLoadResult {
+        if !(uri.starts_with("https://") || uri.starts_with("http://"))
+            || self.cache.max_bytes() == 0
+        {
+            return Err(LoadError::NotSupported);
+        }
+        let mut entries = self.entries.lock();
+        match entries.get(uri) {
+            Some(Entry::Pending) => return Ok(BytesPoll::Pending { size: None }),
+            Some(Entry::Ready(bytes)) => {
+                return Ok(BytesPoll::Ready {
+                    size: None,
+                    mime: sniff_mime(bytes).map(str::to_string),
+                    bytes: Bytes::Shared(bytes.clone()),
+                });
+            }
+            Some(Entry::Failed(e)) => return Err(LoadError::Loading(e.clone())),
+            None => {}
+        }
+        entries.insert(uri.to_string(), Entry::Pending);
+        drop(entries);
+
+        let url = uri.to_string();
+        let cache = self.cache.clone();
+        let client = self.client.clone();
+        let entries = self.entries.clone();
+        let ctx = ctx.clone();
+        tokio::spawn(async move {
+            let entry = match fetch(&cache, &client, &url).await {
+                Ok(bytes) => Entry::Ready(bytes.into()),
+                Err(e) => {
+                    tracing::debug!("Failed to load emote image {}: {}", url, e);
+                    Entry::Failed(e.to_string())
+                }
+            };
+            // Forgotten while loading; the next frame asks again.
+            if let Some(slot) = entries.lock().get_mut(&url) {
+                *slot = entry;
+            }
+            ctx.request_repaint();
+        });
+        Ok(BytesPoll::Pending { size: None })
+    }
+
+    fn forget(&self, uri: &str) {
+        self.entries.lock().remove(uri);
+    }
+
+    fn forget_all(&self) {
+        self.entries.lock().clear();
+    }
+
+    fn byte_size(&self) -> usize {
+        self.entries
+            .lock()
+            .values()
+            .map(|entry| match entry {
+                Entry::Ready(bytes) => bytes.len(),
+                Entry::Pending | Entry::Failed(_) => 0,
+            })
+            .sum()
+    }
+
+    fn has_pending(&self) -> bool {
+        self.entries
+            .lock()
+            .values()
+            .any(|entry| matches!(entry, Entry::Pending))
+    }
+}
+
+/// The image from disk if cached, otherwise downloaded and cached.
+async fn fetch(
+    cache: &Arc<EmoteImageCache>,
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Vec<u8>, Report> {
+    let cached = {
+        let cache = cache.clone();
+        let url = url.to_string();
+        tokio::task::spawn_blocking(move || cache.get(&url)).await?
+    };
+    if let Some(bytes) = cached {
+        return Ok(bytes);
+    }
+
+    let bytes = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?
+        .to_vec();
+    let cache = cache.clone();
+    let url = url.to_string();
+    let stored = bytes.clone();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = cache.put(&url, &stored) {
+            tracing::warn!("Failed to cache emote image {}: {}", url, e);
+        }
+    });
+    Ok(bytes)
+}
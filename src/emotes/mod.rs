@@ -1,4 +1,66 @@
 pub mod bttv_api;
 pub mod ffz_api;
-pub mod twitch_api;
+pub mod image_cache;
 pub mod seventv_api;
+pub mod twitch_api;
+
+use crate::models::emote::Emote;
+use bttv_api::BttvClient;
+use eyre::Report;
+use ffz_api::FfzClient;
+use seventv_api::SevenTvClient;
+
+/// A channel's third-party emotes from the enabled providers, ordered so that
+/// inserting them into an [`EmoteRegistry`](crate::core::tokenize::EmoteRegistry)
+/// lets later ones replace earlier ones with the same code. That gives this
+/// precedence, highest first:
+///
+/// 1. Channel emotes over global ones, whichever provider they're from.
+/// 2. 7TV over FrankerFaceZ over BetterTTV.
+///
+/// Whether these replace emotes Twitch itself tagged is up to
+/// [`EmotePrecedence`](crate::core::tokenize::EmotePrecedence). Failures are logged
+/// and leave the affected set out rather than holding up joining the channel.
+pub async fn third_party_emotes(
+    bttv: Option<&BttvClient>,
+    ffz: Option<&FfzClient>,
+    seventv: Option<&SevenTvClient>,
+    broadcaster_id: &str,
+) -> Vec<Emote> {
+    let mut emotes = Vec::new();
+    if let Some(bttv) = bttv {
+        emotes.extend(or_log("global BetterTTV", bttv.get_global_emotes().await));
+    }
+    if let Some(ffz) = ffz {
+        emotes.extend(or_log("global FrankerFaceZ", ffz.get_global_emotes().await));
+    }
+    if let Some(seventv) = seventv {
+        emotes.extend(or_log("global 7TV", seventv.get_global_emotes().await));
+    }
+    if let Some(bttv) = bttv {
+        emotes.extend(or_log(
+            "channel BetterTTV",
+            bttv.get_channel_emotes(broadcaster_id).await,
+        ));
+    }
+    if let Some(ffz) = ffz {
+        emotes.extend(or_log(
+            "channel FrankerFaceZ",
+            ffz.get_channel_emotes(broadcaster_id).await,
+        ));
+    }
+    if let Some(seventv) = seventv {
+        emotes.extend(or_log(
+            "channel 7TV",
+            seventv.get_channel_emotes(broadcaster_id).await,
+        ));
+    }
+    emotes
+}
+
+fn or_log(what: &str, result: Result<Vec<Emote>, Report>) -> Vec<Emote> {
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Failed to fetch {} emotes: {}", what, e);
+        Vec::new()
+    })
+}
@@ -0,0 +1,149 @@
+use crate::{
+    core::proxy,
+    models::emote::{Emote, EmoteSource},
+};
+use eyre::Report;
+use parking_lot::Mutex;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const API_BASE: &str = "https://7tv.io/v3";
+/// Used when an emote doesn't say which host serves it.
+const DEFAULT_HOST: &str = "//cdn.7tv.app/emote";
+
+/// A 7TV emote set, the shape of both the global set and a channel's active set.
+#[derive(Debug, Deserialize)]
+pub struct EmoteSet {
+    /// `null` for an empty set.
+    #[serde(default)]
+    pub emotes: Option<Vec<SevenTvEmote>>,
+}
+
+impl EmoteSet {
+    pub fn to_emotes(&self) -> Vec<Emote> {
+        self.emotes
+            .iter()
+            .flatten()
+            .map(SevenTvEmote::to_emote)
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SevenTvEmote {
+    pub id: String,
+    /// The code as used in this set, which a channel may have renamed.
+    pub name: String,
+    #[serde(default)]
+    pub data: Option<EmoteData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmoteData {
+    #[serde(default)]
+    pub host: Option<EmoteHost>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmoteHost {
+    /// Protocol-relative, e.g. `//cdn.7tv.app/emote/<id>`; 7TV moves emotes between
+    /// hosts, so this is preferred over building the URL from the id.
+    pub url: String,
+}
+
+impl SevenTvEmote {
+    /// 7TV serves every emote as WebP, animated ones included.
+    pub fn to_emote(&self) -> Emote {
+        let host = self
+            .data
+            .as_ref()
+            .and_then(|data| data.host.as_ref())
+            .map(|host| host.url.clone())
+            .unwrap_or_else(|| format!("{}/{}", DEFAULT_HOST, self.id));
+        let host = host.trim_end_matches('/');
+        let base = match host.strip_prefix("//") {
+            Some(rest) => format!("https://{}", rest),
+            None => host.to_string(),
+        };
+        Emote {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            url: format!("{}/2x.webp", base),
+            source: EmoteSource::Stv,
+            large_url: Some(format!("{}/4x.webp", base)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+    #[serde(default)]
+    emote_set: Option<EmoteSet>,
+}
+
+/// Fetches 7TV emotes. Like BetterTTV's, results are kept for the rest of the session.
+pub struct SevenTvClient {
+    client: reqwest::Client,
+    global_emotes: Mutex<Option<Vec<Emote>>>,
+    /// Keyed by Twitch broadcaster id.
+    channel_emotes: Mutex<HashMap<String, Vec<Emote>>>,
+}
+
+impl Default for SevenTvClient {
+    fn default() -> Self {
+        Self {
+            client: proxy::client_builder()
+                .build()
+                .expect("Failed to build reqwest client"),
+            global_emotes: Mutex::new(None),
+            channel_emotes: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SevenTvClient {
+    pub async fn get_global_emotes(&self) -> Result<Vec<Emote>, Report> {
+        if let Some(emotes) = self.global_emotes.lock().as_ref() {
+            return Ok(emotes.clone());
+        }
+        let emotes = self
+            .client
+            .get(format!("{}/emote-sets/global", API_BASE))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<EmoteSet>()
+            .await?
+            .to_emotes();
+        *self.global_emotes.lock() = Some(emotes.clone());
+        Ok(emotes)
+    }
+
+    /// The channel's active emote set. Channels without a 7TV account have none.
+    pub async fn get_channel_emotes(&self, broadcaster_id: &str) -> Result<Vec<Emote>, Report> {
+        if let Some(emotes) = self.channel_emotes.lock().get(broadcaster_id) {
+            return Ok(emotes.clone());
+        }
+        let response = self
+            .client
+            .get(format!("{}/users/twitch/{}", API_BASE, broadcaster_id))
+            .send()
+            .await?;
+        let emotes = if response.status() == StatusCode::NOT_FOUND {
+            Vec::new()
+        } else {
+            response
+                .error_for_status()?
+                .json::<UserResponse>()
+                .await?
+                .emote_set
+                .map(|set| set.to_emotes())
+                .unwrap_or_default()
+        };
+        self.channel_emotes
+            .lock()
+            .insert(broadcaster_id.to_string(), emotes.clone());
+        Ok(emotes)
+    }
+}
@@ -1,5 +1,12 @@
+use crate::{
+    core::proxy,
+    models::emote::{Emote, EmoteSource},
+};
+use async_trait::async_trait;
+use eyre::Report;
 use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use twitch_oauth2::UserToken;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,12 +26,120 @@ pub struct EmoteImages {
     pub url_4x: String,
 }
 
+impl TwitchEmote {
+    /// The 1x image matching the UI theme. Helix's `images` URLs are always the
+    /// light variant, so the URL is built from the emote id instead.
+    pub fn url(&self, dark_mode: bool) -> String {
+        emote_url(&self.id, dark_mode, "1.0")
+    }
+
+    pub fn to_emote(&self) -> Emote {
+        Emote {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            url: self.url(true),
+            source: EmoteSource::Twitch,
+            large_url: None,
+        }
+    }
+}
+
+/// Builds a Twitch CDN emote URL. Twitch serves separate artwork for dark and light
+/// backgrounds, and `default` picks the animated version when one exists.
+pub fn emote_url(id: &str, dark_mode: bool, scale: &str) -> String {
+    cdn_url(id, "default", dark_mode, scale)
+}
+
+/// Like [`emote_url`], but animated emotes come as their first frame.
+pub fn static_emote_url(id: &str, dark_mode: bool, scale: &str) -> String {
+    cdn_url(id, "static", dark_mode, scale)
+}
+
+fn cdn_url(id: &str, format: &str, dark_mode: bool, scale: &str) -> String {
+    let theme = if dark_mode { "dark" } else { "light" };
+    format!(
+        "https://static-cdn.jtvnw.net/emoticons/v2/{}/{}/{}/{}",
+        id, format, theme, scale
+    )
+}
+
 #[derive(Debug, Serialize, Deserialize)]
-struct GlobalEmotesResponse {
+struct EmotesResponse {
     data: Vec<TwitchEmote>,
     template: String,
 }
 
+/// A cheer prefix such as `Cheer` or a channel's own, with an image per bits tier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cheermote {
+    pub prefix: String,
+    pub tiers: Vec<CheermoteTier>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheermoteTier {
+    /// The smallest cheer drawn with this tier's image.
+    pub min_bits: i64,
+    pub id: String,
+    pub images: CheermoteImages,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheermoteImages {
+    pub dark: CheermoteImageSet,
+    pub light: CheermoteImageSet,
+}
+
+/// Image URLs keyed by scale: `1`, `1.5`, `2`, `3` and `4`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheermoteImageSet {
+    pub animated: HashMap<String, String>,
+    #[serde(rename = "static")]
+    pub still: HashMap<String, String>,
+}
+
+impl Cheermote {
+    /// The image of the highest tier a cheer of `bits` reaches, as an emote named
+    /// after the prefix.
+    pub fn emote_for(&self, bits: i64) -> Option<Emote> {
+        let tier = self
+            .tiers
+            .iter()
+            .filter(|tier| tier.min_bits <= bits)
+            .max_by_key(|tier| tier.min_bits)?;
+        Some(Emote {
+            id: tier.id.clone(),
+            name: self.prefix.clone(),
+            url: tier.images.dark.animated.get("2")?.clone(),
+            source: EmoteSource::Cheer,
+            large_url: tier.images.dark.animated.get("4").cloned(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CheermotesResponse {
+    data: Vec<Cheermote>,
+}
+
+/// Emote lookups against Helix, abstracted so they can be stubbed out in tests.
+#[async_trait]
+pub trait EmoteApi: Send + Sync {
+    async fn get_global_emotes(&self, token: &UserToken) -> Result<Vec<TwitchEmote>, Report>;
+    /// The channel's subscriber, follower and bits emotes.
+    async fn get_channel_emotes(
+        &self,
+        broadcaster_id: &str,
+        token: &UserToken,
+    ) -> Result<Vec<TwitchEmote>, Report>;
+    /// The global cheer prefixes and the channel's own.
+    async fn get_cheermotes(
+        &self,
+        broadcaster_id: &str,
+        token: &UserToken,
+    ) -> Result<Vec<Cheermote>, Report>;
+}
+
 #[derive(Clone)]
 pub struct TwitchApiClient {
     client: reqwest::Client,
@@ -34,18 +149,65 @@ pub struct TwitchApiClient {
 impl TwitchApiClient {
     pub fn new(client_id: String) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: proxy::client_builder()
+                .build()
+                .expect("Failed to build reqwest client"),
             client_id,
         }
     }
+}
 
-    pub async fn get_global_emotes(
+#[async_trait]
+impl EmoteApi for TwitchApiClient {
+    async fn get_global_emotes(&self, token: &UserToken) -> Result<Vec<TwitchEmote>, Report> {
+        let response = self
+            .client
+            .get("https://api.twitch.tv/helix/chat/emotes/global")
+            .header(
+                AUTHORIZATION,
+                format!("Bearer {}", token.access_token.as_str()),
+            )
+            .header("Client-Id", &self.client_id)
+            .header(CONTENT_TYPE, "application/json")
+            .send()
+            .await?
+            .json::<EmotesResponse>()
+            .await?;
+
+        Ok(response.data)
+    }
+    async fn get_channel_emotes(
         &self,
+        broadcaster_id: &str,
         token: &UserToken,
-    ) -> Result<Vec<TwitchEmote>, reqwest::Error> {
+    ) -> Result<Vec<TwitchEmote>, Report> {
         let response = self
             .client
-            .get("https://api.twitch.tv/helix/chat/emotes/global")
+            .get("https://api.twitch.tv/helix/chat/emotes")
+            .query(&[("broadcaster_id", broadcaster_id)])
+            .header(
+                AUTHORIZATION,
+                format!("Bearer {}", token.access_token.as_str()),
+            )
+            .header("Client-Id", &self.client_id)
+            .header(CONTENT_TYPE, "application/json")
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<EmotesResponse>()
+            .await?;
+
+        Ok(response.data)
+    }
+    async fn get_cheermotes(
+        &self,
+        broadcaster_id: &str,
+        token: &UserToken,
+    ) -> Result<Vec<Cheermote>, Report> {
+        let response = self
+            .client
+            .get("https://api.twitch.tv/helix/bits/cheermotes")
+            .query(&[("broadcaster_id", broadcaster_id)])
             .header(
                 AUTHORIZATION,
                 format!("Bearer {}", token.access_token.as_str()),
@@ -54,7 +216,8 @@ impl TwitchApiClient {
             .header(CONTENT_TYPE, "application/json")
             .send()
             .await?
-            .json::<GlobalEmotesResponse>()
+            .error_for_status()?
+            .json::<CheermotesResponse>()
             .await?;
 
         Ok(response.data)
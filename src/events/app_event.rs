@@ -1,25 +1,132 @@
 use crate::{
-    app::config::Config, core::auth::AuthMessage, emotes::twitch_api::TwitchEmote,
-    models::message::ChatMessage,
+    app::config::Config,
+    core::{auth::AuthMessage, chat::is_unauthorized},
+    emotes::twitch_api::{Cheermote, TwitchEmote},
+    features::{automod::HeldMessage, channel_points::Redemption},
+    models::{emote::Emote, message::ChatMessage},
 };
+use std::{path::PathBuf, time::Duration};
 use twitch_oauth2::UserToken;
 
 #[derive(Debug)]
 pub enum AppEvent {
-    ConfigLoaded(Result<Config, eyre::Report>),
+    ConfigLoaded(Result<Box<Config>, eyre::Report>),
     SilentLoginComplete(Result<UserToken, eyre::Report>),
     ProfileSwitchSilentLoginComplete(Result<UserToken, eyre::Report>, String),
+    /// A profile's saved token was loaded to send as, alongside the primary login.
+    IdentityLoaded(Result<UserToken, eyre::Report>, String),
     Auth(AuthMessage),
+    /// The logged-in token was refreshed before it expired.
+    TokenRefreshed(UserToken),
     AuthCancel,
     AuthFlowStartFailed(String),
     Chat(ChatEvent),
+    /// A chat event from the connection of the tab showing `broadcaster_id`'s channel.
+    ChannelChat {
+        broadcaster_id: String,
+        event: ChatEvent,
+    },
     GlobalEmotesLoaded(Result<Vec<TwitchEmote>, String>),
+    ChannelEmotesLoaded {
+        channel: String,
+        result: Result<Vec<TwitchEmote>, String>,
+    },
+    /// The channel's emotes from the enabled third-party providers. Providers that
+    /// failed are already left out.
+    ThirdPartyEmotesLoaded {
+        channel: String,
+        emotes: Vec<Emote>,
+    },
+    CheermotesLoaded {
+        channel: String,
+        result: Result<Vec<Cheermote>, String>,
+    },
+    StatusLineUpdated(Option<String>),
+    /// A channel's chat log was exported; carries where to, or why it failed.
+    ChatLogSaved(Result<PathBuf, String>),
 }
 
 #[derive(Debug)]
 pub enum ChatEvent {
     NewChatMessage(ChatMessage),
+    MessageDeleted(String),
+    /// A moderator removed all of a user's messages, e.g. by banning them; carries
+    /// the user's id.
+    UserMessagesCleared(String),
+    /// A moderator cleared the whole chat.
+    ChatCleared,
     MessageSent,
     MessageSendError(String),
+    /// Twitch refused the message being sent for the rate limit; it's sent again
+    /// after this long.
+    MessageQueued(Duration),
+    RaidStarted(String),
+    RaidCancelled,
+    RaidError(String),
+    /// Cancelling the pending raid failed, so it's still going ahead.
+    RaidCancelError(String),
+    RedemptionAdded(Redemption),
+    /// A redemption left the unfulfilled state, either here or from another client.
+    RedemptionResolved(String),
+    RedemptionError(String),
     EventSubError(String),
+    /// The chat connection is being opened.
+    Connecting,
+    /// The chat connection is open and subscribed to the channel's chat.
+    Connected,
+    /// The chat connection was lost.
+    Disconnected,
+    /// The chat connection dropped and is being re-established.
+    Reconnecting {
+        attempt: u32,
+        max_attempts: u32,
+    },
+    Reconnected,
+    /// The JSON Twitch sent for a message, forwarded when developer tools are on.
+    RawEvent {
+        message_id: String,
+        json: String,
+    },
+    /// A channel being joined doesn't exist (anymore); carries the channel login.
+    ChannelNotFound(String),
+    /// Looking up a channel being joined failed for a reason other than it not
+    /// existing, so its chat isn't connected.
+    ChannelLookupFailed {
+        channel: String,
+        error: String,
+    },
+    /// A channel's broadcaster id was looked up and chat is being connected.
+    ChannelJoined {
+        channel: String,
+        broadcaster_id: String,
+    },
+    /// Twitch revoked an EventSub subscription; carries the revocation status.
+    SubscriptionRevoked(String),
+    MessageHeld(HeldMessage),
+    /// A held message was allowed, denied or expired, here or from another client.
+    HeldMessageResolved(String),
+    HeldMessageError(String),
+    /// A Helix call was rejected because the token expired or was revoked.
+    SessionExpired,
+    /// The logged-in user moderates the joined channel.
+    ModeratorConfirmed,
+    UserBanned(String),
+    ModerationError(String),
+    /// Display names of everyone currently in the channel's chat.
+    ChattersLoaded(Vec<String>),
+}
+
+impl ChatEvent {
+    /// The event to report for a failed Helix call: [`ChatEvent::SessionExpired`] if
+    /// the token is no longer valid, whatever the call was, otherwise `otherwise`'s.
+    pub fn from_api_error(
+        error: &eyre::Report,
+        otherwise: impl FnOnce(&eyre::Report) -> ChatEvent,
+    ) -> ChatEvent {
+        if is_unauthorized(error) {
+            ChatEvent::SessionExpired
+        } else {
+            otherwise(error)
+        }
+    }
 }
@@ -0,0 +1,58 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How far back messages are counted when measuring the chat rate.
+const WINDOW: Duration = Duration::from_secs(5);
+/// Paused animations resume once the rate drops below this share of the threshold,
+/// so they don't flicker on and off while chat hovers around it.
+const RESUME_FRACTION: f32 = 0.75;
+
+/// Measures how fast chat is moving from the arrival times of recent messages.
+#[derive(Debug, Default)]
+pub struct ActivityMeter {
+    arrivals: VecDeque<Instant>,
+    /// Whether animated emotes are currently drawn as still images.
+    animations_paused: bool,
+}
+
+impl ActivityMeter {
+    pub fn record(&mut self, at: Instant) {
+        self.arrivals.push_back(at);
+        self.prune(at);
+    }
+
+    /// Messages per second over the last few seconds.
+    pub fn messages_per_second(&mut self, now: Instant) -> f32 {
+        self.prune(now);
+        self.arrivals.len() as f32 / WINDOW.as_secs_f32()
+    }
+
+    /// Re-evaluates whether animations should be paused at `threshold` messages per
+    /// second, and returns the result.
+    pub fn update_animations_paused(&mut self, now: Instant, threshold: f32) -> bool {
+        let rate = self.messages_per_second(now);
+        self.animations_paused = if self.animations_paused {
+            rate > threshold * RESUME_FRACTION
+        } else {
+            rate > threshold
+        };
+        self.animations_paused
+    }
+
+    pub fn clear(&mut self) {
+        self.arrivals.clear();
+        self.animations_paused = false;
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while self
+            .arrivals
+            .front()
+            .is_some_and(|&at| now.duration_since(at) > WINDOW)
+        {
+            self.arrivals.pop_front();
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use chrono::{DateTime, Local};
+
+/// A chat message AutoMod is holding until a moderator allows or denies it.
+#[derive(Clone, Debug)]
+pub struct HeldMessage {
+    pub id: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub text: String,
+    pub held_at: DateTime<Local>,
+}
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Local};
+
+/// A channel points redemption waiting for the broadcaster to fulfill or reject it.
+#[derive(Clone, Debug)]
+pub struct Redemption {
+    pub id: String,
+    pub reward_id: String,
+    pub reward_title: String,
+    pub reward_cost: i64,
+    pub user_name: String,
+    pub user_input: String,
+    pub redeemed_at: DateTime<Local>,
+}
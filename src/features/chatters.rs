@@ -0,0 +1,54 @@
+use crate::{
+    core::chat::{ChatApi, has_status},
+    events::app_event::{AppEvent, ChatEvent},
+};
+use reqwest::StatusCode;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+use twitch_oauth2::UserToken;
+use twitch_types::UserId;
+
+/// How often the channel's chatter list is fetched again. Twitch itself only
+/// updates it every few minutes.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Fetches everyone in the channel's chat every [`REFRESH_INTERVAL`] and forwards
+/// the list to the UI until the receiving end goes away or the task is aborted.
+/// Only the broadcaster and moderators may see the list, so for anyone else this
+/// stops after the first attempt.
+pub async fn run(
+    chat_client: Arc<dyn ChatApi>,
+    broadcaster_id: UserId,
+    moderator_id: UserId,
+    token: Arc<UserToken>,
+    tx: mpsc::Sender<AppEvent>,
+) {
+    let mut ticker = tokio::time::interval(REFRESH_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+
+        match chat_client
+            .get_chatters(&broadcaster_id, &moderator_id, &token)
+            .await
+        {
+            Ok(chatters) => {
+                let event = AppEvent::ChannelChat {
+                    broadcaster_id: broadcaster_id.to_string(),
+                    event: ChatEvent::ChattersLoaded(chatters),
+                };
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) if has_status(&e, StatusCode::FORBIDDEN) => {
+                tracing::info!(
+                    "Not showing everyone in {}'s chat: only moderators can list chatters",
+                    broadcaster_id
+                );
+                return;
+            }
+            Err(e) => tracing::warn!("Failed to fetch chatters for {}: {}", broadcaster_id, e),
+        }
+    }
+}
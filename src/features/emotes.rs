@@ -1,3 +0,0 @@
-// This file is reserved for future implementation of emote-related features,
-// such as fetching third-party emotes from services like BTTV, FFZ, or 7TV.
-// The basic Twitch emote parsing is now handled directly in the eventsub module.
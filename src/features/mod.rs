@@ -1,3 +1,7 @@
+pub mod activity;
+pub mod automod;
 pub mod channel_points;
-pub mod emotes;
+pub mod chatters;
 pub mod notifications;
+pub mod status_line;
+pub mod webhook;
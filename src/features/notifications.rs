@@ -0,0 +1,41 @@
+use rodio::{OutputStream, Sink, Source, source::SineWave};
+use std::time::Duration;
+
+const MESSAGE_TONE_HZ: f32 = 880.0;
+const MESSAGE_TONE_LENGTH: Duration = Duration::from_millis(120);
+
+/// Plays a short notification tone for an incoming chat message.
+///
+/// Audio output streams aren't `Send`, so playback runs on its own short-lived
+/// thread instead of blocking the UI or a tokio worker.
+pub fn play_message_sound(volume: f32) {
+    let spawn_result = std::thread::Builder::new()
+        .name("message-sound".to_string())
+        .spawn(move || {
+            let (_stream, handle) = match OutputStream::try_default() {
+                Ok(output) => output,
+                Err(e) => {
+                    tracing::warn!("No audio output available for message sound: {}", e);
+                    return;
+                }
+            };
+            let sink = match Sink::try_new(&handle) {
+                Ok(sink) => sink,
+                Err(e) => {
+                    tracing::warn!("Failed to create audio sink for message sound: {}", e);
+                    return;
+                }
+            };
+            sink.set_volume(volume.clamp(0.0, 1.0));
+            sink.append(
+                SineWave::new(MESSAGE_TONE_HZ)
+                    .take_duration(MESSAGE_TONE_LENGTH)
+                    .fade_in(Duration::from_millis(10)),
+            );
+            sink.sleep_until_end();
+        });
+
+    if let Err(e) = spawn_result {
+        tracing::error!("Failed to spawn message sound thread: {}", e);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::events::app_event::AppEvent;
+use std::{path::PathBuf, time::Duration};
+use tokio::sync::mpsc;
+
+/// Longest status line shown in the top panel; anything beyond is cut off.
+const MAX_STATUS_LINE_CHARS: usize = 200;
+
+/// Where the optional top-panel status line (e.g. a "now playing" song) is read from.
+#[derive(Debug, Clone)]
+pub enum StatusLineSource {
+    File(PathBuf),
+    Command(String),
+}
+
+/// Polls the source every `interval` and forwards its first line to the UI until the
+/// receiving end goes away or the task is aborted.
+pub async fn run(source: StatusLineSource, interval: Duration, tx: mpsc::Sender<AppEvent>) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut last_line = None;
+
+    loop {
+        ticker.tick().await;
+
+        let line = match read_source(&source).await {
+            Ok(output) => first_line(&output),
+            Err(e) => {
+                tracing::warn!("Failed to read status line from {:?}: {}", source, e);
+                None
+            }
+        };
+
+        if line != last_line {
+            last_line = line.clone();
+            if tx.send(AppEvent::StatusLineUpdated(line)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn read_source(source: &StatusLineSource) -> Result<String, eyre::Report> {
+    match source {
+        StatusLineSource::File(path) => Ok(tokio::fs::read_to_string(path).await?),
+        StatusLineSource::Command(command) => {
+            let output = if cfg!(target_os = "windows") {
+                tokio::process::Command::new("cmd")
+                    .args(["/C", command])
+                    .output()
+                    .await?
+            } else {
+                tokio::process::Command::new("sh")
+                    .args(["-c", command])
+                    .output()
+                    .await?
+            };
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+    }
+}
+
+fn first_line(output: &str) -> Option<String> {
+    let line = output.lines().map(str::trim).find(|l| !l.is_empty())?;
+    Some(line.chars().take(MAX_STATUS_LINE_CHARS).collect())
+}
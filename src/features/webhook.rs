@@ -0,0 +1,145 @@
+use crate::{
+    core::proxy,
+    models::message::{ChatMessage, MessageKind},
+};
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An optional endpoint that chat is POSTed to as JSON, for overlays and bots.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WebhookSettings {
+    /// Where to send events; nothing is sent while this is unset.
+    pub url: Option<String>,
+    pub forward_chat: bool,
+    /// Subs, raids, announcements and other notices from Twitch.
+    pub forward_notices: bool,
+    pub forward_deletions: bool,
+    /// Events beyond this many per second are dropped rather than queued.
+    pub max_per_second: u32,
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            forward_chat: true,
+            forward_notices: false,
+            forward_deletions: false,
+            max_per_second: 20,
+        }
+    }
+}
+
+impl WebhookSettings {
+    /// Builds the payload for a new message, if its kind is being forwarded.
+    pub fn payload_for_message(
+        &self,
+        channel: Option<&str>,
+        message: &ChatMessage,
+    ) -> Option<WebhookPayload> {
+        self.url.as_ref()?;
+        let channel = channel.unwrap_or_default().to_string();
+        match &message.kind {
+            MessageKind::Chat if self.forward_chat => Some(WebhookPayload::ChatMessage {
+                channel,
+                id: message.id.clone(),
+                user_id: message.sender_id.clone(),
+                user_name: message.sender_name.clone(),
+                text: message.to_plain_text(),
+                timestamp: message.timestamp,
+            }),
+            MessageKind::System { category, text } if self.forward_notices => {
+                Some(WebhookPayload::Notice {
+                    channel,
+                    category: category.label().to_string(),
+                    text: text.clone(),
+                    message: message.to_plain_text(),
+                    timestamp: message.timestamp,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn payload_for_deletion(
+        &self,
+        channel: Option<&str>,
+        message_id: &str,
+    ) -> Option<WebhookPayload> {
+        self.url.as_ref()?;
+        self.forward_deletions
+            .then(|| WebhookPayload::MessageDeleted {
+                channel: channel.unwrap_or_default().to_string(),
+                id: message_id.to_string(),
+            })
+    }
+}
+
+/// The JSON body of a webhook request, tagged with a `type` field.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebhookPayload {
+    ChatMessage {
+        channel: String,
+        id: String,
+        user_id: String,
+        user_name: String,
+        text: String,
+        timestamp: DateTime<Local>,
+    },
+    Notice {
+        channel: String,
+        category: String,
+        text: String,
+        message: String,
+        timestamp: DateTime<Local>,
+    },
+    MessageDeleted {
+        channel: String,
+        id: String,
+    },
+}
+
+/// Start of the current one-second window and how many events were sent in it.
+static RATE_WINDOW: Lazy<Mutex<(Instant, u32)>> = Lazy::new(|| Mutex::new((Instant::now(), 0)));
+
+static CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    proxy::client_builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("Failed to build reqwest client")
+});
+
+/// POSTs `payload` to `url` on a background task. Delivery is best effort: failures
+/// are only logged, and events over `max_per_second` are dropped.
+pub fn send(url: String, payload: WebhookPayload, max_per_second: u32) {
+    {
+        let mut window = RATE_WINDOW.lock();
+        if window.0.elapsed() >= Duration::from_secs(1) {
+            *window = (Instant::now(), 0);
+        }
+        if window.1 >= max_per_second {
+            tracing::debug!("Webhook rate limit reached, dropping {:?}", payload);
+            return;
+        }
+        window.1 += 1;
+    }
+
+    tokio::spawn(async move {
+        let result = CLIENT
+            .post(&url)
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            tracing::warn!("Failed to deliver webhook to {}: {}", url, e);
+        }
+    });
+}
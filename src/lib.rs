@@ -1,7 +1,6 @@
 pub mod app;
 pub mod core;
 pub mod emotes;
-pub mod error;
 pub mod events;
 pub mod features;
 pub mod models;
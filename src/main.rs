@@ -1,4 +1,4 @@
-use eframe::NativeOptions;
+use eframe::{NativeOptions, egui::ViewportBuilder};
 use livenac::ui::app_layout::App;
 use tracing_subscriber::EnvFilter;
 
@@ -12,13 +12,35 @@ async fn main() -> eframe::Result<()> {
         .with_ansi(false)
         .with_timer(tracing_subscriber::fmt::time::LocalTime::rfc_3339())
         .init();
+    livenac::utils::crash::install_panic_hook();
 
-    let native_options = NativeOptions::default();
+    // `--mock` skips login and feeds synthetic chat, for UI work without Twitch
+    // credentials. `--mock-rate=N` sets how many messages arrive per second.
+    // `--config-dir=PATH` stores settings and logins somewhere other than the
+    // platform config directory.
+    let mut mock_rate = None;
+    for arg in std::env::args().skip(1) {
+        if arg == "--mock" {
+            mock_rate.get_or_insert(livenac::core::mock::DEFAULT_RATE);
+        } else if let Some(rate) = arg.strip_prefix("--mock-rate=") {
+            match rate.parse::<f32>() {
+                Ok(rate) if rate > 0.0 => mock_rate = Some(rate),
+                _ => tracing::warn!("Ignoring invalid --mock-rate value: {}", rate),
+            }
+        } else if let Some(dir) = arg.strip_prefix("--config-dir=") {
+            livenac::core::storage::set_override(dir.into());
+        }
+    }
+
+    let native_options = NativeOptions {
+        viewport: ViewportBuilder::default().with_title("LiveNAC"),
+        ..Default::default()
+    };
     eframe::run_native(
         "livenac",
         native_options,
         Box::new(|cc| {
-            let app = App::new(cc);
+            let app = App::new(cc, mock_rate);
             Ok(Box::new(app))
         }),
     )
@@ -1,14 +1,100 @@
-#[derive(Clone, Debug, PartialEq)]
+use crate::emotes::twitch_api::{emote_url, static_emote_url};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Emote {
+    #[serde(default)]
+    pub id: String,
     pub name: String,
     pub url: String,
     pub source: EmoteSource,
+    /// The source's largest rendition, where it has one beyond `url`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub large_url: Option<String>,
+}
+
+impl Emote {
+    /// The image URL to render against the current theme at `scale`. Only Twitch has
+    /// theme-specific artwork and multiple sizes; other sources always use `url`.
+    pub fn image_url(&self, dark_mode: bool, scale: EmoteScale) -> String {
+        match self.source {
+            EmoteSource::Twitch if !self.id.is_empty() => {
+                emote_url(&self.id, dark_mode, scale.cdn_scale())
+            }
+            _ => self.url.clone(),
+        }
+    }
+
+    /// Like [`Emote::image_url`], but the first frame of animated Twitch emotes.
+    pub fn still_image_url(&self, dark_mode: bool, scale: EmoteScale) -> String {
+        match self.source {
+            EmoteSource::Twitch if !self.id.is_empty() => {
+                static_emote_url(&self.id, dark_mode, scale.cdn_scale())
+            }
+            _ => self.url.clone(),
+        }
+    }
+
+    /// The largest image there is of the emote, for a close-up preview.
+    pub fn preview_url(&self, dark_mode: bool) -> String {
+        match &self.large_url {
+            Some(url) => url.clone(),
+            None => self.image_url(dark_mode, EmoteScale::X4),
+        }
+    }
+
+    /// Whether the emote can't be drawn still: third-party GIF and WebP images, and
+    /// FrankerFaceZ's animated ones, have no static version.
+    pub fn lacks_still_image(&self) -> bool {
+        if self.source == EmoteSource::Twitch && !self.id.is_empty() {
+            return false;
+        }
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        path.ends_with(".gif") || path.ends_with(".webp") || path.contains("/animated/")
+    }
+}
+
+/// Which rendition of an emote to load. Twitch serves each emote at 28, 56 and 112
+/// pixels, the `url_1x`/`url_2x`/`url_4x` of Helix's `images`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmoteScale {
+    X1,
+    X2,
+    X4,
+}
+
+impl EmoteScale {
+    const BASE_PIXELS: f32 = 28.0;
+
+    /// The smallest rendition that stays crisp when drawn `size` points wide on a
+    /// display with `pixels_per_point` physical pixels per point.
+    pub fn for_size(size: f32, pixels_per_point: f32) -> Self {
+        let pixels = size * pixels_per_point;
+        if pixels <= Self::BASE_PIXELS {
+            EmoteScale::X1
+        } else if pixels <= Self::BASE_PIXELS * 2.0 {
+            EmoteScale::X2
+        } else {
+            EmoteScale::X4
+        }
+    }
+
+    /// The scale segment of a Twitch CDN URL; the largest size is called `3.0` there.
+    pub fn cdn_scale(self) -> &'static str {
+        match self {
+            EmoteScale::X1 => "1.0",
+            EmoteScale::X2 => "2.0",
+            EmoteScale::X4 => "3.0",
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum EmoteSource {
     Twitch,
     Bttv,
     Ffz,
     Stv,
+    /// A bits cheer tier image.
+    Cheer,
 }
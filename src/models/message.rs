@@ -1,16 +1,195 @@
 use super::emote::Emote;
+use crate::utils::text_processing::truncate_chars;
 use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MessageFragment {
     Text(String),
     Emote(Emote),
+    /// An `@mention` of another chatter, holding their display name without the `@`.
+    Mention(String),
+    /// A bits cheer such as `Cheer100`.
+    Cheer {
+        prefix: String,
+        bits: i64,
+    },
+    /// A cheer whose tier image is known.
+    Cheermote {
+        prefix: String,
+        bits: i64,
+        emote: Emote,
+    },
 }
 
-#[derive(Clone, Debug)]
+/// What a message represents. System messages are notices from Twitch (subs, raids,
+/// announcements, ...) whose `text` is shown in place of the sender, followed by
+/// any message the user attached.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum MessageKind {
+    #[default]
+    Chat,
+    System {
+        category: SystemCategory,
+        text: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SystemCategory {
+    /// Subs, resubs, gifted subs and sub upgrades.
+    Subscription,
+    Raid,
+    Announcement,
+    BitsBadge,
+    Other,
+}
+
+impl SystemCategory {
+    pub const ALL: [SystemCategory; 5] = [
+        SystemCategory::Subscription,
+        SystemCategory::Raid,
+        SystemCategory::Announcement,
+        SystemCategory::BitsBadge,
+        SystemCategory::Other,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SystemCategory::Subscription => "Subscriptions",
+            SystemCategory::Raid => "Raids",
+            SystemCategory::Announcement => "Announcements",
+            SystemCategory::BitsBadge => "Bits badges",
+            SystemCategory::Other => "Other notices",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            SystemCategory::Subscription => "⭐",
+            SystemCategory::Raid => "🚀",
+            SystemCategory::Announcement => "📣",
+            SystemCategory::BitsBadge => "💎",
+            SystemCategory::Other => "ℹ",
+        }
+    }
+}
+
+/// Twitch's flag on messages from users a moderator marked as suspicious.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LowTrustStatus {
+    /// The user can chat, but moderators see their messages flagged.
+    ActiveMonitoring,
+    /// Only moderators see the user's messages.
+    Restricted,
+}
+
+/// The message a reply answers, as Twitch sends it along with the reply.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplyInfo {
+    pub parent_id: String,
+    pub parent_user_name: String,
+    pub parent_text: String,
+}
+
+/// How much of the parent message is shown above a reply.
+const REPLY_PREVIEW_CHARS: usize = 60;
+
+impl ReplyInfo {
+    /// `@user: text`, with long parent messages cut short.
+    pub fn summary(&self) -> String {
+        let text = truncate_chars(&self.parent_text, REPLY_PREVIEW_CHARS);
+        let ellipsis = if text.len() < self.parent_text.len() {
+            "…"
+        } else {
+            ""
+        };
+        format!("@{}: {}{}", self.parent_user_name, text, ellipsis)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChatMessage {
+    pub id: String,
+    #[serde(default)]
+    pub sender_id: String,
     pub sender_name: String,
     pub sender_color: Option<(u8, u8, u8)>,
     pub fragments: Vec<MessageFragment>,
     pub timestamp: DateTime<Local>,
+    /// Set when a moderator removed the message and the config keeps deleted messages visible.
+    #[serde(default)]
+    pub deleted: bool,
+    /// Sent with `/me`.
+    #[serde(default)]
+    pub is_action: bool,
+    #[serde(default)]
+    pub kind: MessageKind,
+    /// Only ever set for moderators, who are the only ones Twitch tells.
+    #[serde(default)]
+    pub low_trust: Option<LowTrustStatus>,
+    /// Set when the message is a reply to another one.
+    #[serde(default)]
+    pub reply_to: Option<ReplyInfo>,
+}
+
+impl ChatMessage {
+    /// What a reply to this message refers to.
+    pub fn reply_info(&self) -> ReplyInfo {
+        ReplyInfo {
+            parent_id: self.id.clone(),
+            parent_user_name: self.sender_name.clone(),
+            parent_text: self.to_plain_text(),
+        }
+    }
+
+    /// Whether the sender or the text contains `query`, ignoring case.
+    pub fn matches_search(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.sender_name.to_lowercase().contains(&query)
+            || self.to_plain_text().to_lowercase().contains(&query)
+    }
+
+    /// Whether `user_name` sent the message or is mentioned in it, ignoring case.
+    pub fn involves(&self, user_name: &str) -> bool {
+        self.sender_name.eq_ignore_ascii_case(user_name)
+            || self.fragments.iter().any(|fragment| {
+                matches!(fragment, MessageFragment::Mention(name) if name.eq_ignore_ascii_case(user_name))
+            })
+    }
+
+    /// The canonical text form of the message body, used anywhere a message leaves
+    /// the UI as text (copying, exports, logs, duplicate detection) so they all agree.
+    ///
+    /// Emotes become their codes, cheers `cheerN`, mentions `@name`, and `/me`
+    /// actions are prefixed with `* `. System messages start with their notice text.
+    /// The sender and timestamp are not included.
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::new();
+        if let MessageKind::System { text: notice, .. } = &self.kind {
+            text.push_str(notice);
+            if !self.fragments.is_empty() {
+                text.push(' ');
+            }
+        }
+        if self.is_action {
+            text.push_str("* ");
+        }
+        for fragment in &self.fragments {
+            match fragment {
+                MessageFragment::Text(t) => text.push_str(t),
+                MessageFragment::Emote(emote) => text.push_str(&emote.name),
+                MessageFragment::Mention(name) => {
+                    text.push('@');
+                    text.push_str(name);
+                }
+                MessageFragment::Cheer { prefix, bits }
+                | MessageFragment::Cheermote { prefix, bits, .. } => {
+                    text.push_str(&prefix.to_lowercase());
+                    text.push_str(&bits.to_string());
+                }
+            }
+        }
+        text
+    }
 }
@@ -2,4 +2,7 @@
 pub struct User {
     pub name: String,
     pub color: Option<(u8, u8, u8)>,
+    /// Set once they've sent a message; otherwise they're only known from the
+    /// channel's chatter list.
+    pub has_spoken: bool,
 }
@@ -1,47 +1,162 @@
 use crate::{
     app::{
-        config::{self, Config, Profile},
+        actions::{self, Action},
+        config::{
+            self, AppearanceOverrides, Config, InlineAlignment, OwnMessageStyle, Profile, Theme,
+        },
+        config_saver::ConfigSaver,
         reducer,
-        state::AppState,
+        state::{AppState, ChannelTab, ConnectionStatus, RAID_COUNTDOWN_SECS},
     },
     core::{
-        auth::{AuthClient, AuthMessage},
-        chat::{AnnouncementColor, ChatClient},
+        auth::{self, AuthClient, AuthMessage},
+        chat::{
+            MODERATOR_MESSAGES_PER_WINDOW, USER_MESSAGES_PER_WINDOW, check_whisper_length,
+            is_unauthorized,
+        },
+        commands::{ChatCommand, me_message, parse_command},
         eventsub::EventSubClient,
+        export,
+        history::ChatHistory,
+        mock, proxy,
+        send::{self, ChatSend},
+        storage,
+        tokenize::{EmotePrecedence, EmoteRegistry},
+    },
+    emotes::{
+        self,
+        bttv_api::BttvClient,
+        ffz_api::FfzClient,
+        image_cache::{EmoteImageCache, EmoteImageLoader},
+        seventv_api::SevenTvClient,
     },
     events::app_event::{AppEvent, ChatEvent},
+    features::{chatters, status_line},
+    models::message::{ChatMessage, SystemCategory},
     ui::{
-        chat::{chat_bar, chat_log, emote_picker, user_list},
+        channel_list::{self, TabAction},
+        channel_switcher::{self, ChannelSwitcherState},
+        chat::{
+            autocomplete::CompletionState,
+            automod_queue::{self, HeldMessageAction},
+            chat_bar::{self, PasteState},
+            chat_log::{self, MessageAction},
+            emote_picker::{self, EmotePickerState},
+            message_style,
+            redemption_queue::{self, RedemptionAction},
+            user_list,
+        },
         profiles,
+        replay::{self, ReplayState},
+    },
+    utils::{
+        text_processing::{AutolinkMode, contains_url, sanitize_input, split_message_lines},
+        time::{DisplayTimezone, TIMESTAMP_PRESETS, is_valid_format},
     },
 };
 use eframe::egui::{
-    self, Align, FontDefinitions, Key, Layout, RichText, SidePanel, TopBottomPanel,
+    self, Align, FontDefinitions, Key, KeyboardShortcut, Layout, Modifiers, RichText, SidePanel,
+    TopBottomPanel,
 };
 use fontdb;
-use tokio::sync::mpsc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+use twitch_oauth2::UserToken;
+use twitch_types::UserId;
+
+/// Most events applied in one frame. Anything beyond this waits for the next frame,
+/// so a burst of chat can't hold up drawing.
+const MAX_EVENTS_PER_FRAME: usize = 500;
+
+/// `emote_cache_mb` is in megabytes.
+const BYTES_PER_MB: u64 = 1024 * 1024;
 
 pub struct App {
     state: AppState,
     event_rx: mpsc::Receiver<AppEvent>,
     event_tx: mpsc::Sender<AppEvent>,
     config: Config,
+    /// `config` with the active profile's appearance overrides applied, refreshed
+    /// every frame. Drawing reads this; settings edits go to `config`.
+    effective_config: Config,
     show_settings_window: bool,
+    /// Name typed in the settings to pin a color to.
+    user_color_input: String,
+    /// A timestamp format being typed that isn't valid yet; valid ones go straight
+    /// to the config.
+    timestamp_format_input: Option<String>,
+    /// Whether the appearance settings edit the active profile rather than the
+    /// global defaults.
+    edit_profile_appearance: bool,
     show_profile_manager: bool,
     new_profile_name: String,
+    /// Starting channel entered during first-time setup.
+    default_channel_input: String,
     profile_to_remove_name: String,
     profile_manager_error: Option<String>,
     show_toolbar: bool,
     show_emote_picker: bool,
+    emote_picker: EmotePickerState,
+    paste_state: PasteState,
+    completion_state: CompletionState,
     show_user_list: bool,
+    raid_target: String,
+    show_redemption_queue: bool,
+    show_automod_queue: bool,
+    status_line_task: Option<JoinHandle<()>>,
+    /// Refreshes the logged-in token before it expires; the token it was started
+    /// with tells when it has to be restarted for a new one.
+    token_watch: Option<(Arc<UserToken>, JoinHandle<()>)>,
+    show_replay_window: bool,
+    replay: ReplayState,
+    /// A send waiting on the user to confirm it contains a link; holds the text to
+    /// send and `is_announcement`.
+    pending_link_confirmation: Option<(Vec<String>, bool)>,
+    /// A clicked chat link waiting on the user to confirm it should be opened.
+    pending_link_open: Option<String>,
+    /// Raw event JSON shown in the developer tools window.
+    raw_event_view: Option<String>,
+    /// Why the proxy settings being edited can't be used.
+    proxy_error: Option<String>,
+    /// Channels to go back to once a re-authentication completes.
+    rejoin_after_login: Vec<String>,
+    channel_switcher: ChannelSwitcherState,
+    /// Chat messages that arrived while the window was in the background.
+    unread_count: usize,
+    /// The title last sent to the window, so it's only updated when it changes.
+    window_title: String,
+    /// Messages per second for the synthetic chat feed; `Some` means `--mock` mode.
+    mock_rate: Option<f32>,
+    /// Shared across channel joins so each channel's emotes are only fetched once.
+    bttv: Arc<BttvClient>,
+    ffz: Arc<FfzClient>,
+    seventv: Arc<SevenTvClient>,
+    /// `None` when nothing is persisted.
+    emote_cache: Option<Arc<EmoteImageCache>>,
+    config_saver: ConfigSaver,
+}
+
+enum RaidAction {
+    Start(String),
+    Cancel,
 }
 
 impl App {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, mock_rate: Option<f32>) -> Self {
         egui_extras::install_image_loaders(&cc.egui_ctx);
 
-        let (event_tx, event_rx) = mpsc::channel(100);
+        let (event_tx, event_rx) = Self::spawn_event_forwarder(cc.egui_ctx.clone());
         let default_config = Config::default();
+        let emote_cache =
+            EmoteImageCache::from_data_dir(default_config.emote_cache_mb * BYTES_PER_MB)
+                .map(Arc::new);
+        if let Some(cache) = &emote_cache {
+            cc.egui_ctx
+                .add_bytes_loader(Arc::new(EmoteImageLoader::new(cache.clone())));
+        }
 
         // --- FONT SETUP ---
         let mut fonts = FontDefinitions::default();
@@ -150,14 +265,43 @@ impl App {
             event_rx,
             event_tx,
             config: default_config.clone(),
+            effective_config: default_config.clone(),
             show_settings_window: false,
+            edit_profile_appearance: false,
             show_profile_manager: false,
+            user_color_input: String::new(),
+            timestamp_format_input: None,
             new_profile_name: String::new(),
+            default_channel_input: String::new(),
             profile_to_remove_name: String::new(),
             profile_manager_error: None,
             show_toolbar: false,
             show_emote_picker: false,
+            emote_picker: EmotePickerState::default(),
+            paste_state: PasteState::default(),
+            completion_state: CompletionState::default(),
             show_user_list: false,
+            raid_target: String::new(),
+            show_redemption_queue: false,
+            show_automod_queue: false,
+            status_line_task: None,
+            token_watch: None,
+            show_replay_window: false,
+            replay: ReplayState::default(),
+            pending_link_confirmation: None,
+            pending_link_open: None,
+            raw_event_view: None,
+            proxy_error: None,
+            rejoin_after_login: Vec::new(),
+            channel_switcher: ChannelSwitcherState::default(),
+            unread_count: 0,
+            window_title: String::new(),
+            mock_rate,
+            bttv: Arc::new(BttvClient::default()),
+            ffz: Arc::new(FfzClient::default()),
+            seventv: Arc::new(SevenTvClient::default()),
+            emote_cache,
+            config_saver: ConfigSaver::new(),
         }
     }
 }
@@ -167,15 +311,54 @@ use eyre::eyre;
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.apply_settings(ctx);
+        if storage::is_in_memory() {
+            egui::TopBottomPanel::bottom("storage_warning").show(ctx, |ui| {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "⚠ No writable config directory: settings and logins won't be saved. \
+                     Start with --config-dir=PATH to choose one.",
+                );
+            });
+        }
 
-        while let Ok(event) = self.event_rx.try_recv() {
-            reducer::reduce(
-                &mut self.state,
-                event,
-                &mut self.config,
-                self.event_tx.clone(),
-            );
+        let focused = ctx.input(|i| i.focused);
+        let mut events = Vec::new();
+        let mut keyword_matched = false;
+        while events.len() < MAX_EVENTS_PER_FRAME {
+            let Ok(event) = self.event_rx.try_recv() else {
+                break;
+            };
+            if let AppEvent::Chat(ChatEvent::NewChatMessage(message))
+            | AppEvent::ChannelChat {
+                event: ChatEvent::NewChatMessage(message),
+                ..
+            } = &event
+                && !focused
+                && !self.config.is_ignored(&message.sender_name)
+            {
+                self.unread_count += 1;
+                keyword_matched |=
+                    message_style::matches_keywords(message, &self.config.highlight_keywords);
+            }
+            events.push(event);
+        }
+        if keyword_matched {
+            // Flashes the taskbar entry where the platform supports it.
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
         }
+        if events.len() == MAX_EVENTS_PER_FRAME {
+            // Leave the rest for the next frame so a flood can't stall drawing.
+            ctx.request_repaint();
+        }
+        for action in reducer::reduce_batch(&mut self.state, events, &mut self.config) {
+            actions::perform(action, &self.event_tx, &self.config_saver);
+        }
+        if focused {
+            self.unread_count = 0;
+        }
+        self.update_window_title(ctx);
 
         let mut send_action: Option<bool> = None;
         let mut login_action: Option<bool> = None;
@@ -190,11 +373,16 @@ impl eframe::App for App {
                     let tx = self.event_tx.clone();
                     tokio::spawn(async move {
                         let config_result = config::load().await;
-                        tx.send(AppEvent::ConfigLoaded(config_result)).await.ok();
+                        tx.send(AppEvent::ConfigLoaded(config_result.map(Box::new)))
+                            .await
+                            .ok();
                     });
                 }
                 self.draw_loading_ui(ctx, "Starting...");
             }
+            AppState::Initializing { .. } if self.mock_rate.is_some() => {
+                self.start_mock_session();
+            }
             AppState::Initializing { task_spawned } => {
                 if !*task_spawned {
                     *task_spawned = true;
@@ -212,7 +400,8 @@ impl eframe::App for App {
                                 client_id,
                                 client_secret,
                                 tx.clone(),
-                                config.active_profile_name,
+                                config.active_profile_name.clone(),
+                                config.requested_scopes(),
                             )
                             .await
                             {
@@ -242,34 +431,129 @@ impl eframe::App for App {
             AppState::RequestingInteractiveLogin { profile_name } => {
                 trigger_interactive_login_for_profile = Some(profile_name.clone());
             }
-            AppState::LoggedIn { .. } => self.draw_logged_in(ctx, &mut send_action),
+            AppState::LoggedIn { pending_joins, .. } => {
+                let mut pending = std::mem::take(pending_joins);
+                if pending.is_empty() {
+                    pending = std::mem::take(&mut self.rejoin_after_login);
+                }
+                for channel in pending {
+                    self.join_channel(channel);
+                }
+                self.draw_logged_in(ctx, &mut send_action);
+            }
         }
 
         // Refactored action handling to be outside the main state match
         if let Some(is_announcement) = send_action {
             self.send_message(is_announcement);
         }
+        self.draw_link_confirmation(ctx);
+        self.draw_link_open_confirmation(ctx);
+        self.draw_session_expired(ctx);
         if let Some(true) = login_action {
             self.handle_login_action();
         }
         if let Some(profile_name) = trigger_interactive_login_for_profile {
             self.trigger_interactive_login(Some(profile_name));
         }
+        self.ensure_status_line_task();
+        self.ensure_token_watch();
         if cancel_auth_action {
             // Use try_send to avoid blocking the UI thread.
             self.event_tx.try_send(AppEvent::AuthCancel).ok();
         }
 
-        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        // Incoming events wake the UI on their own (see `spawn_event_forwarder`) and
+        // spinners/animated images schedule their own frames, so this only bounds how
+        // stale time-based UI like countdowns can get while idle.
+        let mut idle_interval =
+            Duration::from_secs_f32(self.config.idle_repaint_interval_secs.max(0.05));
+        if let AppState::LoggedIn {
+            pending_raid: Some(_),
+            ..
+        } = &self.state
+        {
+            idle_interval = idle_interval.min(Duration::from_secs(1));
+        }
+        ctx.request_repaint_after(idle_interval);
     }
 }
 
 impl App {
+    /// Creates the app's event channel. Events are relayed through a small task that
+    /// requests a repaint for each one, so the UI reacts immediately without having to
+    /// poll on a timer.
+    fn spawn_event_forwarder(
+        ctx: egui::Context,
+    ) -> (mpsc::Sender<AppEvent>, mpsc::Receiver<AppEvent>) {
+        let (event_tx, mut incoming_rx) = mpsc::channel(100);
+        let (forward_tx, event_rx) = mpsc::channel(100);
+        tokio::spawn(async move {
+            while let Some(event) = incoming_rx.recv().await {
+                if forward_tx.send(event).await.is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+        (event_tx, event_rx)
+    }
+
     fn handle_login_action(&mut self) {
         let profile_name = self.config.active_profile_name.clone();
         self.trigger_interactive_login(profile_name);
     }
 
+    /// Logs the active profile in again, coming back to the open channels after.
+    fn reauthenticate(&mut self) {
+        if let AppState::LoggedIn { tabs, .. } = &self.state {
+            self.rejoin_after_login = tabs.iter().map(|tab| tab.channel.clone()).collect();
+        }
+        self.handle_login_action();
+    }
+
+    /// Leaves every channel and deletes the active profile's stored token, going
+    /// back to choosing a profile.
+    fn log_out(&mut self) {
+        if let AppState::LoggedIn { tabs, .. } = &self.state {
+            for task in tabs.iter().filter_map(|tab| tab.eventsub_task.as_ref()) {
+                task.abort();
+            }
+        }
+        if let Some(task) = self.status_line_task.take() {
+            task.abort();
+        }
+
+        let client_id = self.config.client_id.clone().unwrap_or_default();
+        let client_secret = self.config.client_secret.clone().unwrap_or_default();
+        let tx = self.event_tx.clone();
+        let profile_name = self.config.active_profile_name.take();
+        let scopes = self.config.requested_scopes();
+        tokio::spawn(async move {
+            let result =
+                match AuthClient::new(client_id, client_secret, tx, profile_name, scopes).await {
+                    Ok(auth_client) => auth_client.delete_token().await,
+                    Err(e) => Err(e),
+                };
+            match result {
+                Ok(()) => tracing::info!("Logged out"),
+                Err(e) => tracing::error!("Failed to delete token while logging out: {}", e),
+            }
+        });
+        self.config_saver.save(self.config.clone());
+
+        self.state = if self.config.profiles.is_empty() {
+            AppState::FirstTimeSetup {
+                client_id_input: String::new(),
+                client_secret_input: String::new(),
+                profile_name_input: String::new(),
+                error: None,
+            }
+        } else {
+            AppState::ProfileSelection { error: None }
+        };
+    }
+
     fn handle_profile_switch(&mut self, profile_name: String) {
         self.config.active_profile_name = Some(profile_name.clone());
         self.show_profile_manager = false;
@@ -283,27 +567,33 @@ impl App {
         let client_secret = self.config.client_secret.clone().unwrap_or_default();
         let tx = self.event_tx.clone();
         let profile_name_clone = profile_name.clone();
+        let scopes = self.config.requested_scopes();
 
         self.state = AppState::Startup {
             task_spawned: true, // Prevent re-triggering silent auth
         };
 
         tokio::spawn(async move {
-            let auth_client =
-                match AuthClient::new(client_id, client_secret, tx.clone(), profile_name_clone)
+            let auth_client = match AuthClient::new(
+                client_id,
+                client_secret,
+                tx.clone(),
+                profile_name_clone,
+                scopes,
+            )
+            .await
+            {
+                Ok(client) => client,
+                Err(e) => {
+                    tx.send(AppEvent::AuthFlowStartFailed(format!(
+                        "Failed to create auth client: {}",
+                        e
+                    )))
                     .await
-                {
-                    Ok(client) => client,
-                    Err(e) => {
-                        tx.send(AppEvent::AuthFlowStartFailed(format!(
-                            "Failed to create auth client: {}",
-                            e
-                        )))
-                        .await
-                        .ok();
-                        return;
-                    }
-                };
+                    .ok();
+                    return;
+                }
+            };
 
             match auth_client.clone().start_interactive_login().await {
                 Ok(token) => {
@@ -329,14 +619,115 @@ impl App {
         });
     }
 
+    /// Keeps the status line poller running while logged in with a source configured.
+    fn ensure_status_line_task(&mut self) {
+        if self.status_line_task.is_some() || !matches!(self.state, AppState::LoggedIn { .. }) {
+            return;
+        }
+        if let Some(source) = self.config.status_line_source() {
+            let interval = Duration::from_secs(self.config.status_line_interval_secs.max(1));
+            self.status_line_task = Some(tokio::spawn(status_line::run(
+                source,
+                interval,
+                self.event_tx.clone(),
+            )));
+        }
+    }
+
+    /// Watches the logged-in token, starting over whenever it is replaced. The mock
+    /// session's token isn't known to Twitch, so it isn't watched.
+    fn ensure_token_watch(&mut self) {
+        let token = match &self.state {
+            AppState::LoggedIn { token, .. } if self.mock_rate.is_none() => Some(token.clone()),
+            _ => None,
+        };
+        if let (Some(token), Some((watched, _))) = (&token, &self.token_watch)
+            && Arc::ptr_eq(token, watched)
+        {
+            return;
+        }
+        if let Some((_, task)) = self.token_watch.take() {
+            task.abort();
+        }
+        let Some(token) = token else {
+            return;
+        };
+
+        let client_id = self.config.client_id.clone().unwrap_or_default();
+        let client_secret = self.config.client_secret.clone().unwrap_or_default();
+        let tx = self.event_tx.clone();
+        let profile_name = self.config.active_profile_name.clone();
+        let scopes = self.config.requested_scopes();
+        let user_token = (*token).clone();
+        let task = tokio::spawn(async move {
+            match AuthClient::new(client_id, client_secret, tx, profile_name, scopes).await {
+                Ok(auth_client) => auth_client.watch_token(user_token).await,
+                Err(e) => tracing::error!("Failed to start watching the token: {}", e),
+            }
+        });
+        self.token_watch = Some((token, task));
+    }
+
+    fn restart_status_line_task(&mut self) {
+        if let Some(task) = self.status_line_task.take() {
+            task.abort();
+        }
+        if let AppState::LoggedIn { status_line, .. } = &mut self.state {
+            *status_line = None;
+        }
+    }
+
     fn apply_settings(&mut self, ctx: &egui::Context) {
+        self.effective_config = self.config.effective();
+        if let Some(cache) = &self.emote_cache {
+            cache.set_max_bytes(self.config.emote_cache_mb * BYTES_PER_MB);
+        }
         let mut style = (*ctx.style()).clone();
         style.text_styles.iter_mut().for_each(|(_, font_id)| {
-            font_id.size = self.config.font_size;
+            font_id.size = self.effective_config.font_size;
         });
+        style.visuals = self.visuals(ctx);
         ctx.set_style(style);
     }
 
+    fn visuals(&self, ctx: &egui::Context) -> egui::Visuals {
+        let dark = match self.config.theme {
+            Theme::Dark => true,
+            Theme::Light => false,
+            // egui tracks the operating system's theme, including later switches.
+            Theme::System => ctx.system_theme() != Some(egui::Theme::Light),
+        };
+        let mut visuals = if dark {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        };
+        if let Some([r, g, b]) = self.config.accent_color {
+            visuals.selection.bg_fill = egui::Color32::from_rgb(r, g, b);
+        }
+        visuals
+    }
+
+    /// Shows the current channel and unread count in the title, e.g.
+    /// "#channel (3) — LiveNAC", so several chat windows can be told apart.
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let channel = self
+            .state
+            .current_channel()
+            .filter(|_| self.config.show_channel_in_title);
+        let title = match channel {
+            Some(channel) if self.unread_count > 0 => {
+                format!("#{} ({}) — LiveNAC", channel, self.unread_count)
+            }
+            Some(channel) => format!("#{} — LiveNAC", channel),
+            None => "LiveNAC".to_string(),
+        };
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
     fn draw_loading_ui(&self, ctx: &egui::Context, message: &str) {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.centered_and_justified(|ui| {
@@ -371,6 +762,9 @@ impl App {
             if self.show_toolbar {
                 egui::MenuBar::new().ui(ui, |ui| {
                     ui.menu_button("File", |ui| {
+                        if ui.button("Replay Chat Log…").clicked() {
+                            self.show_replay_window = true;
+                        }
                         if ui.button("Exit").clicked() {
                             std::process::exit(0);
                         }
@@ -393,6 +787,14 @@ impl App {
 
         self.draw_settings_window(ctx);
         self.draw_profile_manager_window(ctx);
+        if let Some(action) = replay::draw_replay_window(
+            ctx,
+            &mut self.replay,
+            &self.effective_config,
+            &mut self.show_replay_window,
+        ) {
+            self.handle_message_action(action);
+        }
     }
 
     fn draw_first_time_setup(&mut self, ctx: &egui::Context, login_action: &mut Option<bool>) {
@@ -419,6 +821,10 @@ impl App {
                     profile_input_resp = Some(ui.text_edit_singleline(profile_name_input));
                     ui.add_space(10.0);
 
+                    ui.label("Channel to Join (optional):");
+                    ui.text_edit_singleline(&mut self.default_channel_input);
+                    ui.add_space(10.0);
+
                     let credentials_exist =
                         self.config.client_id.is_some() && self.config.client_secret.is_some();
                     if !credentials_exist {
@@ -480,7 +886,17 @@ impl App {
                         self.config.profiles.push(Profile {
                             name: profile_name_input.clone(),
                             twitch_user_id: None,
+                            open_channels: Vec::new(),
+                            appearance: Default::default(),
                         });
+                        let default_channel = self
+                            .default_channel_input
+                            .trim()
+                            .trim_start_matches('#')
+                            .to_lowercase();
+                        if !default_channel.is_empty() {
+                            self.config.default_channel = Some(default_channel);
+                        }
 
                         *login_action = Some(true);
                     }
@@ -508,6 +924,7 @@ impl App {
                 &mut self.new_profile_name,
                 &mut self.profile_to_remove_name,
                 &mut self.profile_manager_error,
+                None,
             ) {
                 self.handle_profile_action(action);
             }
@@ -515,38 +932,152 @@ impl App {
     }
 
     fn draw_logged_in(&mut self, ctx: &egui::Context, send_action: &mut Option<bool>) {
+        let mut raid_action = None;
+        let mut relogin_requested = false;
+        let mut logout_requested = false;
+        let mut save_log_requested = false;
+        let mut join_request = None;
+        let mut message_action = None;
+        let mut tab_action = None;
+        let current_channel = self.state.current_channel().map(str::to_string);
+        let (connection_lost, is_moderator, reconnecting, connection_status) = self
+            .state
+            .active_tab()
+            .map(|tab| {
+                let connection_lost = tab
+                    .eventsub_task
+                    .as_ref()
+                    .is_some_and(|task| task.is_finished());
+                let status = if connection_lost {
+                    ConnectionStatus::Disconnected
+                } else {
+                    tab.connection_status
+                };
+                (
+                    connection_lost,
+                    tab.is_moderator,
+                    tab.reconnecting,
+                    Some(status),
+                )
+            })
+            .unwrap_or_default();
         if let AppState::LoggedIn {
             user_login,
             channel_to_join,
-            current_channel,
-            chat_messages,
+            tabs,
+            active_tab,
             last_error,
-            token,
-            user_id,
-            eventsub_task,
+            pending_raid,
+            reauth_required,
+            status_line,
             ..
         } = &mut self.state
         {
+            if pending_raid
+                .as_ref()
+                .is_some_and(|raid| raid.started_at.elapsed().as_secs() >= RAID_COUNTDOWN_SECS)
+            {
+                *pending_raid = None;
+            }
+
             if last_error.is_some() {
                 TopBottomPanel::top("error_panel").show(ctx, |ui| {
                     ui.horizontal(|ui| {
                         ui.label("Error:");
                         ui.colored_label(ui.visuals().error_fg_color, last_error.as_ref().unwrap());
+                        if *reauth_required && ui.button("Log In Again").clicked() {
+                            relogin_requested = true;
+                        } else if connection_lost && ui.button("Reconnect").clicked() {
+                            join_request = current_channel.clone();
+                        }
+                    });
+                });
+            }
+
+            if let Some((attempt, max_attempts)) = reconnecting {
+                TopBottomPanel::top("reconnect_panel").show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!(
+                            "Chat connection lost, reconnecting… (attempt {} of {})",
+                            attempt, max_attempts
+                        ));
                     });
                 });
             }
 
+            let is_broadcaster = current_channel
+                .as_deref()
+                .is_some_and(|channel| channel.eq_ignore_ascii_case(user_login));
+
+            let narrow = ctx.screen_rect().width() < self.config.narrow_layout_width;
+            let compact = self.effective_config.compact_header || narrow;
             TopBottomPanel::top("top_panel").show(ctx, |ui| {
                 ui.horizontal(|ui| {
                     if ui.button("☰").clicked() {
                         self.show_toolbar = !self.show_toolbar;
                     }
-                    ui.heading(format!("Logged in as {}", user_login));
+                    if compact {
+                        let selected = current_channel
+                            .as_deref()
+                            .map_or("Join a channel…".to_string(), |c| format!("#{}", c));
+                        egui::ComboBox::from_id_salt("compact_channel_combo")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                let response = ui.add(
+                                    egui::TextEdit::singleline(channel_to_join)
+                                        .hint_text("Channel name"),
+                                );
+                                if response.lost_focus()
+                                    && ctx.input(|i| i.key_pressed(Key::Enter))
+                                    && !channel_to_join.is_empty()
+                                {
+                                    join_request = Some(channel_to_join.clone());
+                                }
+                                for channel in &self.config.recent_channels {
+                                    let is_current = current_channel.as_deref() == Some(channel);
+                                    if ui.selectable_label(is_current, channel).clicked() {
+                                        join_request = Some(channel.clone());
+                                    }
+                                }
+                            });
+                        if let Some(status) = connection_status {
+                            draw_connection_status(ui, status);
+                        }
+                        if connection_lost && ui.small_button("Retry").clicked() {
+                            join_request = current_channel.clone();
+                        }
+                        if is_moderator {
+                            ui.toggle_value(&mut self.show_automod_queue, "🛡");
+                        }
+                    } else {
+                        ui.heading(format!("Logged in as {}", user_login));
+                    }
+                    if let Some(line) = status_line.as_deref() {
+                        ui.separator();
+                        ui.label(RichText::new(line).italics());
+                    }
                 });
 
                 if self.show_toolbar {
                     egui::MenuBar::new().ui(ui, |ui| {
                         ui.menu_button("File", |ui| {
+                            if ui.button("Replay Chat Log…").clicked() {
+                                self.show_replay_window = true;
+                            }
+                            if ui
+                                .add_enabled(
+                                    current_channel.is_some(),
+                                    egui::Button::new("Save Chat Log…"),
+                                )
+                                .on_hover_text("As plain text, or HTML when saved as .html")
+                                .clicked()
+                            {
+                                save_log_requested = true;
+                            }
+                            if ui.button("Log Out").clicked() {
+                                logout_requested = true;
+                            }
                             if ui.button("Exit").clicked() {
                                 std::process::exit(0);
                             }
@@ -557,69 +1088,144 @@ impl App {
                         if ui.button("Profiles").clicked() {
                             self.show_profile_manager = true;
                         }
+                        if let Some(channel) = current_channel.as_deref()
+                            && ui.button("Open in Browser").clicked()
+                        {
+                            open_channel_in_browser(channel);
+                        }
                     });
                 }
 
-                ui.separator();
-                ui.horizontal(|ui| {
-                    ui.label("Channel:");
-                    let response = ui.text_edit_singleline(channel_to_join);
-                    let join_clicked = ui.button("Join").clicked();
-                    let enter_pressed =
-                        response.lost_focus() && ctx.input(|i| i.key_pressed(Key::Enter));
-                    if (join_clicked || enter_pressed) && !channel_to_join.is_empty() {
-                        if let Some(task) = eventsub_task.take() {
-                            task.abort();
-                        }
-                        *last_error = None;
-                        chat_messages.clear();
-                        *current_channel = Some(channel_to_join.clone());
-                        let tx = self.event_tx.clone();
-                        let token = token.clone();
-                        let user_id = user_id.clone();
-                        let channel_login = channel_to_join.clone();
-                        *eventsub_task = Some(tokio::spawn(async move {
-                            let chat_client = ChatClient::new();
-                            match chat_client.get_user_id(&channel_login, &token).await {
-                                Ok(Some(id)) => {
-                                    let eventsub_client =
-                                        EventSubClient::new(user_id.clone(), token.clone(), tx, id);
-                                    if let Err(e) = eventsub_client.run().await {
-                                        tracing::error!("EventSub client failed: {}", e);
-                                    }
-                                }
-                                _ => {
-                                    let _ = tx
-                                        .send(AppEvent::Chat(ChatEvent::EventSubError(
-                                            "Channel not found".to_string(),
-                                        )))
-                                        .await;
-                                }
+                if !compact {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Channel:");
+                        let response = ui.text_edit_singleline(channel_to_join);
+                        let join_clicked = ui.button("Join").clicked();
+                        let enter_pressed =
+                            response.lost_focus() && ctx.input(|i| i.key_pressed(Key::Enter));
+                        if (join_clicked || enter_pressed) && !channel_to_join.is_empty() {
+                            join_request = Some(channel_to_join.clone());
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if tabs.is_empty() {
+                            ui.label("Not in any channel");
+                        }
+                        if let Some(status) = connection_status {
+                            draw_connection_status(ui, status);
+                        }
+                        if connection_lost && ui.small_button("Retry").clicked() {
+                            join_request = current_channel.clone();
+                        }
+                        if is_moderator {
+                            ui.toggle_value(&mut self.show_automod_queue, "🛡 AutoMod");
+                        }
+                        if let Some(channel) = current_channel.as_deref()
+                            && ui
+                                .small_button("🌐")
+                                .on_hover_text("Open channel in browser")
+                                .clicked()
+                        {
+                            open_channel_in_browser(channel);
+                        }
+                    });
+                }
+
+                if !tabs.is_empty() {
+                    ui.separator();
+                    tab_action = channel_list::draw_channel_tabs(ui, tabs, *active_tab);
+                }
+
+                if is_broadcaster {
+                    ui.horizontal(|ui| {
+                        if let Some(raid) = pending_raid {
+                            let remaining = RAID_COUNTDOWN_SECS
+                                .saturating_sub(raid.started_at.elapsed().as_secs());
+                            ui.label(format!("Raiding {} in {}s", raid.target, remaining));
+                            if ui.button("Cancel Raid").clicked() {
+                                raid_action = Some(RaidAction::Cancel);
                             }
-                        }));
-                    }
-                });
-                ui.label(format!(
-                    "Current Channel: {}",
-                    current_channel.as_deref().unwrap_or("None")
-                ));
+                        } else {
+                            if self.config.redemption_queue_enabled {
+                                ui.toggle_value(&mut self.show_redemption_queue, "🎁 Redemptions");
+                            }
+                            ui.label("Raid:");
+                            ui.text_edit_singleline(&mut self.raid_target);
+                            if ui
+                                .add_enabled(
+                                    !self.raid_target.trim().is_empty(),
+                                    egui::Button::new("Start Raid"),
+                                )
+                                .clicked()
+                            {
+                                raid_action =
+                                    Some(RaidAction::Start(self.raid_target.trim().to_string()));
+                                self.raid_target.clear();
+                            }
+                        }
+                    });
+                }
             });
 
             TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
                 if self.show_emote_picker {
-                    emote_picker::draw_emote_picker(ui, &mut self.state, &self.config);
+                    emote_picker::draw_emote_picker(
+                        ui,
+                        &mut self.state,
+                        &self.effective_config,
+                        &mut self.emote_picker,
+                    );
                     ui.separator();
                 }
                 chat_bar::draw_chat_bar(
                     ui,
                     &mut self.state,
+                    &self.effective_config,
                     send_action,
                     &mut self.show_emote_picker,
                     &mut self.show_user_list,
+                    &mut self.paste_state,
+                    &mut self.completion_state,
                 );
             });
 
-            if self.show_user_list {
+            let mut redemption_action = None;
+            if is_broadcaster && self.config.redemption_queue_enabled && self.show_redemption_queue
+            {
+                SidePanel::right("redemption_queue_panel")
+                    .min_width(180.0)
+                    .default_width(220.0)
+                    .show(ctx, |ui| {
+                        redemption_action = redemption_queue::draw_redemption_queue(
+                            ui,
+                            &mut self.state,
+                            &self.effective_config,
+                        );
+                    });
+            }
+            if let Some(action) = redemption_action {
+                self.resolve_redemption(action);
+            }
+
+            let mut held_message_action = None;
+            if is_moderator && self.show_automod_queue {
+                SidePanel::right("automod_queue_panel")
+                    .min_width(180.0)
+                    .default_width(220.0)
+                    .show(ctx, |ui| {
+                        held_message_action = automod_queue::draw_automod_queue(
+                            ui,
+                            &mut self.state,
+                            &self.effective_config,
+                        );
+                    });
+            }
+            if let Some(action) = held_message_action {
+                self.resolve_held_message(action);
+            }
+
+            if self.show_user_list && !narrow {
                 SidePanel::right("user_list_panel")
                     .min_width(150.0)
                     .default_width(180.0)
@@ -629,55 +1235,897 @@ impl App {
             }
 
             egui::CentralPanel::default().show(ctx, |ui| {
-                chat_log::draw_chat_log(ui, &mut self.state, &self.config);
+                message_action =
+                    chat_log::draw_chat_log(ui, &mut self.state, &self.effective_config);
             });
 
             self.draw_settings_window(ctx);
             self.draw_profile_manager_window(ctx);
+            self.draw_raw_event_window(ctx);
+            if let Some(action) = replay::draw_replay_window(
+                ctx,
+                &mut self.replay,
+                &self.effective_config,
+                &mut self.show_replay_window,
+            ) {
+                message_action = Some(action);
+            }
+        }
+
+        // `/` jumps to the message box, unless something is being typed into already.
+        let input_id = egui::Id::new(chat_bar::CHAT_INPUT_ID);
+        if matches!(self.state, AppState::LoggedIn { .. })
+            && ctx.memory(|m| m.focused().is_none())
+            && ctx.input_mut(|i| {
+                let pressed = i.consume_key(Modifiers::NONE, Key::Slash);
+                if pressed {
+                    // The key also arrives as typed text, which the box would take
+                    // as the start of a command once it has focus.
+                    i.events
+                        .retain(|event| !matches!(event, egui::Event::Text(text) if text == "/"));
+                }
+                pressed
+            })
+        {
+            ctx.memory_mut(|m| m.request_focus(input_id));
+        }
+        let switcher_shortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::P);
+        if ctx.input_mut(|i| i.consume_shortcut(&switcher_shortcut)) {
+            self.channel_switcher.toggle();
+        }
+        let switcher_channels: Vec<String> = match &self.state {
+            AppState::LoggedIn { .. } => self
+                .config
+                .recent_channels
+                .iter()
+                .filter(|c| current_channel.as_deref() != Some(c.as_str()))
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        };
+        if let Some(channel) = channel_switcher::draw_channel_switcher(
+            ctx,
+            &mut self.channel_switcher,
+            &switcher_channels,
+        ) {
+            join_request = Some(channel);
+        }
+
+        if let Some(channel) = join_request {
+            self.join_channel(channel);
+        }
+        match tab_action {
+            Some(TabAction::Select(index)) => self.state.select_tab(index),
+            Some(TabAction::Close(index)) => self.close_tab(index),
+            None => {}
+        }
+        if let Some(action) = message_action {
+            if matches!(action, MessageAction::Reply(_)) {
+                ctx.memory_mut(|m| m.request_focus(input_id));
+            }
+            self.handle_message_action(action);
+        }
+        match raid_action {
+            Some(RaidAction::Start(target)) => self.start_raid(target),
+            Some(RaidAction::Cancel) => self.cancel_raid(),
+            None => {}
+        }
+        if relogin_requested {
+            self.reauthenticate();
+        }
+        if save_log_requested {
+            self.save_chat_log();
         }
+        if logout_requested {
+            self.log_out();
+        }
+    }
+
+    /// Asks where to save the active tab's messages and writes them there.
+    fn save_chat_log(&self) {
+        let Some(tab) = self.state.active_tab() else {
+            return;
+        };
+        let channel = tab.channel.clone();
+        let messages: Vec<ChatMessage> = tab.chat_messages.iter().cloned().collect();
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let Some(file) = rfd::AsyncFileDialog::new()
+                .set_file_name(format!("{}.txt", channel))
+                .add_filter("Text", &["txt"])
+                .add_filter("HTML", &["html", "htm"])
+                .save_file()
+                .await
+            else {
+                return;
+            };
+            let path = file.path().to_path_buf();
+            let contents = export::render(&path, &channel, &messages);
+            let result = tokio::fs::write(&path, contents)
+                .await
+                .map(|()| path)
+                .map_err(|e| e.to_string());
+            let _ = tx.send(AppEvent::ChatLogSaved(result)).await;
+        });
     }
 
     fn draw_settings_window(&mut self, ctx: &egui::Context) {
+        let current_channel = self.state.current_channel().map(str::to_string);
+        let mut restart_status_line = false;
+        let mut fetch_twitch_emotes = false;
+        let mut scopes_changed = false;
         egui::Window::new("Settings")
             .open(&mut self.show_settings_window)
             .show(ctx, |ui| {
                 ui.heading("Appearance");
                 let mut config_changed = false;
 
+                if let Some(profile_name) = self.config.active_profile_name.clone() {
+                    ui.horizontal(|ui| {
+                        ui.label("Apply to:");
+                        ui.radio_value(&mut self.edit_profile_appearance, false, "All profiles");
+                        ui.radio_value(
+                            &mut self.edit_profile_appearance,
+                            true,
+                            format!("Profile \"{}\" only", profile_name),
+                        );
+                    });
+                }
+                // Appearance widgets edit a copy; the changes are then written to either
+                // the global settings or the active profile's overrides.
+                let profile_scope =
+                    self.edit_profile_appearance && self.config.get_active_profile().is_some();
+                let appearance_before = if profile_scope {
+                    self.config.effective()
+                } else {
+                    self.config.clone()
+                };
+                let mut appearance = appearance_before.clone();
+                if profile_scope {
+                    let has_overrides = self
+                        .config
+                        .get_active_profile()
+                        .is_some_and(|profile| !profile.appearance.is_empty());
+                    if ui
+                        .add_enabled(has_overrides, egui::Button::new("Reset to global settings"))
+                        .clicked()
+                    {
+                        if let Some(profile) = self.config.get_active_profile_mut() {
+                            profile.appearance = AppearanceOverrides::default();
+                        }
+                        config_changed = true;
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Theme:");
+                    egui::ComboBox::from_id_salt("theme_combo")
+                        .selected_text(format!("{:?}", self.config.theme))
+                        .show_ui(ui, |ui| {
+                            for theme in [Theme::Dark, Theme::Light, Theme::System] {
+                                config_changed |= ui
+                                    .selectable_value(
+                                        &mut self.config.theme,
+                                        theme,
+                                        format!("{:?}", theme),
+                                    )
+                                    .changed();
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Accent color:");
+                    let mut accent = self.config.accent_color.unwrap_or_else(|| {
+                        let default = ui.visuals().selection.bg_fill;
+                        [default.r(), default.g(), default.b()]
+                    });
+                    if ui
+                        .color_edit_button_srgb(&mut accent)
+                        .on_hover_text("For highlights and names of chatters without a color")
+                        .changed()
+                    {
+                        self.config.accent_color = Some(accent);
+                        config_changed = true;
+                    }
+                    if ui
+                        .add_enabled(
+                            self.config.accent_color.is_some(),
+                            egui::Button::new("Reset"),
+                        )
+                        .clicked()
+                    {
+                        self.config.accent_color = None;
+                        config_changed = true;
+                    }
+                });
+
+                ui.label("Pinned user colors:");
+                let mut unpin = None;
+                egui::Grid::new("user_colors_grid").show(ui, |ui| {
+                    let mut names: Vec<String> = self.config.user_colors.keys().cloned().collect();
+                    names.sort();
+                    for name in names {
+                        ui.label(&name);
+                        if let Some(color) = self.config.user_colors.get_mut(&name) {
+                            config_changed |= ui.color_edit_button_srgb(color).changed();
+                        }
+                        if ui.small_button("✖").on_hover_text("Unpin").clicked() {
+                            unpin = Some(name);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(name) = unpin {
+                    self.config.user_colors.remove(&name);
+                    config_changed = true;
+                }
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.user_color_input)
+                            .hint_text("Username")
+                            .desired_width(150.0),
+                    );
+                    let name = self.user_color_input.trim().to_lowercase();
+                    if ui
+                        .add_enabled(!name.is_empty(), egui::Button::new("Pin Color"))
+                        .clicked()
+                    {
+                        // Start from the color they're shown in now.
+                        let stored_color = self
+                            .state
+                            .active_tab()
+                            .and_then(|tab| {
+                                tab.chat_messages
+                                    .iter()
+                                    .rev()
+                                    .find(|m| m.sender_name.eq_ignore_ascii_case(&name))
+                            })
+                            .and_then(|m| m.sender_color);
+                        let color = message_style::color_for_user(&name, stored_color);
+                        self.config.user_colors.entry(name).or_insert([
+                            color.r(),
+                            color.g(),
+                            color.b(),
+                        ]);
+                        self.user_color_input.clear();
+                        config_changed = true;
+                    }
+                });
+
+                config_changed |= ui
+                    .add(egui::Slider::new(&mut appearance.font_size, 8.0..=24.0).text("Font Size"))
+                    .changed();
+
                 config_changed |= ui
                     .add(
-                        egui::Slider::new(&mut self.config.font_size, 8.0..=24.0).text("Font Size"),
+                        egui::Slider::new(&mut appearance.emote_size, 16.0..=64.0)
+                            .text("Emote Size"),
                     )
                     .changed();
 
                 config_changed |= ui
                     .add(
-                        egui::Slider::new(&mut self.config.emote_size, 16.0..=64.0)
-                            .text("Emote Size"),
+                        egui::Slider::new(&mut self.config.idle_repaint_interval_secs, 0.1..=10.0)
+                            .text("Idle Refresh Interval (s)"),
                     )
+                    .on_hover_text("How often the window redraws when nothing is happening")
                     .changed();
 
                 config_changed |= ui
                     .checkbox(
-                        &mut self.config.collapse_emotes,
+                        &mut self.config.show_channel_in_title,
+                        "Show channel in window title",
+                    )
+                    .on_hover_text(
+                        "Also counts messages that arrive while the window is in the background",
+                    )
+                    .changed();
+                config_changed |= ui
+                    .checkbox(&mut appearance.compact_header, "Compact header")
+                    .on_hover_text("Fit the channel picker and status into a single row")
+                    .changed();
+                config_changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.config.narrow_layout_width, 0.0..=1000.0)
+                            .text("Narrow Layout Below (px)"),
+                    )
+                    .on_hover_text(
+                        "Narrower windows get a compact header, overflow menus and no user list",
+                    )
+                    .changed();
+                config_changed |= ui
+                    .checkbox(
+                        &mut appearance.collapse_emotes,
                         "Collapse space between emotes",
                     )
                     .changed();
-
                 config_changed |= ui
-                    .checkbox(&mut self.config.show_timestamps, "Show Timestamps")
+                    .checkbox(
+                        &mut appearance.group_consecutive,
+                        "Group consecutive messages from the same sender",
+                    )
                     .changed();
-
-                if config_changed {
-                    let config_to_save = self.config.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = config::save(&config_to_save).await {
-                            tracing::error!("Failed to save config: {}", e);
+                ui.horizontal(|ui| {
+                    config_changed |= ui
+                        .checkbox(
+                            &mut appearance.highlight_own_messages,
+                            "Highlight my messages",
+                        )
+                        .changed();
+                    ui.add_enabled_ui(appearance.highlight_own_messages, |ui| {
+                        for style in [OwnMessageStyle::Tint, OwnMessageStyle::Border] {
+                            config_changed |= ui
+                                .radio_value(
+                                    &mut appearance.own_message_style,
+                                    style,
+                                    format!("{:?}", style),
+                                )
+                                .changed();
                         }
                     });
-                }
-            });
-    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Text Alignment:");
+                    egui::ComboBox::from_id_salt("inline_alignment_combo")
+                        .selected_text(format!("{:?}", appearance.inline_alignment))
+                        .show_ui(ui, |ui| {
+                            for alignment in [
+                                InlineAlignment::Top,
+                                InlineAlignment::Center,
+                                InlineAlignment::Bottom,
+                            ] {
+                                config_changed |= ui
+                                    .selectable_value(
+                                        &mut appearance.inline_alignment,
+                                        alignment,
+                                        format!("{:?}", alignment),
+                                    )
+                                    .changed();
+                            }
+                        });
+                });
+
+                config_changed |= ui
+                    .checkbox(&mut appearance.show_timestamps, "Show Timestamps")
+                    .changed();
+                ui.horizontal(|ui| {
+                    ui.label("Timezone:");
+                    egui::ComboBox::from_id_salt("timezone_combo")
+                        .selected_text(appearance.timezone.to_string())
+                        .show_ui(ui, |ui| {
+                            let zones = [DisplayTimezone::Local, DisplayTimezone::Utc]
+                                .into_iter()
+                                .chain(
+                                    chrono_tz::TZ_VARIANTS
+                                        .iter()
+                                        .map(|tz| DisplayTimezone::Named(*tz)),
+                                );
+                            for zone in zones {
+                                let label = zone.to_string();
+                                config_changed |= ui
+                                    .selectable_value(&mut appearance.timezone, zone, label)
+                                    .changed();
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Timestamp Format:");
+                    for (label, pattern) in TIMESTAMP_PRESETS {
+                        let selected = self.timestamp_format_input.is_none()
+                            && self.config.timestamp_format == pattern;
+                        if ui.selectable_label(selected, label).clicked() {
+                            self.timestamp_format_input = None;
+                            self.config.timestamp_format = pattern.to_string();
+                            config_changed = true;
+                        }
+                    }
+                    let mut input = self
+                        .timestamp_format_input
+                        .clone()
+                        .unwrap_or_else(|| self.config.timestamp_format.clone());
+                    if ui
+                        .add(egui::TextEdit::singleline(&mut input).desired_width(120.0))
+                        .on_hover_text("A strftime pattern, like %H:%M for hours and minutes")
+                        .changed()
+                    {
+                        if is_valid_format(&input) {
+                            self.timestamp_format_input = None;
+                            self.config.timestamp_format = input;
+                            config_changed = true;
+                        } else {
+                            self.timestamp_format_input = Some(input);
+                        }
+                    }
+                });
+                if self.timestamp_format_input.is_some() {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "Not a valid format; keeping the previous one",
+                    );
+                } else if !is_valid_format(&self.config.timestamp_format) {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "The configured format isn't valid; showing the default",
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Turn into links:");
+                    for (mode, label) in [
+                        (AutolinkMode::Off, "Nothing"),
+                        (AutolinkMode::HttpOnly, "http(s) links"),
+                        (AutolinkMode::All, "Links and domains"),
+                    ] {
+                        config_changed |= ui
+                            .radio_value(&mut self.config.autolink_urls, mode, label)
+                            .changed();
+                    }
+                });
+
+                if let Some(profile) = self
+                    .config
+                    .get_active_profile_mut()
+                    .filter(|_| profile_scope)
+                {
+                    profile
+                        .appearance
+                        .capture_changes(&appearance_before, &appearance);
+                } else {
+                    let mut changes = AppearanceOverrides::default();
+                    changes.capture_changes(&appearance_before, &appearance);
+                    changes.apply_to(&mut self.config);
+                }
+
+                ui.heading("System Messages");
+                egui::Grid::new("system_message_colors_grid").show(ui, |ui| {
+                    let dark_mode = ui.visuals().dark_mode;
+                    for category in SystemCategory::ALL {
+                        ui.label(format!("{} {}", category.icon(), category.label()));
+                        let custom = self.config.system_message_colors.get_mut(category);
+                        let mut color = custom.unwrap_or_else(|| {
+                            let default = message_style::default_system_color(category, dark_mode);
+                            [default.r(), default.g(), default.b()]
+                        });
+                        if ui.color_edit_button_srgb(&mut color).changed() {
+                            *custom = Some(color);
+                            config_changed = true;
+                        }
+                        if ui
+                            .add_enabled(custom.is_some(), egui::Button::new("Reset"))
+                            .clicked()
+                        {
+                            *custom = None;
+                            config_changed = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                ui.heading("Notifications");
+                ui.label("Highlight messages containing (one per line):");
+                let mut highlight_keywords = self.config.highlight_keywords.join("\n");
+                if ui
+                    .add(
+                        egui::TextEdit::multiline(&mut highlight_keywords)
+                            .desired_rows(2)
+                            .hint_text("your name"),
+                    )
+                    .changed()
+                {
+                    self.config.highlight_keywords =
+                        highlight_keywords.split('\n').map(str::to_string).collect();
+                    config_changed = true;
+                }
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.message_sound_enabled,
+                        "Play a sound for every message",
+                    )
+                    .changed();
+                config_changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.config.message_sound_volume, 0.0..=1.0)
+                            .text("Sound Volume"),
+                    )
+                    .changed();
+                config_changed |= ui
+                    .add(
+                        egui::Slider::new(
+                            &mut self.config.message_sound_min_interval_secs,
+                            0.5..=60.0,
+                        )
+                        .text("Minimum Seconds Between Sounds"),
+                    )
+                    .changed();
+                if let Some(channel) = &current_channel {
+                    let key = channel.to_lowercase();
+                    let mut channel_setting = self.config.message_sound_channels.get(&key).copied();
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Sound in #{}:", channel));
+                        let mut changed = false;
+                        changed |= ui
+                            .radio_value(&mut channel_setting, None, "Default")
+                            .changed();
+                        changed |= ui
+                            .radio_value(&mut channel_setting, Some(true), "On")
+                            .changed();
+                        changed |= ui
+                            .radio_value(&mut channel_setting, Some(false), "Off")
+                            .changed();
+                        if changed {
+                            match channel_setting {
+                                Some(enabled) => {
+                                    self.config.message_sound_channels.insert(key, enabled);
+                                }
+                                None => {
+                                    self.config.message_sound_channels.remove(&key);
+                                }
+                            }
+                            config_changed = true;
+                        }
+                    });
+                }
+
+                ui.heading("Status Line");
+                ui.label("Shown in the top panel, e.g. a now-playing song.");
+                let mut status_line_changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("Read from file:");
+                    let mut file = self.config.status_line_file.clone().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut file).changed() {
+                        self.config.status_line_file = (!file.is_empty()).then_some(file);
+                        status_line_changed = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Or run command:");
+                    let mut command = self.config.status_line_command.clone().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut command).changed() {
+                        self.config.status_line_command = (!command.is_empty()).then_some(command);
+                        status_line_changed = true;
+                    }
+                });
+                status_line_changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.config.status_line_interval_secs, 1..=300)
+                            .text("Refresh Interval (s)"),
+                    )
+                    .changed();
+                if status_line_changed {
+                    restart_status_line = true;
+                    config_changed = true;
+                }
+
+                ui.heading("Session");
+                ui.horizontal(|ui| {
+                    ui.label("Default channel:");
+                    let mut channel = self.config.default_channel.clone().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut channel).changed() {
+                        self.config.default_channel = (!channel.is_empty()).then_some(channel);
+                        config_changed = true;
+                    }
+                });
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.restore_session_on_launch,
+                        "Re-join open channels on launch",
+                    )
+                    .changed();
+                ui.horizontal(|ui| {
+                    ui.label("Messages shown per channel:");
+                    config_changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.max_chat_lines)
+                                .range(50..=10_000)
+                                .suffix(" messages"),
+                        )
+                        .on_hover_text("Older messages are dropped from the chat log.")
+                        .changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Chat history per channel:");
+                    config_changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.chat_history_lines)
+                                .range(0..=10_000)
+                                .suffix(" messages"),
+                        )
+                        .on_hover_text(
+                            "Saved to disk and shown again on rejoining. 0 turns it off.",
+                        )
+                        .changed();
+                });
+
+                ui.heading("Chat Input");
+                ui.label(RichText::new("Press / anywhere else to jump to the message box.").weak());
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.confirm_links,
+                        "Confirm before sending messages with links",
+                    )
+                    .changed();
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.send_lines_separately,
+                        "Send each line as its own message",
+                    )
+                    .on_hover_text(
+                        "Shift+Enter starts a new line. Otherwise lines are joined with spaces.",
+                    )
+                    .changed();
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.confirm_link_clicks,
+                        "Confirm before opening links from chat",
+                    )
+                    .changed();
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.show_emote_preview,
+                        "Preview emotes below the message box",
+                    )
+                    .changed();
+
+                ui.heading("Emotes");
+                let twitch_emotes_were_enabled = self.config.enable_twitch_emotes;
+                config_changed |= ui
+                    .checkbox(&mut self.config.enable_twitch_emotes, "Twitch emotes")
+                    .changed();
+                config_changed |= ui
+                    .checkbox(&mut self.config.enable_bttv, "BetterTTV emotes")
+                    .changed();
+                config_changed |= ui
+                    .checkbox(&mut self.config.enable_ffz, "FrankerFaceZ emotes")
+                    .changed();
+                config_changed |= ui
+                    .checkbox(&mut self.config.enable_7tv, "7TV emotes")
+                    .changed();
+                ui.horizontal(|ui| {
+                    ui.label("When codes collide, show:");
+                    for (precedence, label) in [
+                        (EmotePrecedence::Twitch, "Twitch emote"),
+                        (EmotePrecedence::ThirdParty, "Third-party emote"),
+                    ] {
+                        config_changed |= ui
+                            .radio_value(&mut self.config.emote_precedence, precedence, label)
+                            .on_hover_text(
+                                "For codes that are both a Twitch emote and a BTTV/FFZ/7TV emote",
+                            )
+                            .changed();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    config_changed |= ui
+                        .checkbox(
+                            &mut self.config.pause_emote_animations_when_busy,
+                            "Pause emote animations above",
+                        )
+                        .changed();
+                    config_changed |= ui
+                        .add_enabled(
+                            self.config.pause_emote_animations_when_busy,
+                            egui::DragValue::new(&mut self.config.busy_chat_threshold)
+                                .range(1.0..=500.0)
+                                .suffix(" msg/s"),
+                        )
+                        .changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Emote image cache:");
+                    config_changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut self.config.emote_cache_mb)
+                                .range(0..=10_000)
+                                .suffix(" MB"),
+                        )
+                        .on_hover_text(
+                            "Emote images kept on disk between sessions. 0 turns the cache off.",
+                        )
+                        .changed();
+                });
+                if self.config.enable_twitch_emotes && !twitch_emotes_were_enabled {
+                    fetch_twitch_emotes = true;
+                }
+
+                ui.heading("Network");
+                let mut proxy_changed = false;
+                for (label, value) in [
+                    ("HTTP proxy:", &mut self.config.http_proxy),
+                    ("HTTPS proxy:", &mut self.config.https_proxy),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        let mut url = value.clone().unwrap_or_default();
+                        if ui
+                            .add(egui::TextEdit::singleline(&mut url).hint_text("From environment"))
+                            .on_hover_text("Applies to new connections")
+                            .changed()
+                        {
+                            *value = (!url.is_empty()).then_some(url);
+                            proxy_changed = true;
+                        }
+                    });
+                }
+                if proxy_changed {
+                    config_changed = true;
+                    self.proxy_error = proxy::configure(
+                        self.config.http_proxy.as_deref(),
+                        self.config.https_proxy.as_deref(),
+                    )
+                    .err()
+                    .map(|e| e.to_string());
+                }
+                if let Some(error) = &self.proxy_error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.heading("Webhook");
+                ui.horizontal(|ui| {
+                    ui.label("POST chat to:");
+                    let mut url = self.config.webhook.url.clone().unwrap_or_default();
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut url)
+                                .hint_text("http://localhost:8080/chat"),
+                        )
+                        .changed()
+                    {
+                        self.config.webhook.url = (!url.is_empty()).then_some(url);
+                        config_changed = true;
+                    }
+                });
+                if let Some(url) = &self.config.webhook.url {
+                    let valid =
+                        url::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https"));
+                    if !valid {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            "Enter an http:// or https:// URL",
+                        );
+                    }
+                }
+                ui.horizontal(|ui| {
+                    config_changed |= ui
+                        .checkbox(&mut self.config.webhook.forward_chat, "Chat messages")
+                        .changed();
+                    config_changed |= ui
+                        .checkbox(&mut self.config.webhook.forward_notices, "Notices")
+                        .changed();
+                    config_changed |= ui
+                        .checkbox(&mut self.config.webhook.forward_deletions, "Deletions")
+                        .changed();
+                });
+                config_changed |= ui
+                    .add(
+                        egui::Slider::new(&mut self.config.webhook.max_per_second, 1..=100)
+                            .text("Max Events per Second"),
+                    )
+                    .on_hover_text("Events over the limit are dropped")
+                    .changed();
+
+                ui.heading("Developer");
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.developer_tools,
+                        "Keep raw events for \"Show raw event\"",
+                    )
+                    .on_hover_text("Takes effect the next time a channel is joined")
+                    .changed();
+                scopes_changed |= ui
+                    .checkbox(
+                        &mut self.config.minimal_scopes,
+                        "Only request chat permissions",
+                    )
+                    .on_hover_text("Moderation and channel features won't work")
+                    .changed();
+                ui.label("Extra scopes (one per line):");
+                let mut extra_scopes = self.config.extra_scopes.join("\n");
+                if ui
+                    .add(
+                        egui::TextEdit::multiline(&mut extra_scopes)
+                            .desired_rows(2)
+                            .hint_text("moderator:read:followers"),
+                    )
+                    .changed()
+                {
+                    self.config.extra_scopes =
+                        extra_scopes.split('\n').map(str::to_string).collect();
+                    scopes_changed = true;
+                }
+                for name in self
+                    .config
+                    .extra_scopes
+                    .iter()
+                    .filter(|s| !s.trim().is_empty())
+                {
+                    if let Err(e) = auth::parse_scope(name) {
+                        ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                    }
+                }
+                config_changed |= scopes_changed;
+
+                ui.heading("Ignored Users");
+                if self.config.ignored_users.is_empty() {
+                    ui.label(RichText::new("Right-click a message to ignore its sender.").weak());
+                }
+                let mut unignore = None;
+                for (i, name) in self.config.ignored_users.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text("Stop ignoring")
+                            .clicked()
+                        {
+                            unignore = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = unignore {
+                    self.config.ignored_users.remove(i);
+                    config_changed = true;
+                }
+
+                ui.heading("Moderation");
+                config_changed |= ui
+                    .checkbox(
+                        &mut self.config.keep_deleted_messages,
+                        "Keep deleted messages visible (struck through)",
+                    )
+                    .changed();
+
+                if config_changed {
+                    self.config_saver.save(self.config.clone());
+                }
+            });
+
+        if restart_status_line {
+            self.restart_status_line_task();
+        }
+        if fetch_twitch_emotes {
+            self.fetch_global_emotes();
+        }
+        if scopes_changed
+            && let AppState::LoggedIn {
+                last_error,
+                reauth_required,
+                ..
+            } = &mut self.state
+        {
+            // The current token was granted the old scopes.
+            *reauth_required = true;
+            *last_error = Some("Scope settings changed. Log in again to apply them.".to_string());
+        }
+    }
+
+    /// Fetches global Twitch emotes if they weren't loaded at login, e.g. because
+    /// they were disabled at the time.
+    fn fetch_global_emotes(&mut self) {
+        if let AppState::LoggedIn {
+            token,
+            global_emotes,
+            global_emotes_loaded,
+            ..
+        } = &mut self.state
+        {
+            let Some(client_id) = self.config.client_id.clone() else {
+                return;
+            };
+            if !global_emotes.is_empty() || self.mock_rate.is_some() {
+                return;
+            }
+            *global_emotes_loaded = false;
+            actions::perform(
+                Action::FetchGlobalEmotes {
+                    client_id,
+                    token: token.clone(),
+                },
+                &self.event_tx,
+                &self.config_saver,
+            );
+        }
+    }
 
     fn handle_profile_action(&mut self, action: profiles::ProfileManagerAction) {
         match action {
@@ -691,35 +2139,81 @@ impl App {
                     self.config.profiles.push(Profile {
                         name,
                         twitch_user_id: None,
+                        open_channels: Vec::new(),
+                        appearance: Default::default(),
                     });
-                    let config_to_save = self.config.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = config::save(&config_to_save).await {
-                            tracing::error!("Failed to save config: {}", e);
-                        }
-                    });
+                    self.config_saver.save(self.config.clone());
                 } else {
                     self.profile_manager_error =
                         Some("A profile with this name already exists.".to_string());
                 }
             }
+            profiles::ProfileManagerAction::AddIdentity(name) => {
+                self.profile_manager_error = None;
+                self.load_identity(name);
+            }
+            profiles::ProfileManagerAction::RemoveIdentity(name) => {
+                if let AppState::LoggedIn {
+                    identities,
+                    send_as,
+                    ..
+                } = &mut self.state
+                {
+                    identities.retain(|i| i.profile_name != name);
+                    if send_as.as_ref() == Some(&name) {
+                        *send_as = None;
+                    }
+                }
+            }
             profiles::ProfileManagerAction::Remove(name) => {
                 self.config.profiles.retain(|p| p.name != name);
                 if self.config.active_profile_name.as_ref() == Some(&name) {
                     self.config.active_profile_name = None;
                 }
-                let config_to_save = self.config.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = config::save(&config_to_save).await {
-                        tracing::error!("Failed to save config: {}", e);
-                    }
-                });
+                self.config_saver.save(self.config.clone());
             }
         }
     }
 
+    /// Loads another profile's saved token so messages can be sent as it. Only saved
+    /// tokens are used; an interactive login would replace the primary session.
+    fn load_identity(&mut self, profile_name: String) {
+        let (Some(client_id), Some(client_secret)) = (
+            self.config.client_id.clone(),
+            self.config.client_secret.clone(),
+        ) else {
+            self.profile_manager_error = Some("Client ID or Secret not configured".to_string());
+            return;
+        };
+        let tx = self.event_tx.clone();
+        let scopes = self.config.requested_scopes();
+        tokio::spawn(async move {
+            let result = match AuthClient::new(
+                client_id,
+                client_secret,
+                tx.clone(),
+                Some(profile_name.clone()),
+                scopes,
+            )
+            .await
+            {
+                Ok(auth_client) => auth_client.try_silent_login().await,
+                Err(e) => Err(e),
+            };
+            tx.send(AppEvent::IdentityLoaded(result, profile_name))
+                .await
+                .ok();
+        });
+    }
+
     fn draw_profile_manager_window(&mut self, ctx: &egui::Context) {
         let mut is_open = self.show_profile_manager;
+        let identity_profiles: Option<Vec<String>> = match &self.state {
+            AppState::LoggedIn { identities, .. } => {
+                Some(identities.iter().map(|i| i.profile_name.clone()).collect())
+            }
+            _ => None,
+        };
         egui::Window::new("Profile Manager")
             .open(&mut is_open)
             .show(ctx, |ui| {
@@ -730,6 +2224,7 @@ impl App {
                     &mut self.new_profile_name,
                     &mut self.profile_to_remove_name,
                     &self.profile_manager_error,
+                    identity_profiles.as_deref(),
                 ) {
                     self.handle_profile_action(action);
                 }
@@ -738,69 +2233,892 @@ impl App {
     }
 
     fn send_message(&mut self, is_announcement: bool) {
+        let AppState::LoggedIn {
+            message_to_send,
+            last_error,
+            ..
+        } = &mut self.state
+        else {
+            return;
+        };
+        if self.config.send_lines_separately {
+            let lines = split_message_lines(message_to_send);
+            if lines.len() > 1 {
+                self.send_lines(lines, is_announcement);
+                return;
+            }
+        }
+        *message_to_send = sanitize_input(message_to_send);
+        if message_to_send.trim().is_empty() {
+            message_to_send.clear();
+            return;
+        }
+
+        let (text, is_announcement) = match parse_command(message_to_send) {
+            ChatCommand::Message(text) => (text, is_announcement),
+            ChatCommand::Me(text) => (me_message(&text), false),
+            ChatCommand::Announce(text) => (text, true),
+            ChatCommand::Ban { user, reason } => {
+                message_to_send.clear();
+                self.ban_user(user, None, None, reason);
+                return;
+            }
+            ChatCommand::Timeout {
+                user,
+                duration_secs,
+                reason,
+            } => {
+                message_to_send.clear();
+                self.ban_user(user, None, Some(duration_secs), reason);
+                return;
+            }
+            ChatCommand::Clear => {
+                message_to_send.clear();
+                self.clear_chat();
+                return;
+            }
+            ChatCommand::Raid(target) => {
+                message_to_send.clear();
+                self.start_raid(target);
+                return;
+            }
+            ChatCommand::Unraid => {
+                message_to_send.clear();
+                self.cancel_raid();
+                return;
+            }
+            ChatCommand::Whisper { recipient, message } => {
+                self.send_whisper(recipient, message);
+                return;
+            }
+            ChatCommand::Invalid(error) => {
+                *last_error = Some(error);
+                return;
+            }
+        };
+        self.confirm_and_dispatch(vec![text], is_announcement);
+    }
+
+    /// Sends each line as its own chat message, in order. Of the commands, only
+    /// `/me` works on a line among others.
+    fn send_lines(&mut self, lines: Vec<String>, is_announcement: bool) {
+        let mut messages = Vec::with_capacity(lines.len());
+        for line in lines {
+            match parse_command(&line) {
+                ChatCommand::Message(text) => messages.push(text),
+                ChatCommand::Me(text) => messages.push(me_message(&text)),
+                _ => {
+                    if let AppState::LoggedIn { last_error, .. } = &mut self.state {
+                        *last_error =
+                            Some("Commands can't be sent along with other lines".to_string());
+                    }
+                    return;
+                }
+            }
+        }
+        self.confirm_and_dispatch(messages, is_announcement);
+    }
+
+    fn confirm_and_dispatch(&mut self, messages: Vec<String>, is_announcement: bool) {
+        if self.config.confirm_links
+            && messages
+                .iter()
+                .any(|text| contains_url(text, self.config.autolink_urls))
+        {
+            self.pending_link_confirmation = Some((messages, is_announcement));
+            return;
+        }
+        self.dispatch_message(messages, is_announcement);
+    }
+
+    fn draw_raw_event_window(&mut self, ctx: &egui::Context) {
+        let Some(json) = &self.raw_event_view else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Raw Event")
+            .open(&mut open)
+            .default_size([480.0, 360.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::both().show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut json.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
+        if !open {
+            self.raw_event_view = None;
+        }
+    }
+
+    fn draw_session_expired(&mut self, ctx: &egui::Context) {
+        let AppState::LoggedIn {
+            session_expired: true,
+            ..
+        } = &self.state
+        else {
+            return;
+        };
+        let mut decision = None;
+        egui::Modal::new(egui::Id::new("session_expired_modal")).show(ctx, |ui| {
+            ui.heading("Session expired");
+            ui.label("Your Twitch session expired. Please log in again to keep chatting.");
+            ui.horizontal(|ui| {
+                if ui.button("Log In Again").clicked() {
+                    decision = Some(true);
+                }
+                if ui.button("Later").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                    decision = Some(false);
+                }
+            });
+        });
+
+        match decision {
+            Some(true) => self.reauthenticate(),
+            Some(false) => {
+                if let AppState::LoggedIn {
+                    session_expired, ..
+                } = &mut self.state
+                {
+                    *session_expired = false;
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn draw_link_confirmation(&mut self, ctx: &egui::Context) {
+        let Some((messages, is_announcement)) = self.pending_link_confirmation.clone() else {
+            return;
+        };
+        let mut decision = None;
+        egui::Modal::new(egui::Id::new("link_confirmation_modal")).show(ctx, |ui| {
+            ui.heading("Send a link?");
+            ui.label("This message contains a link. Send it anyway?");
+            for text in &messages {
+                ui.label(RichText::new(text.as_str()).italics());
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Send").clicked() {
+                    decision = Some(true);
+                }
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                    decision = Some(false);
+                }
+            });
+        });
+
+        match decision {
+            Some(true) => {
+                self.pending_link_confirmation = None;
+                self.dispatch_message(messages, is_announcement);
+            }
+            Some(false) => self.pending_link_confirmation = None,
+            None => {}
+        }
+    }
+
+    fn draw_link_open_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(url) = &self.pending_link_open else {
+            return;
+        };
+        let mut decision = None;
+        egui::Modal::new(egui::Id::new("link_open_modal")).show(ctx, |ui| {
+            ui.heading("Open link?");
+            ui.label("This link leads to:");
+            ui.label(RichText::new(url.as_str()).monospace());
+            if url.to_ascii_lowercase().starts_with("http://") {
+                ui.colored_label(
+                    egui::Color32::from_rgb(230, 120, 60),
+                    "⚠ This link is not secure (http).",
+                );
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Open").clicked() {
+                    decision = Some(true);
+                }
+                if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                    decision = Some(false);
+                }
+            });
+        });
+
+        match decision {
+            Some(true) => {
+                if let Some(url) = self.pending_link_open.take() {
+                    open_in_browser(&url);
+                }
+            }
+            Some(false) => self.pending_link_open = None,
+            None => {}
+        }
+    }
+
+    /// Shows `channel`'s tab, joining it in a new one if it isn't open yet. Joining a
+    /// channel whose connection was lost connects it again.
+    fn join_channel(&mut self, channel: String) {
+        let channel = channel.trim().trim_start_matches('#').to_lowercase();
+        if channel.is_empty() {
+            return;
+        }
+        let opened = self.state.open_tab(&channel);
+        self.remember_channel(&channel);
+
         if let AppState::LoggedIn {
-            current_channel,
-            send_in_progress,
+            tabs,
+            active_tab,
             last_error,
             token,
             user_id,
             chat_client,
+            ..
+        } = &mut self.state
+        {
+            let Some(tab) = tabs.get_mut(*active_tab) else {
+                return;
+            };
+            if opened
+                && let Some(history) = ChatHistory::from_data_dir(self.config.chat_history_lines)
+            {
+                match history.load_tail(&channel) {
+                    Ok(messages) => tab.chat_messages.extend(messages),
+                    Err(e) => {
+                        tracing::warn!("Failed to load chat history for {}: {}", channel, e)
+                    }
+                }
+            }
+            if self.mock_rate.is_some() {
+                // The synthetic feed doesn't care which channel is shown.
+                return;
+            }
+            if tab
+                .eventsub_task
+                .as_ref()
+                .is_some_and(|task| !task.is_finished())
+            {
+                return;
+            }
+
+            tab.reconnecting = None;
+            tab.connection_status = ConnectionStatus::Connecting;
+            *last_error = None;
+            let tx = self.event_tx.clone();
+            let token = token.clone();
+            let user_id = user_id.clone();
+            let chat_client = chat_client.clone();
+            let subscribe_redemptions = self.config.redemption_queue_enabled;
+            let forward_raw_events = self.config.developer_tools;
+            let mut emote_registry = EmoteRegistry::new(self.config.emote_precedence);
+            let bttv = self.config.enable_bttv.then(|| self.bttv.clone());
+            let ffz = self.config.enable_ffz.then(|| self.ffz.clone());
+            let seventv = self.config.enable_7tv.then(|| self.seventv.clone());
+            tab.eventsub_task = Some(tokio::spawn(async move {
+                match chat_client.get_user_id(&channel, &token).await {
+                    Ok(Some(id)) => {
+                        let _ = tx
+                            .send(AppEvent::Chat(ChatEvent::ChannelJoined {
+                                channel: channel.clone(),
+                                broadcaster_id: id.to_string(),
+                            }))
+                            .await;
+                        let third_party_emotes = emotes::third_party_emotes(
+                            bttv.as_deref(),
+                            ffz.as_deref(),
+                            seventv.as_deref(),
+                            id.as_str(),
+                        )
+                        .await;
+                        let _ = tx
+                            .send(AppEvent::ThirdPartyEmotesLoaded {
+                                channel: channel.clone(),
+                                emotes: third_party_emotes.clone(),
+                            })
+                            .await;
+                        emote_registry.extend(third_party_emotes);
+                        let poll_chatters = chatters::run(
+                            chat_client,
+                            id.clone(),
+                            user_id.clone(),
+                            token.clone(),
+                            tx.clone(),
+                        );
+                        // Giving up on the chatter list mustn't end the chat connection.
+                        let poll_chatters = async {
+                            poll_chatters.await;
+                            std::future::pending::<()>().await
+                        };
+                        let eventsub_client = EventSubClient::new(
+                            user_id.clone(),
+                            token.clone(),
+                            tx,
+                            id,
+                            subscribe_redemptions,
+                            forward_raw_events,
+                            emote_registry,
+                        );
+                        tokio::select! {
+                            result = eventsub_client.run() => {
+                                if let Err(e) = result {
+                                    tracing::error!("EventSub client failed: {}", e);
+                                }
+                            }
+                            _ = poll_chatters => {}
+                        }
+                    }
+                    Ok(None) => {
+                        let _ = tx
+                            .send(AppEvent::Chat(ChatEvent::ChannelNotFound(channel)))
+                            .await;
+                    }
+                    Err(e) => {
+                        let _ = tx
+                            .send(AppEvent::Chat(ChatEvent::ChannelLookupFailed {
+                                channel,
+                                error: e.to_string(),
+                            }))
+                            .await;
+                        if is_unauthorized(&e) {
+                            let _ = tx.send(AppEvent::Chat(ChatEvent::SessionExpired)).await;
+                        }
+                    }
+                }
+            }));
+        }
+    }
+
+    /// Moves `channel` to the front of the recently joined list used by the switcher
+    /// and records the open tabs as the profile's channels for the next launch.
+    fn remember_channel(&mut self, channel: &str) {
+        let recent = &mut self.config.recent_channels;
+        recent.retain(|c| c != channel);
+        recent.insert(0, channel.to_string());
+        recent.truncate(config::MAX_RECENT_CHANNELS);
+        self.save_open_channels();
+    }
+
+    fn save_open_channels(&mut self) {
+        if let (AppState::LoggedIn { tabs, .. }, Some(profile)) =
+            (&self.state, self.config.get_active_profile_mut())
+        {
+            profile.open_channels = tabs.iter().map(|tab| tab.channel.clone()).collect();
+        }
+        self.config_saver.save(self.config.clone());
+    }
+
+    /// Leaves the channel in the tab at `index`.
+    fn close_tab(&mut self, index: usize) {
+        if let Some(tab) = self.state.close_tab(index) {
+            tracing::info!("Left #{}", tab.channel);
+            self.save_open_channels();
+        }
+    }
+
+    /// Skips authentication entirely and starts feeding synthetic chat into a fake channel.
+    fn start_mock_session(&mut self) {
+        let rate = self.mock_rate.unwrap_or(mock::DEFAULT_RATE);
+        let mut state = AppState::logged_in_with(
+            Arc::new(mock::mock_token()),
+            Arc::new(mock::MockChatApi::new()),
+        );
+        if let AppState::LoggedIn {
+            tabs,
+            global_emotes_loaded,
+            ..
+        } = &mut state
+        {
+            // There's nothing to fetch emotes from; the feed links Twitch emotes directly.
+            *global_emotes_loaded = true;
+            let mut tab = ChannelTab::new(mock::MOCK_CHANNEL.to_string());
+            tab.eventsub_task = Some(tokio::spawn(mock::run_mock_chat(
+                self.event_tx.clone(),
+                rate,
+            )));
+            tabs.push(tab);
+        }
+        tracing::info!("Running in mock mode at {} messages/s", rate);
+        self.state = state;
+    }
+
+    /// Sends `messages` one after another, as paced by the rate limit. Only the first
+    /// one is a reply. Sending stops at the first that fails, and the input stays as
+    /// typed until the send succeeds, so it can be retried.
+    fn dispatch_message(&mut self, messages: Vec<String>, is_announcement: bool) {
+        let current_channel = self.state.current_channel().map(str::to_string);
+        // Announcements can't be replies.
+        let reply_parent_id = self
+            .state
+            .active_tab()
+            .and_then(|tab| tab.replying_to.as_ref())
+            .filter(|_| !is_announcement)
+            .map(|reply| reply.parent_id.clone());
+        let per_window = if self.state.active_tab().is_some_and(|tab| tab.is_moderator) {
+            MODERATOR_MESSAGES_PER_WINDOW
+        } else {
+            USER_MESSAGES_PER_WINDOW
+        };
+        if let AppState::LoggedIn {
+            send_in_progress,
+            message_queue,
+            send_queued_until,
+            last_error,
+            token,
+            chat_client,
             message_to_send,
+            unsent_message,
+            identities,
+            send_as,
             ..
         } = &mut self.state
         {
-            if let Some(channel) = current_channel.clone() {
+            if let Some(channel) = current_channel {
+                let now = Instant::now();
+                // Reserved on a copy, so a batch that's refused doesn't use up the limit.
+                let mut queue = message_queue.clone();
+                let delays: Result<Vec<Duration>, Duration> = messages
+                    .iter()
+                    .map(|_| queue.reserve(now, per_window))
+                    .collect();
+                let delays = match delays {
+                    Ok(delays) => delays,
+                    Err(wait) => {
+                        *last_error = Some(format!(
+                            "Sending messages too fast, try again in {} seconds",
+                            wait.as_secs().max(1)
+                        ));
+                        return;
+                    }
+                };
+                *message_queue = queue;
+                let first_delay = delays.first().copied().unwrap_or_default();
                 *send_in_progress = true;
+                *send_queued_until = Some(now + first_delay).filter(|_| !first_delay.is_zero());
                 *last_error = None;
-                let token = token.clone();
-                let user_id = user_id.clone();
-                let chat_client = chat_client.clone();
+                *unsent_message = Some(message_to_send.clone());
                 let tx = self.event_tx.clone();
-                let message = message_to_send.clone();
+                let token = send_as
+                    .as_ref()
+                    .and_then(|name| identities.iter().find(|i| &i.profile_name == name))
+                    .map(|identity| identity.token.clone())
+                    .unwrap_or_else(|| token.clone());
+                let chat_client = chat_client.clone();
+                let send = ChatSend {
+                    channel,
+                    messages: messages.into_iter().zip(delays).collect(),
+                    start: now,
+                    reply_parent_id,
+                    is_announcement,
+                };
                 tokio::spawn(async move {
-                    let broadcaster_id = match chat_client.get_user_id(&channel, &token).await {
-                        Ok(Some(id)) => id,
-                        _ => {
-                            let _ = tx
-                                .send(AppEvent::Chat(ChatEvent::MessageSendError(
-                                    "Channel not found".to_string(),
-                                )))
-                                .await;
-                            return;
-                        }
-                    };
-                    let result = if is_announcement {
-                        chat_client
-                            .send_announcement(
-                                broadcaster_id.as_ref(),
-                                user_id.as_ref(),
-                                &message,
-                                Some(AnnouncementColor::Primary),
-                                &token,
-                            )
-                            .await
-                    } else {
-                        chat_client
-                            .send_chat_message(
-                                broadcaster_id.as_ref(),
-                                user_id.as_ref(),
-                                &message,
-                                &token,
-                            )
-                            .await
-                    };
-                    let _ = match result {
-                        Ok(_) => tx.send(AppEvent::Chat(ChatEvent::MessageSent)).await,
-                        Err(e) => {
-                            tx.send(AppEvent::Chat(ChatEvent::MessageSendError(format!(
-                                "Failed to send: {}",
+                    send::send_chat(chat_client.as_ref(), &token, send, &tx).await;
+                });
+            }
+        }
+    }
+
+    /// Whispers as whichever account chat messages are currently sent as. The input
+    /// is kept until Twitch accepts the whisper, like a chat message.
+    fn send_whisper(&mut self, recipient: String, message: String) {
+        if let AppState::LoggedIn {
+            send_in_progress,
+            last_error,
+            token,
+            chat_client,
+            message_to_send,
+            unsent_message,
+            identities,
+            send_as,
+            ..
+        } = &mut self.state
+        {
+            if let Err(e) = check_whisper_length(&message) {
+                *last_error = Some(e.to_string());
+                return;
+            }
+            *send_in_progress = true;
+            *last_error = None;
+            *unsent_message = Some(message_to_send.clone());
+            let tx = self.event_tx.clone();
+            let token = send_as
+                .as_ref()
+                .and_then(|name| identities.iter().find(|i| &i.profile_name == name))
+                .map(|identity| identity.token.clone())
+                .unwrap_or_else(|| token.clone());
+            let chat_client = chat_client.clone();
+            tokio::spawn(async move {
+                send::send_whisper(chat_client.as_ref(), &token, &recipient, &message, &tx).await;
+            });
+        }
+    }
+
+    fn start_raid(&mut self, target: String) {
+        let current_channel = self.state.current_channel().map(str::to_string);
+        if let AppState::LoggedIn {
+            token,
+            user_id,
+            user_login,
+            chat_client,
+            last_error,
+            ..
+        } = &mut self.state
+        {
+            let is_broadcaster = current_channel
+                .as_deref()
+                .is_some_and(|channel| channel.eq_ignore_ascii_case(user_login));
+            if !is_broadcaster {
+                *last_error = Some("Only the broadcaster can start a raid.".to_string());
+                return;
+            }
+            *last_error = None;
+            let token = token.clone();
+            let user_id = user_id.clone();
+            let chat_client = chat_client.clone();
+            let tx = self.event_tx.clone();
+            tokio::spawn(async move {
+                let target_id = match chat_client.get_user_id(&target, &token).await {
+                    Ok(Some(id)) => id,
+                    Ok(None) => {
+                        let _ = tx
+                            .send(AppEvent::Chat(ChatEvent::RaidError(format!(
+                                "Channel '{}' not found",
+                                target
+                            ))))
+                            .await;
+                        return;
+                    }
+                    Err(e) => {
+                        let event = ChatEvent::from_api_error(&e, |e| {
+                            ChatEvent::RaidError(format!("Failed to look up '{}': {}", target, e))
+                        });
+                        let _ = tx.send(AppEvent::Chat(event)).await;
+                        return;
+                    }
+                };
+                let event = match chat_client
+                    .start_raid(user_id.as_ref(), target_id.as_ref(), &token)
+                    .await
+                {
+                    Ok(()) => ChatEvent::RaidStarted(target),
+                    // Twitch explains offline targets and channels that don't accept
+                    // raids in the error message, so pass it through as-is.
+                    Err(e) => ChatEvent::from_api_error(&e, |e| {
+                        ChatEvent::RaidError(format!("Failed to start raid: {}", e))
+                    }),
+                };
+                let _ = tx.send(AppEvent::Chat(event)).await;
+            });
+        }
+    }
+
+    fn resolve_redemption(&mut self, action: RedemptionAction) {
+        if let AppState::LoggedIn {
+            token,
+            user_id,
+            chat_client,
+            ..
+        } = &mut self.state
+        {
+            let (redemption, fulfilled) = match action {
+                RedemptionAction::Fulfill(redemption) => (redemption, true),
+                RedemptionAction::Reject(redemption) => (redemption, false),
+            };
+            let token = token.clone();
+            let user_id = user_id.clone();
+            let chat_client = chat_client.clone();
+            let tx = self.event_tx.clone();
+            tokio::spawn(async move {
+                let result = chat_client
+                    .update_redemption_status(
+                        user_id.as_ref(),
+                        &redemption.reward_id,
+                        &redemption.id,
+                        fulfilled,
+                        &token,
+                    )
+                    .await;
+                let event = match result {
+                    Ok(()) => ChatEvent::RedemptionResolved(redemption.id),
+                    Err(e) => ChatEvent::from_api_error(&e, |e| {
+                        ChatEvent::RedemptionError(format!(
+                            "Failed to update redemption '{}': {}",
+                            redemption.reward_title, e
+                        ))
+                    }),
+                };
+                let _ = tx.send(AppEvent::Chat(event)).await;
+            });
+        }
+    }
+
+    fn resolve_held_message(&mut self, action: HeldMessageAction) {
+        if let AppState::LoggedIn {
+            token,
+            user_id,
+            chat_client,
+            ..
+        } = &mut self.state
+        {
+            let (message, allow) = match action {
+                HeldMessageAction::Allow(message) => (message, true),
+                HeldMessageAction::Deny(message) => (message, false),
+            };
+            let token = token.clone();
+            let user_id = user_id.clone();
+            let chat_client = chat_client.clone();
+            let tx = self.event_tx.clone();
+            tokio::spawn(async move {
+                let result = chat_client
+                    .resolve_held_message(user_id.as_ref(), &message.id, allow, &token)
+                    .await;
+                let event = match result {
+                    Ok(()) => ChatEvent::HeldMessageResolved(message.id),
+                    Err(e) => ChatEvent::from_api_error(&e, |e| {
+                        ChatEvent::HeldMessageError(format!(
+                            "Failed to resolve {}'s held message: {}",
+                            message.user_name, e
+                        ))
+                    }),
+                };
+                let _ = tx.send(AppEvent::Chat(event)).await;
+            });
+        }
+    }
+
+    fn handle_message_action(&mut self, action: MessageAction) {
+        if let MessageAction::OpenLink(url) = action {
+            if self.config.confirm_link_clicks {
+                self.pending_link_open = Some(url);
+            } else {
+                open_in_browser(&url);
+            }
+            return;
+        }
+        let Some(tab) = self.state.active_tab_mut() else {
+            return;
+        };
+        match action {
+            MessageAction::ShowRawEvent(message_id) => {
+                // Suspicious-user messages arrive twice, so show every event for the id.
+                let json: Vec<&str> = tab
+                    .raw_events
+                    .iter()
+                    .filter(|(id, _)| *id == message_id)
+                    .map(|(_, json)| json.as_str())
+                    .collect();
+                self.raw_event_view = Some(json.join("\n\n"));
+            }
+            // Links were opened above, and the chat log applies mention filters itself.
+            MessageAction::OpenLink(_) | MessageAction::FilterUser(_) => {}
+            MessageAction::Reply(reply) => {
+                tab.replying_to = Some(reply);
+            }
+            MessageAction::IgnoreUser(user_name) => {
+                if !self.config.is_ignored(&user_name) {
+                    self.config.ignored_users.push(user_name.to_lowercase());
+                    self.config_saver.save(self.config.clone());
+                }
+            }
+            MessageAction::HideLowTrustWarning(message_id) => {
+                for message in tab.chat_messages.iter_mut().filter(|m| m.id == message_id) {
+                    message.low_trust = None;
+                }
+            }
+            MessageAction::Ban {
+                user_id: target_id,
+                user_name,
+                duration_secs,
+                reason,
+            } => {
+                self.ban_user(user_name, Some(target_id), duration_secs, reason);
+            }
+        }
+    }
+
+    /// Bans `user_name` from the current channel, or times them out for
+    /// `duration_secs`. Their id, and the channel's, are only looked up when not
+    /// already known.
+    fn ban_user(
+        &mut self,
+        user_name: String,
+        target_id: Option<String>,
+        duration_secs: Option<u32>,
+        reason: String,
+    ) {
+        let AppState::LoggedIn {
+            token,
+            user_id,
+            chat_client,
+            ..
+        } = &self.state
+        else {
+            return;
+        };
+        let Some(tab) = self.state.active_tab() else {
+            return;
+        };
+        let channel = tab.channel.clone();
+        let known_broadcaster_id = tab.broadcaster_id.clone();
+        let token = token.clone();
+        let moderator_id = user_id.clone();
+        let chat_client = chat_client.clone();
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let broadcaster_id = match known_broadcaster_id {
+                Some(id) => UserId::from(id),
+                None => match chat_client.get_user_id(&channel, &token).await {
+                    Ok(Some(id)) => id,
+                    Ok(None) => {
+                        let _ = tx
+                            .send(AppEvent::Chat(ChatEvent::ModerationError(
+                                "Channel not found".to_string(),
+                            )))
+                            .await;
+                        return;
+                    }
+                    Err(e) => {
+                        let event = ChatEvent::from_api_error(&e, |e| {
+                            ChatEvent::ModerationError(format!(
+                                "Failed to look up the channel: {}",
                                 e
+                            ))
+                        });
+                        let _ = tx.send(AppEvent::Chat(event)).await;
+                        return;
+                    }
+                },
+            };
+            let target_id = match target_id {
+                Some(id) => UserId::from(id),
+                None => match chat_client.get_user_id(&user_name, &token).await {
+                    Ok(Some(id)) => id,
+                    Ok(None) => {
+                        let _ = tx
+                            .send(AppEvent::Chat(ChatEvent::ModerationError(format!(
+                                "User '{}' not found",
+                                user_name
                             ))))
-                            .await
-                        }
-                    };
+                            .await;
+                        return;
+                    }
+                    Err(e) => {
+                        let event = ChatEvent::from_api_error(&e, |e| {
+                            ChatEvent::ModerationError(format!(
+                                "Failed to look up '{}': {}",
+                                user_name, e
+                            ))
+                        });
+                        let _ = tx.send(AppEvent::Chat(event)).await;
+                        return;
+                    }
+                },
+            };
+            let verb = if duration_secs.is_some() {
+                "time out"
+            } else {
+                "ban"
+            };
+            let event = match chat_client
+                .ban_user(
+                    broadcaster_id.as_ref(),
+                    moderator_id.as_ref(),
+                    target_id.as_ref(),
+                    &reason,
+                    duration_secs,
+                    &token,
+                )
+                .await
+            {
+                Ok(()) => ChatEvent::UserBanned(user_name),
+                Err(e) => ChatEvent::from_api_error(&e, |e| {
+                    ChatEvent::ModerationError(format!("Failed to {} {}: {}", verb, user_name, e))
+                }),
+            };
+            let _ = tx.send(AppEvent::Chat(event)).await;
+        });
+    }
+
+    /// Deletes every message in the current channel. The log itself is emptied once
+    /// Twitch reports the clear back.
+    fn clear_chat(&mut self) {
+        let AppState::LoggedIn {
+            token,
+            user_id,
+            chat_client,
+            ..
+        } = &self.state
+        else {
+            return;
+        };
+        let Some(channel) = self.state.current_channel().map(str::to_string) else {
+            return;
+        };
+        let token = token.clone();
+        let moderator_id = user_id.clone();
+        let chat_client = chat_client.clone();
+        let tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            let result = match chat_client.get_user_id(&channel, &token).await {
+                Ok(Some(broadcaster_id)) => {
+                    chat_client
+                        .clear_chat(broadcaster_id.as_ref(), moderator_id.as_ref(), &token)
+                        .await
+                }
+                Ok(None) => Err(eyre!("Channel not found")),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                let event = ChatEvent::from_api_error(&e, |e| {
+                    ChatEvent::ModerationError(format!("Failed to clear chat: {}", e))
                 });
+                let _ = tx.send(AppEvent::Chat(event)).await;
             }
+        });
+    }
+
+    fn cancel_raid(&mut self) {
+        if let AppState::LoggedIn {
+            token,
+            user_id,
+            chat_client,
+            ..
+        } = &mut self.state
+        {
+            let token = token.clone();
+            let user_id = user_id.clone();
+            let chat_client = chat_client.clone();
+            let tx = self.event_tx.clone();
+            tokio::spawn(async move {
+                let event = match chat_client.cancel_raid(user_id.as_ref(), &token).await {
+                    Ok(()) => ChatEvent::RaidCancelled,
+                    Err(e) => ChatEvent::from_api_error(&e, |e| {
+                        ChatEvent::RaidCancelError(format!("Failed to cancel raid: {}", e))
+                    }),
+                };
+                let _ = tx.send(AppEvent::Chat(event)).await;
+            });
         }
     }
 }
+
+/// A dot colored by how the shown channel's chat connection is doing, and a label.
+fn draw_connection_status(ui: &mut egui::Ui, status: ConnectionStatus) {
+    let (color, label) = match status {
+        ConnectionStatus::Connected => (egui::Color32::from_rgb(80, 200, 120), "Connected"),
+        ConnectionStatus::Connecting => (egui::Color32::from_rgb(230, 190, 60), "Connecting…"),
+        ConnectionStatus::Disconnected => (egui::Color32::from_rgb(220, 70, 70), "Disconnected"),
+    };
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(10.0), egui::Sense::hover());
+    ui.painter().circle_filled(rect.center(), 4.0, color);
+    ui.label(label);
+}
+
+fn open_channel_in_browser(channel: &str) {
+    open_in_browser(&format!("https://twitch.tv/{}", channel));
+}
+
+fn open_in_browser(url: &str) {
+    if let Err(e) = webbrowser::open(url) {
+        tracing::error!("Failed to open {} in browser: {}", url, e);
+    }
+}
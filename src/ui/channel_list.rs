@@ -0,0 +1,52 @@
+use crate::app::state::ChannelTab;
+use eframe::egui::{self, RichText, ScrollArea};
+
+/// What the user did in the tab strip.
+pub enum TabAction {
+    Select(usize),
+    Close(usize),
+}
+
+/// Draws a tab per joined channel, with its unread count and a close button. Tabs
+/// whose chat connection is down are drawn in the warning color.
+pub fn draw_channel_tabs(
+    ui: &mut egui::Ui,
+    tabs: &[ChannelTab],
+    active_tab: usize,
+) -> Option<TabAction> {
+    let mut action = None;
+    ScrollArea::horizontal()
+        .id_salt("channel_tabs_scroll_area")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for (index, tab) in tabs.iter().enumerate() {
+                    let label = if tab.unread > 0 {
+                        format!("#{} ({})", tab.channel, tab.unread)
+                    } else {
+                        format!("#{}", tab.channel)
+                    };
+                    let disconnected = tab.reconnecting.is_some()
+                        || tab
+                            .eventsub_task
+                            .as_ref()
+                            .is_some_and(|task| task.is_finished());
+                    let mut text = RichText::new(label);
+                    if disconnected {
+                        text = text.color(ui.visuals().warn_fg_color);
+                    }
+                    if ui.selectable_label(index == active_tab, text).clicked() {
+                        action = Some(TabAction::Select(index));
+                    }
+                    if ui
+                        .small_button("✖")
+                        .on_hover_text("Leave channel")
+                        .clicked()
+                    {
+                        action = Some(TabAction::Close(index));
+                    }
+                    ui.separator();
+                }
+            });
+        });
+    action
+}
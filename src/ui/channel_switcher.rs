@@ -0,0 +1,114 @@
+use crate::utils::fuzzy;
+use eframe::egui::{self, Align2, Frame, Key, RichText};
+
+const MAX_RESULTS: usize = 10;
+
+/// State for the Ctrl+P channel switcher overlay.
+#[derive(Default)]
+pub struct ChannelSwitcherState {
+    pub open: bool,
+    pub query: String,
+    /// Index into the filtered results of the highlighted row.
+    pub selected: usize,
+}
+
+impl ChannelSwitcherState {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.query.clear();
+        self.selected = 0;
+    }
+}
+
+/// Draws the switcher over the rest of the UI and returns the channel to join once
+/// the user picks one. If nothing matches, Enter joins whatever was typed.
+pub fn draw_channel_switcher(
+    ctx: &egui::Context,
+    switcher: &mut ChannelSwitcherState,
+    channels: &[String],
+) -> Option<String> {
+    if !switcher.open {
+        return None;
+    }
+
+    let matches: Vec<&str> = if switcher.query.is_empty() {
+        channels
+            .iter()
+            .map(String::as_str)
+            .take(MAX_RESULTS)
+            .collect()
+    } else {
+        fuzzy::rank(
+            &switcher.query,
+            channels.iter().map(String::as_str),
+            MAX_RESULTS,
+        )
+    };
+    switcher.selected = switcher.selected.min(matches.len().saturating_sub(1));
+
+    let (up, down, enter, escape) = ctx.input(|i| {
+        (
+            i.key_pressed(Key::ArrowUp),
+            i.key_pressed(Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+            i.key_pressed(Key::Escape),
+        )
+    });
+    if escape {
+        switcher.open = false;
+        return None;
+    }
+    if up {
+        switcher.selected = switcher.selected.saturating_sub(1);
+    }
+    if down && switcher.selected + 1 < matches.len() {
+        switcher.selected += 1;
+    }
+
+    let mut chosen = None;
+    egui::Area::new(egui::Id::new("channel_switcher"))
+        .anchor(Align2::CENTER_TOP, [0.0, 60.0])
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(320.0);
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut switcher.query)
+                        .hint_text("Jump to channel...")
+                        .desired_width(f32::INFINITY),
+                );
+                response.request_focus();
+                if response.changed() {
+                    switcher.selected = 0;
+                }
+
+                if matches.is_empty() && !switcher.query.trim().is_empty() {
+                    ui.label(
+                        RichText::new(format!("Enter to join '{}'", switcher.query.trim())).weak(),
+                    );
+                }
+                for (i, channel) in matches.iter().enumerate() {
+                    if ui
+                        .selectable_label(i == switcher.selected, *channel)
+                        .clicked()
+                    {
+                        chosen = Some(channel.to_string());
+                    }
+                }
+            });
+        });
+
+    if enter {
+        chosen = matches
+            .get(switcher.selected)
+            .map(|c| c.to_string())
+            .or_else(|| {
+                let typed = switcher.query.trim().trim_start_matches('#');
+                (!typed.is_empty()).then(|| typed.to_lowercase())
+            });
+    }
+    if chosen.is_some() {
+        switcher.open = false;
+    }
+    chosen
+}
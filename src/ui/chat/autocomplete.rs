@@ -0,0 +1,325 @@
+//! Completing the word being typed in the chat bar. Suggestions show once a word
+//! is started; Tab or the arrow keys pick one and Enter takes it, so Enter still
+//! sends the message as long as nothing was picked. An `@mention` is instead
+//! completed in place by Tab, which moves on to the next chatter when pressed again.
+
+use crate::utils::fuzzy;
+use eframe::egui::{
+    self, Align2, EventFilter, Id, Key, Modifiers, Order, Rect,
+    text::{CCursor, CCursorRange},
+};
+use std::ops::Range;
+
+const MAX_SUGGESTIONS: usize = 8;
+/// A single character matches too much to be worth suggesting for.
+const MIN_TYPED_CHARS: usize = 2;
+
+#[derive(Default)]
+pub struct CompletionState {
+    /// The part of the word typed so far that the suggestions are for.
+    typed: String,
+    selected: Option<usize>,
+    /// Escape hides the suggestions until the word changes.
+    dismissed: bool,
+    /// Whether the cursor is in a mention, so Tab stays in the input.
+    in_mention: bool,
+    mention: Option<MentionCycle>,
+}
+
+/// A mention completed by Tab, kept so the next Tab can swap in another match.
+struct MentionCycle {
+    /// Byte offset of the `@`.
+    start: usize,
+    matches: Vec<String>,
+    index: usize,
+    /// The input and cursor right after completing; any edit ends the cycle.
+    text: String,
+    cursor: usize,
+}
+
+/// The word around a cursor in the input.
+#[derive(Debug, PartialEq)]
+pub struct Word<'a> {
+    /// Byte range of the whole word, including any part after the cursor.
+    pub range: Range<usize>,
+    /// The part of the word before the cursor.
+    pub typed: &'a str,
+}
+
+/// The word at `cursor`, a character index into `text`, or `None` when the cursor
+/// isn't right after part of a word.
+pub fn word_at(text: &str, cursor: usize) -> Option<Word<'_>> {
+    let cursor = text
+        .char_indices()
+        .nth(cursor)
+        .map_or(text.len(), |(i, _)| i);
+    let start = text[..cursor]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let end = text[cursor..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |i| cursor + i);
+    let typed = &text[start..cursor];
+    (!typed.is_empty()).then_some(Word {
+        range: start..end,
+        typed,
+    })
+}
+
+/// Replaces the word at `range` with `completion` and a space, returning the
+/// character index after that space for the cursor.
+pub fn replace_word(text: &mut String, range: Range<usize>, completion: &str) -> usize {
+    let start = text[..range.start].chars().count();
+    let mut replacement = completion.to_string();
+    if !text[range.end..].starts_with(char::is_whitespace) {
+        replacement.push(' ');
+    }
+    text.replace_range(range, &replacement);
+    start + completion.chars().count() + 1
+}
+
+/// The best matches for `typed` among `candidates`. A word that is too short, or
+/// already complete with nothing longer to offer, gets none.
+pub fn suggestions<'a>(typed: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    if typed.chars().count() < MIN_TYPED_CHARS {
+        return Vec::new();
+    }
+    let ranked = fuzzy::rank(typed, candidates, MAX_SUGGESTIONS);
+    if ranked == [typed] {
+        Vec::new()
+    } else {
+        ranked
+    }
+}
+
+/// Chatters whose name starts with the mention `typed`, ignoring case and the `@`,
+/// in the order given.
+pub fn mention_matches<'a>(typed: &str, chatters: &[&'a str]) -> Vec<&'a str> {
+    let typed = typed.trim_start_matches('@').to_lowercase();
+    chatters
+        .iter()
+        .copied()
+        .filter(|name| name.to_lowercase().starts_with(&typed))
+        .collect()
+}
+
+/// Completes the word at the cursor of the text edit `input_id`: emote names come
+/// from `candidates`, and mentions from `chatters`, most recently active first.
+/// Call before adding the text edit, so the keys used here don't also reach it,
+/// and finish with [`draw_suggestions`] once it has been added.
+pub fn handle_input<'a>(
+    ui: &mut egui::Ui,
+    input_id: Id,
+    text: &mut String,
+    candidates: impl IntoIterator<Item = &'a str>,
+    chatters: &[&str],
+    state: &mut CompletionState,
+) -> Vec<&'a str> {
+    state.in_mention = false;
+    if !ui.memory(|m| m.has_focus(input_id)) {
+        return Vec::new();
+    }
+    let Some(cursor) = egui::TextEdit::load_state(ui.ctx(), input_id)
+        .and_then(|edit| edit.cursor.char_range())
+        .map(|range| range.primary.index)
+    else {
+        return Vec::new();
+    };
+    state.in_mention = complete_mention(ui, input_id, text, cursor, chatters, state);
+    if state.in_mention {
+        return Vec::new();
+    }
+    let Some(word) = word_at(text, cursor) else {
+        return Vec::new();
+    };
+    if word.typed != state.typed {
+        state.typed = word.typed.to_string();
+        state.selected = None;
+        state.dismissed = false;
+    }
+    if state.dismissed {
+        return Vec::new();
+    }
+    let found = suggestions(word.typed, candidates);
+    if found.is_empty() {
+        return found;
+    }
+
+    let count = found.len();
+    let mut accepted = None;
+    ui.input_mut(|i| {
+        if i.consume_key(Modifiers::SHIFT, Key::Tab) || i.consume_key(Modifiers::NONE, Key::ArrowUp)
+        {
+            state.selected = Some(
+                state
+                    .selected
+                    .map_or(count - 1, |s| (s + count - 1) % count),
+            );
+        } else if i.consume_key(Modifiers::NONE, Key::Tab)
+            || i.consume_key(Modifiers::NONE, Key::ArrowDown)
+        {
+            state.selected = Some(state.selected.map_or(0, |s| (s + 1) % count));
+        }
+        if state.selected.is_some() && i.consume_key(Modifiers::NONE, Key::Enter) {
+            accepted = state.selected;
+        }
+        if i.consume_key(Modifiers::NONE, Key::Escape) {
+            state.dismissed = true;
+        }
+    });
+    if let Some(index) = accepted {
+        let range = word.range;
+        accept(ui.ctx(), input_id, text, range, found[index]);
+        return Vec::new();
+    }
+    if state.dismissed {
+        return Vec::new();
+    }
+    found
+}
+
+/// Shows `suggestions` above the text edit `input_id`, whose response is `input`,
+/// and keeps Tab, the arrows and Escape from moving focus away from it.
+pub fn draw_suggestions(
+    ui: &mut egui::Ui,
+    input_id: Id,
+    input: Rect,
+    text: &mut String,
+    suggestions: &[&str],
+    state: &mut CompletionState,
+) {
+    if suggestions.is_empty() && !state.in_mention {
+        return;
+    }
+    ui.memory_mut(|m| {
+        m.set_focus_lock_filter(
+            input_id,
+            EventFilter {
+                tab: true,
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                escape: true,
+            },
+        )
+    });
+    if suggestions.is_empty() {
+        return;
+    }
+
+    let mut clicked = None;
+    egui::Area::new(input_id.with("suggestions"))
+        .order(Order::Foreground)
+        .pivot(Align2::LEFT_BOTTOM)
+        .fixed_pos(input.left_top())
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for (i, suggestion) in suggestions.iter().enumerate() {
+                    if ui
+                        .selectable_label(state.selected == Some(i), *suggestion)
+                        .clicked()
+                    {
+                        clicked = Some(*suggestion);
+                    }
+                }
+            });
+        });
+    if let Some(suggestion) = clicked {
+        let cursor = egui::TextEdit::load_state(ui.ctx(), input_id)
+            .and_then(|edit| edit.cursor.char_range())
+            .map(|range| range.primary.index);
+        if let Some(range) = cursor
+            .and_then(|cursor| word_at(text, cursor))
+            .map(|word| word.range)
+        {
+            accept(ui.ctx(), input_id, text, range, suggestion);
+        }
+        ui.memory_mut(|m| m.request_focus(input_id));
+    }
+}
+
+/// Handles Tab in a mention, or right after completing one. Returns whether the
+/// cursor is in one.
+fn complete_mention(
+    ui: &mut egui::Ui,
+    input_id: Id,
+    text: &mut String,
+    cursor: usize,
+    chatters: &[&str],
+    state: &mut CompletionState,
+) -> bool {
+    let in_cycle = state
+        .mention
+        .as_ref()
+        .is_some_and(|cycle| cycle.text == *text && cycle.cursor == cursor);
+    let in_word = word_at(text, cursor).is_some_and(|word| word.typed.starts_with('@'));
+    if !in_cycle && !in_word {
+        state.mention = None;
+        return false;
+    }
+    let step = ui.input_mut(|i| {
+        if i.consume_key(Modifiers::SHIFT, Key::Tab) {
+            Some(-1)
+        } else if i.consume_key(Modifiers::NONE, Key::Tab) {
+            Some(1)
+        } else {
+            None
+        }
+    });
+
+    if let Some(cycle) = state.mention.as_mut().filter(|_| in_cycle) {
+        if let Some(step) = step {
+            let previous = format!("@{}", cycle.matches[cycle.index]);
+            let count = cycle.matches.len() as isize;
+            cycle.index = (cycle.index as isize + step).rem_euclid(count) as usize;
+            let range = cycle.start..cycle.start + previous.len();
+            let completion = format!("@{}", cycle.matches[cycle.index]);
+            cycle.cursor = accept(ui.ctx(), input_id, text, range, &completion);
+            cycle.text = text.clone();
+        }
+        return true;
+    }
+    state.mention = None;
+
+    let Some(word) = word_at(text, cursor).filter(|word| word.typed.starts_with('@')) else {
+        return false;
+    };
+    let matches = mention_matches(word.typed, chatters);
+    if step.is_none() || matches.is_empty() {
+        return true;
+    }
+    let index = if step == Some(1) {
+        0
+    } else {
+        matches.len() - 1
+    };
+    let completion = format!("@{}", matches[index]);
+    let range = word.range;
+    let start = range.start;
+    let cursor = accept(ui.ctx(), input_id, text, range, &completion);
+    state.mention = Some(MentionCycle {
+        start,
+        matches: matches.iter().map(|name| name.to_string()).collect(),
+        index,
+        text: text.clone(),
+        cursor,
+    });
+    true
+}
+
+fn accept(
+    ctx: &egui::Context,
+    input_id: Id,
+    text: &mut String,
+    range: Range<usize>,
+    completion: &str,
+) -> usize {
+    let cursor = replace_word(text, range, completion);
+    if let Some(mut edit) = egui::TextEdit::load_state(ctx, input_id) {
+        edit.cursor
+            .set_char_range(Some(CCursorRange::one(CCursor::new(cursor))));
+        edit.store(ctx, input_id);
+    }
+    cursor
+}
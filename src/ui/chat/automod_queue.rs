@@ -0,0 +1,55 @@
+use crate::{
+    app::{
+        config::Config,
+        state::{AppState, ChannelTab},
+    },
+    features::automod::HeldMessage,
+};
+use eframe::egui::{self, Color32, RichText, ScrollArea};
+
+#[derive(Debug)]
+pub enum HeldMessageAction {
+    Allow(HeldMessage),
+    Deny(HeldMessage),
+}
+
+pub fn draw_automod_queue(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    config: &Config,
+) -> Option<HeldMessageAction> {
+    let mut action = None;
+
+    if let Some(ChannelTab { held_messages, .. }) = state.active_tab() {
+        ui.heading("AutoMod Queue");
+        ScrollArea::vertical()
+            .id_salt("automod_queue_scroll_area")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                if held_messages.is_empty() {
+                    ui.label(RichText::new("No held messages.").italics());
+                }
+
+                for message in held_messages.iter() {
+                    ui.group(|ui| {
+                        ui.label(format!(
+                            "{} at {}",
+                            message.user_name,
+                            config.timezone.format(&message.held_at, "%H:%M")
+                        ));
+                        ui.label(RichText::new(&message.text).color(Color32::from_gray(200)));
+                        ui.horizontal(|ui| {
+                            if ui.button("Allow").clicked() {
+                                action = Some(HeldMessageAction::Allow(message.clone()));
+                            }
+                            if ui.button("Deny").clicked() {
+                                action = Some(HeldMessageAction::Deny(message.clone()));
+                            }
+                        });
+                    });
+                }
+            });
+    }
+
+    action
+}
@@ -1,39 +1,174 @@
-use crate::app::state::AppState;
-use eframe::egui;
+use crate::{
+    app::{config::Config, state::AppState},
+    core::tokenize::EmotePrecedence,
+    models::emote::Emote,
+    ui::chat::{
+        autocomplete::{self, CompletionState},
+        emote_image,
+    },
+    utils::text_processing::find_clip_url,
+};
+use eframe::egui::{self, RichText};
+use std::time::{Duration, Instant};
 
+/// The message box, for focusing it and finding its cursor. `/` focuses it from
+/// anywhere else.
+pub const CHAT_INPUT_ID: &str = "chat_bar_input";
+/// The message box grows with its text up to this many rows, then scrolls.
+const MAX_INPUT_ROWS: f32 = 4.0;
+
+/// Tracks pastes into the message box so pastes that can't work in Twitch chat
+/// get an explanation instead of silently doing nothing.
+#[derive(Default)]
+pub struct PasteState {
+    last_text_paste: Option<Instant>,
+    notice: Option<PasteNotice>,
+}
+
+enum PasteNotice {
+    ImageNotSupported,
+    /// Pasted text had a clip link buried in other text.
+    ClipLink(String),
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn draw_chat_bar(
     ui: &mut egui::Ui,
     state: &mut AppState,
+    config: &Config,
     send_action: &mut Option<bool>,
     show_emote_picker: &mut bool,
     show_user_list: &mut bool,
+    paste: &mut PasteState,
+    completion: &mut CompletionState,
 ) {
     if let AppState::LoggedIn {
         message_to_send,
-        current_channel,
+        tabs,
+        active_tab,
         send_in_progress,
+        send_queued_until,
         last_error,
+        unsent_message,
+        user_login,
+        identities,
+        send_as,
+        global_emotes,
         ..
     } = state
     {
+        let has_channel = !tabs.is_empty();
+        if let Some(tab) = tabs.get_mut(*active_tab)
+            && let Some(reply) = &tab.replying_to
+        {
+            let mut cancel = false;
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(format!("↳ Replying to {}", reply.summary())).weak());
+                cancel = ui.small_button("✖").on_hover_text("Cancel reply").clicked();
+            });
+            if cancel {
+                tab.replying_to = None;
+            }
+        }
+        let (channel_emotes, third_party_emotes) =
+            tabs.get(*active_tab).map_or((&[][..], &[][..]), |tab| {
+                (
+                    tab.channel_emotes.as_slice(),
+                    tab.third_party_emotes.as_slice(),
+                )
+            });
         ui.scope(|ui| {
             let new_font_size = ui.style().text_styles[&egui::TextStyle::Body].size * 1.25;
-            ui.style_mut().override_font_id = Some(egui::FontId::new(
-                new_font_size,
-                egui::FontFamily::Proportional,
-            ));
+            let input_font = egui::FontId::new(new_font_size, egui::FontFamily::Proportional);
+            ui.style_mut().override_font_id = Some(input_font.clone());
 
             ui.add_space(4.0);
+            let narrow = ui.available_width() < config.narrow_layout_width;
             ui.horizontal(|ui| {
                 if ui.button("😀").clicked() {
                     *show_emote_picker = !*show_emote_picker;
                 }
-                let response = ui
-                    .add(egui::TextEdit::singleline(message_to_send).hint_text("Enter message..."));
-                let enter_pressed =
-                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
-                let can_send =
-                    !message_to_send.is_empty() && current_channel.is_some() && !*send_in_progress;
+                if !identities.is_empty() {
+                    let selected = send_as.clone().unwrap_or_else(|| user_login.clone());
+                    egui::ComboBox::from_id_salt("send_as_combo")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(send_as, None, user_login.as_str());
+                            for identity in identities.iter() {
+                                ui.selectable_value(
+                                    send_as,
+                                    Some(identity.profile_name.clone()),
+                                    identity.profile_name.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("Account to send messages as");
+                }
+                let input_id = egui::Id::new(CHAT_INPUT_ID);
+                let third_party_candidates = third_party_emotes
+                    .iter()
+                    .filter(|e| config.emote_source_enabled(&e.source))
+                    .map(|e| e.name.as_str());
+                let candidates = channel_emotes
+                    .iter()
+                    .chain(global_emotes.iter())
+                    .filter(|_| config.enable_twitch_emotes)
+                    .map(|e| e.name.as_str())
+                    .chain(third_party_candidates);
+                let chatters = tabs
+                    .get(*active_tab)
+                    .map(|tab| tab.recent_chatters())
+                    .unwrap_or_default();
+                let suggestions = autocomplete::handle_input(
+                    ui,
+                    input_id,
+                    message_to_send,
+                    candidates,
+                    &chatters,
+                    completion,
+                );
+                // Enter sends; Shift+Enter starts a new line.
+                let max_height = ui.fonts(|f| f.row_height(&input_font)) * MAX_INPUT_ROWS;
+                let input = egui::ScrollArea::vertical()
+                    .id_salt(input_id.with("scroll"))
+                    .max_height(max_height)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(message_to_send)
+                                .id(input_id)
+                                .desired_rows(1)
+                                .return_key(egui::KeyboardShortcut::new(
+                                    egui::Modifiers::SHIFT,
+                                    egui::Key::Enter,
+                                ))
+                                .hint_text("Enter message..."),
+                        )
+                    });
+                let response = input.inner;
+                // Suggestions sit above the visible part of the box, however tall.
+                autocomplete::draw_suggestions(
+                    ui,
+                    input_id,
+                    input.inner_rect,
+                    message_to_send,
+                    &suggestions,
+                    completion,
+                );
+                let enter_pressed = response.has_focus()
+                    && ui.input(|i| i.key_pressed(egui::Key::Enter) && !i.modifiers.shift);
+                if response.has_focus() {
+                    detect_paste(ui, paste);
+                }
+                // Up on an empty input recalls a message that failed to send.
+                if response.has_focus()
+                    && message_to_send.is_empty()
+                    && ui.input(|i| i.key_pressed(egui::Key::ArrowUp))
+                    && let Some(unsent) = unsent_message.as_ref()
+                {
+                    *message_to_send = unsent.clone();
+                }
+                let can_send = !message_to_send.is_empty() && has_channel && !*send_in_progress;
                 if ui
                     .add_enabled(can_send, egui::Button::new("Send"))
                     .clicked()
@@ -41,26 +176,173 @@ pub fn draw_chat_bar(
                 {
                     *send_action = Some(false);
                 }
-                if ui
-                    .add_enabled(can_send, egui::Button::new("Announce"))
-                    .clicked()
-                {
-                    *send_action = Some(true);
-                }
                 if *send_in_progress {
                     ui.spinner();
+                    let wait = send_queued_until
+                        .map(|until| until.saturating_duration_since(Instant::now()))
+                        .filter(|wait| !wait.is_zero());
+                    if let Some(wait) = wait {
+                        ui.label(RichText::new(format!("Queued, {}s", wait.as_secs() + 1)).weak())
+                            .on_hover_text("Held back to stay under Twitch's chat rate limit");
+                        ui.ctx().request_repaint_after(Duration::from_millis(250));
+                    }
                 }
 
-                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                    if ui.button("👥").clicked() {
-                        *show_user_list = !*show_user_list;
+                if narrow {
+                    // The user list is hidden in the narrow layout anyway, so only
+                    // announcing is left for the overflow menu.
+                    ui.menu_button("⋯", |ui| {
+                        if ui
+                            .add_enabled(can_send, egui::Button::new("Announce"))
+                            .clicked()
+                        {
+                            *send_action = Some(true);
+                            ui.close();
+                        }
+                    });
+                } else {
+                    if ui
+                        .add_enabled(can_send, egui::Button::new("Announce"))
+                        .clicked()
+                    {
+                        *send_action = Some(true);
                     }
-                });
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("👥").clicked() {
+                            *show_user_list = !*show_user_list;
+                        }
+                    });
+                }
             });
             ui.add_space(4.0);
         });
+        if config.show_emote_preview {
+            // Emote codes are matched as whole words, exactly as chat will render them.
+            let emotes: Vec<Emote> = message_to_send
+                .split_whitespace()
+                .filter_map(|word| {
+                    let twitch = || {
+                        channel_emotes
+                            .iter()
+                            .chain(global_emotes.iter())
+                            .find(|e| e.name == word)
+                            .map(|e| e.to_emote())
+                            .filter(|e| config.emote_source_enabled(&e.source))
+                    };
+                    // Later third-party emotes replace earlier ones with the same code.
+                    let third_party = || {
+                        third_party_emotes
+                            .iter()
+                            .rev()
+                            .find(|e| e.name == word)
+                            .filter(|e| config.emote_source_enabled(&e.source))
+                            .cloned()
+                    };
+                    match config.emote_precedence {
+                        EmotePrecedence::Twitch => twitch().or_else(third_party),
+                        EmotePrecedence::ThirdParty => third_party().or_else(twitch),
+                    }
+                })
+                .collect();
+            if !emotes.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(RichText::new("Emotes:").weak());
+                    for emote in &emotes {
+                        emote_image::draw_emote_image(
+                            ui,
+                            emote_image::emote_url_for_size(ui, emote, config.emote_size),
+                            &emote.name,
+                            config.emote_size,
+                            None,
+                        )
+                        .on_hover_text(&emote.name);
+                    }
+                });
+            }
+        }
+        match &paste.notice {
+            Some(PasteNotice::ImageNotSupported) => {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("Images can't be sent in Twitch chat; paste a link instead.")
+                            .weak(),
+                    );
+                    if ui.small_button("OK").clicked() {
+                        paste.notice = None;
+                    }
+                });
+            }
+            Some(PasteNotice::ClipLink(url)) => {
+                let url = url.clone();
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("Pasted text contains a clip link.").weak());
+                    if ui.small_button("Use just the link").clicked() {
+                        *message_to_send = url;
+                        paste.notice = None;
+                    }
+                    if ui.small_button("Keep as is").clicked() {
+                        paste.notice = None;
+                    }
+                });
+            }
+            None => {}
+        }
         if let Some(error) = last_error {
-            ui.colored_label(egui::Color32::RED, error);
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::RED, error.as_str());
+                if let Some(unsent) = unsent_message.as_ref()
+                    && !*send_in_progress
+                    && has_channel
+                    && ui.small_button("Retry").clicked()
+                {
+                    *message_to_send = unsent.clone();
+                    *send_action = Some(false);
+                }
+            });
         }
     }
 }
+
+/// egui only reports text pastes. When the clipboard holds an image, egui-winit
+/// drops the paste entirely but still reports the V key being released, so a
+/// release without a recent text paste is a cue to check the clipboard.
+fn detect_paste(ui: &egui::Ui, paste: &mut PasteState) {
+    let (pasted_text, paste_key_released) = ui.input(|i| {
+        let mut pasted_text = None;
+        let mut paste_key_released = false;
+        for event in &i.events {
+            match event {
+                egui::Event::Paste(text) => pasted_text = Some(text.clone()),
+                egui::Event::Key {
+                    key: egui::Key::V,
+                    pressed: false,
+                    modifiers,
+                    ..
+                } if modifiers.command => paste_key_released = true,
+                _ => {}
+            }
+        }
+        (pasted_text, paste_key_released)
+    });
+
+    if let Some(text) = pasted_text {
+        paste.last_text_paste = Some(Instant::now());
+        paste.notice = match find_clip_url(&text) {
+            Some(url) if url != text.trim() => Some(PasteNotice::ClipLink(url.to_string())),
+            _ => None,
+        };
+    }
+
+    let pasted_recently = paste
+        .last_text_paste
+        .is_some_and(|t| t.elapsed() < Duration::from_secs(1));
+    if paste_key_released && !pasted_recently && clipboard_has_image() {
+        paste.notice = Some(PasteNotice::ImageNotSupported);
+    }
+}
+
+fn clipboard_has_image() -> bool {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_image())
+        .is_ok()
+}
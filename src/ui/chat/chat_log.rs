@@ -1,85 +1,557 @@
 use crate::{
-    app::{config::Config, state::AppState},
-    models::message::MessageFragment,
-    utils::text_processing::{TextOrUrl, parse_text_for_urls},
+    app::{
+        config::{Config, InlineAlignment, OwnMessageStyle},
+        state::AppState,
+    },
+    models::message::{ChatMessage, LowTrustStatus, MessageFragment, MessageKind, ReplyInfo},
+    ui::chat::{
+        emote_image, empty_state,
+        message_style::{self, RowHighlight},
+    },
+    utils::{
+        text_processing::{TextOrUrl, parse_text_for_urls, truncate_chars},
+        time,
+    },
 };
-use eframe::egui::{self, Color32, Image, RichText, ScrollArea, Vec2};
+use eframe::egui::{self, Align, Color32, Layout, RichText, ScrollArea, Sense, UiBuilder, Vec2};
+use std::{collections::VecDeque, time::Instant};
 
-pub fn draw_chat_log(ui: &mut egui::Ui, state: &mut AppState, config: &Config) {
-    if let AppState::LoggedIn { chat_messages, .. } = state {
+const DELETED_COLOR: Color32 = Color32::from_gray(100);
+const CHEER_COLOR: Color32 = Color32::from_rgb(145, 70, 255);
+const LOW_TRUST_COLOR: Color32 = Color32::from_rgb(255, 165, 0);
+/// Plain `http://` links are drawn in this color so they stand out from `https://` ones.
+const INSECURE_LINK_COLOR: Color32 = Color32::from_rgb(230, 120, 60);
+const LOW_TRUST_FILL: Color32 = Color32::from_rgba_premultiplied(60, 40, 0, 60);
+const KEYWORD_FILL: Color32 = Color32::from_rgba_premultiplied(80, 20, 50, 70);
+const CONTINUATION_INDENT: f32 = 16.0;
+/// Longer messages are cut off behind a "show more" link, so a wall of text can't
+/// take over the log.
+const MAX_RENDERED_CHARS: usize = 500;
+/// Choices in the sender menu's timeout submenu.
+const TIMEOUT_DURATIONS: [(&str, u32); 5] = [
+    ("1 minute", 60),
+    ("10 minutes", 600),
+    ("1 hour", 3600),
+    ("1 day", 86_400),
+    ("1 week", 604_800),
+];
+
+/// Something the user asked to do to a message from the chat log.
+#[derive(Debug)]
+pub enum MessageAction {
+    /// Ban the sender, or time them out when `duration_secs` is set.
+    Ban {
+        user_id: String,
+        user_name: String,
+        duration_secs: Option<u32>,
+        reason: String,
+    },
+    /// Hide the low-trust warning on a message the moderator has reviewed. Only
+    /// this app forgets the warning; Twitch keeps the user's status.
+    HideLowTrustWarning(String),
+    /// Open the JSON Twitch sent for the message with this id.
+    ShowRawEvent(String),
+    /// A link in a message was clicked.
+    OpenLink(String),
+    /// An `@mention` was clicked; show only messages to and from that user.
+    FilterUser(String),
+    /// Make the next message sent a reply to this one.
+    Reply(ReplyInfo),
+    /// Hide all messages from this user from now on.
+    IgnoreUser(String),
+}
+
+pub fn draw_chat_log(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    config: &Config,
+) -> Option<MessageAction> {
+    let mut action = None;
+    if let AppState::LoggedIn {
+        tabs,
+        active_tab,
+        chat_filter,
+        user_id,
+        identities,
+        ..
+    } = state
+    {
+        let Some(tab) = tabs.get_mut(*active_tab) else {
+            empty_state::draw_empty_state(ui, "Join a channel to start chatting.", false);
+            return None;
+        };
+        if tab.chat_messages.is_empty() {
+            empty_state::draw_empty_state(
+                ui,
+                &format!("No one has chatted in #{} yet.", tab.channel),
+                false,
+            );
+            return None;
+        }
+        let animations_paused = config.pause_emote_animations_when_busy
+            && tab
+                .activity
+                .update_animations_paused(Instant::now(), config.busy_chat_threshold);
+        if animations_paused {
+            ui.label(RichText::new("⏸ Emote animations paused while chat is busy").weak());
+        }
+        ui.horizontal(|ui| {
+            ui.label("🔍");
+            ui.add(
+                egui::TextEdit::singleline(chat_filter)
+                    .hint_text("Search messages")
+                    .desired_width(f32::INFINITY),
+            );
+        });
+        let mut clear_filter = false;
+        if let Some(user_name) = &tab.mention_filter {
+            ui.horizontal(|ui| {
+                ui.label(format!("Showing messages to and from @{}", user_name));
+                clear_filter = ui.small_button("✖ Clear").clicked();
+            });
+        }
         ScrollArea::vertical()
-            .id_salt("chat_log_scroll_area")
+            .id_salt(("chat_log_scroll_area", &tab.channel))
             .stick_to_bottom(true)
             .auto_shrink([false, false])
             .show(ui, |ui| {
-                for message in chat_messages.iter() {
-                    ui.horizontal_wrapped(|ui| {
-                        ui.set_min_height(config.emote_size);
+                let raw_events = config.developer_tools.then_some(&tab.raw_events);
+                let own_user_ids: Vec<&str> = std::iter::once(user_id.as_str())
+                    .chain(identities.iter().map(|i| i.token.user_id.as_str()))
+                    .collect();
+                let filter = tab.mention_filter.as_deref();
+                let query = chat_filter.trim();
+                action = draw_messages(
+                    ui,
+                    tab.chat_messages.iter().filter(|m| {
+                        filter.is_none_or(|user_name| m.involves(user_name))
+                            && (query.is_empty() || m.matches_search(query))
+                    }),
+                    config,
+                    tab.is_moderator,
+                    raw_events,
+                    &own_user_ids,
+                    !animations_paused,
+                    true,
+                );
+            });
+        if clear_filter {
+            tab.mention_filter = None;
+        }
+        // Filtering only applies to the live log, so it's handled here rather than
+        // passed on with the other actions.
+        if let Some(MessageAction::FilterUser(user_name)) = &action {
+            tab.mention_filter = Some(user_name.clone());
+            action = None;
+        }
+    }
+    action
+}
+
+/// Renders chat rows without any surrounding scroll area, so both the live log and
+/// the replay view lay messages out identically. `show_mod_tools` adds the
+/// low-trust highlight and its actions, and a timeout/ban menu on other users'
+/// names. Right-clicking a row offers to copy its text or sender; with
+/// `raw_events`, rows that have one also offer "Show raw event", and with
+/// `allow_reply`, chat messages offer "Reply". Messages from `own_user_ids` are
+/// highlighted if enabled, and messages from ignored users are left out. Without
+/// `animate_emotes`, animated emotes are drawn as still images.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_messages<'a>(
+    ui: &mut egui::Ui,
+    messages: impl Iterator<Item = &'a ChatMessage>,
+    config: &Config,
+    show_mod_tools: bool,
+    raw_events: Option<&VecDeque<(String, String)>>,
+    own_user_ids: &[&str],
+    animate_emotes: bool,
+    allow_reply: bool,
+) -> Option<MessageAction> {
+    let text_height = ui.text_style_height(&egui::TextStyle::Body);
+    let row_layout = Layout::left_to_right(match config.inline_alignment {
+        InlineAlignment::Top => Align::Min,
+        InlineAlignment::Center => Align::Center,
+        InlineAlignment::Bottom => Align::Max,
+    })
+    .with_main_wrap(true);
+
+    let mut action = None;
+    let mut previous: Option<&ChatMessage> = None;
+    for message in messages.filter(|m| !config.is_ignored(&m.sender_name)) {
+        let continuation = message_style::should_group(previous, message, config);
+        let row_height = message_style::row_height(message, config, text_height);
+        let row_size = Vec2::new(ui.available_size_before_wrap().x, row_height);
+        let low_trust = message.low_trust.filter(|_| show_mod_tools);
+        let highlight = message_style::row_highlight(message, config, show_mod_tools, own_user_ids);
+        let can_moderate = show_mod_tools
+            && !message.sender_id.is_empty()
+            && !own_user_ids.contains(&message.sender_id.as_str());
+        let has_raw_event =
+            raw_events.is_some_and(|events| events.iter().any(|(id, _)| *id == message.id));
+        let can_ignore = matches!(message.kind, MessageKind::Chat)
+            && !message.sender_name.is_empty()
+            && !own_user_ids.contains(&message.sender_id.as_str());
+        let can_reply = allow_reply
+            && !message.id.is_empty()
+            && !message.deleted
+            && matches!(message.kind, MessageKind::Chat);
+        if let Some(reply) = &message.reply_to {
+            ui.label(
+                RichText::new(format!("↳ replying to {}", reply.summary()))
+                    .small()
+                    .weak(),
+            );
+        }
+        ui.allocate_ui_with_layout(row_size, row_layout, |ui| {
+            // The row's sense sits below its own widgets, so links and emotes keep
+            // working.
+            ui.scope_builder(UiBuilder::new().sense(Sense::click()), |ui| {
+                // Reserve a spot behind the row so the highlight can be sized to it afterwards.
+                let background = ui.painter().add(egui::Shape::Noop);
+                if let Some(status) = low_trust
+                    && let Some(low_trust_action) = draw_low_trust_controls(ui, message, status)
+                {
+                    action = Some(low_trust_action);
+                }
+                if let Some(message_action) = draw_message(
+                    ui,
+                    message,
+                    config,
+                    continuation,
+                    animate_emotes,
+                    can_moderate,
+                ) {
+                    action = Some(message_action);
+                }
+                let rect = ui.min_rect().expand(1.0);
+                let own_color = ui.visuals().selection.bg_fill;
+                let shape = match highlight {
+                    RowHighlight::None => egui::Shape::Noop,
+                    RowHighlight::LowTrust => egui::Shape::rect_filled(rect, 2.0, LOW_TRUST_FILL),
+                    RowHighlight::Keyword => {
+                        let fill = config.accent_color.map_or(KEYWORD_FILL, |[r, g, b]| {
+                            Color32::from_rgb(r, g, b).gamma_multiply(0.3)
+                        });
+                        egui::Shape::rect_filled(rect, 2.0, fill)
+                    }
+                    RowHighlight::Own(OwnMessageStyle::Tint) => {
+                        egui::Shape::rect_filled(rect, 2.0, own_color.gamma_multiply(0.25))
+                    }
+                    RowHighlight::Own(OwnMessageStyle::Border) => egui::Shape::rect_stroke(
+                        rect,
+                        2.0,
+                        egui::Stroke::new(1.0, own_color),
+                        egui::StrokeKind::Inside,
+                    ),
+                };
+                ui.painter().set(background, shape);
+            })
+            .response
+            .context_menu(|ui| {
+                if can_reply && ui.button("Reply").clicked() {
+                    action = Some(MessageAction::Reply(message.reply_info()));
+                    ui.close();
+                }
+                if ui.button("Copy message text").clicked() {
+                    ui.ctx().copy_text(message.to_plain_text());
+                    ui.close();
+                }
+                if matches!(message.kind, MessageKind::Chat) && ui.button("Copy username").clicked()
+                {
+                    ui.ctx().copy_text(message.sender_name.clone());
+                    ui.close();
+                }
+                if can_ignore && ui.button("Ignore user").clicked() {
+                    action = Some(MessageAction::IgnoreUser(message.sender_name.clone()));
+                    ui.close();
+                }
+                if has_raw_event && ui.button("Show raw event").clicked() {
+                    action = Some(MessageAction::ShowRawEvent(message.id.clone()));
+                    ui.close();
+                }
+            });
+        });
+        previous = Some(message);
+    }
+    action
+}
+
+fn draw_low_trust_controls(
+    ui: &mut egui::Ui,
+    message: &ChatMessage,
+    status: LowTrustStatus,
+) -> Option<MessageAction> {
+    let label = match status {
+        LowTrustStatus::ActiveMonitoring => "⚠ Monitored",
+        LowTrustStatus::Restricted => "⚠ Restricted",
+    };
+    ui.label(RichText::new(label).strong().color(LOW_TRUST_COLOR))
+        .on_hover_text("A moderator flagged this user as suspicious");
+    if ui.small_button("Ban").clicked() {
+        return Some(MessageAction::Ban {
+            user_id: message.sender_id.clone(),
+            user_name: message.sender_name.clone(),
+            duration_secs: None,
+            reason: "Suspicious user".to_string(),
+        });
+    }
+    if ui
+        .small_button("Hide warning")
+        .on_hover_text("Only hides it here; the user stays flagged on Twitch")
+        .clicked()
+    {
+        return Some(MessageAction::HideLowTrustWarning(message.id.clone()));
+    }
+    None
+}
+
+/// With `can_moderate`, right-clicking the sender's name offers to time them out or
+/// ban them.
+fn draw_message(
+    ui: &mut egui::Ui,
+    message: &ChatMessage,
+    config: &Config,
+    continuation: bool,
+    animate_emotes: bool,
+    can_moderate: bool,
+) -> Option<MessageAction> {
+    let mut action = None;
+    if config.show_timestamps {
+        let pattern = time::valid_format_or_default(&config.timestamp_format);
+        let timestamp_str = format!("[{}] ", config.timezone.format(&message.timestamp, pattern));
+        ui.label(RichText::new(timestamp_str).color(Color32::from_gray(128)));
+    }
 
-                        if config.show_timestamps {
-                            let timestamp_str = message.timestamp.format("[%H:%M:%S] ").to_string();
-                            ui.label(RichText::new(timestamp_str).color(Color32::from_gray(128)));
+    let color =
+        message_style::resolve_sender_color(&message.sender_name, message.sender_color, config);
+    match &message.kind {
+        MessageKind::Chat if continuation => {
+            ui.add_space(CONTINUATION_INDENT);
+        }
+        MessageKind::Chat => {
+            let mut sender = RichText::new(format!("{}: ", message.sender_name));
+            sender = if message.deleted {
+                sender.color(DELETED_COLOR).strikethrough()
+            } else {
+                sender.color(color)
+            };
+            if can_moderate {
+                ui.add(egui::Label::new(sender).sense(Sense::click()))
+                    .on_hover_cursor(egui::CursorIcon::ContextMenu)
+                    .context_menu(|ui| {
+                        if let Some(menu_action) = draw_moderation_menu(ui, message) {
+                            action = Some(menu_action);
                         }
+                    });
+            } else {
+                ui.label(sender);
+            }
+        }
+        MessageKind::System { category, text } => {
+            let color =
+                message_style::resolve_system_color(*category, config, ui.visuals().dark_mode);
+            ui.label(
+                RichText::new(format!("{} {}", category.icon(), text))
+                    .strong()
+                    .color(color),
+            );
+        }
+    }
 
-                        let color = if let Some((r, g, b)) = message.sender_color {
-                            Color32::from_rgb(r, g, b)
-                        } else {
-                            // Default color if none provided
-                            Color32::from_gray(160)
-                        };
-                        let sender =
-                            RichText::new(format!("{}: ", message.sender_name)).color(color);
-                        ui.label(sender);
-
-                        let original_spacing_x = ui.spacing().item_spacing.x;
-                        for (i, fragment) in message.fragments.iter().enumerate() {
-                            let is_emote = matches!(fragment, MessageFragment::Emote(_));
-                            let mut reset_spacing = true;
-
-                            if i > 0 && config.collapse_emotes {
-                                let prev_is_emote = matches!(
-                                    message.fragments.get(i - 1),
-                                    Some(MessageFragment::Emote(_))
-                                );
-                                if prev_is_emote && is_emote {
-                                    ui.spacing_mut().item_spacing.x = 0.0;
-                                    reset_spacing = false;
-                                }
-                            }
+    let long = message.fragments.iter().map(display_len).sum::<usize>() > MAX_RENDERED_CHARS;
+    let expanded_id = egui::Id::new(("message_expanded", &message.id));
+    let expanded = long && ui.data(|d| d.get_temp::<bool>(expanded_id).unwrap_or(false));
+    let mut remaining = if long && !expanded {
+        MAX_RENDERED_CHARS
+    } else {
+        usize::MAX
+    };
 
-                            if reset_spacing {
-                                ui.spacing_mut().item_spacing.x = original_spacing_x;
-                            }
+    let original_spacing_x = ui.spacing().item_spacing.x;
+    for (i, fragment) in message.fragments.iter().enumerate() {
+        if remaining == 0 {
+            break;
+        }
+        let previous = i.checked_sub(1).and_then(|p| message.fragments.get(p));
+        ui.spacing_mut().item_spacing.x =
+            if message_style::should_collapse_spacing(previous, fragment, config) {
+                0.0
+            } else {
+                original_spacing_x
+            };
 
-                            match fragment {
-                                MessageFragment::Text(text) => {
-                                    for segment in parse_text_for_urls(text) {
-                                        match segment {
-                                            TextOrUrl::Text(t) => {
-                                                ui.label(RichText::new(t));
-                                            }
-                                            TextOrUrl::Url(u) => {
-                                                ui.hyperlink(&u);
-                                            }
-                                        }
-                                    }
-                                }
-                                MessageFragment::Emote(emote) => {
-                                    let image = Image::new(emote.url.as_str())
-                                        .max_size(Vec2::new(config.emote_size, config.emote_size));
-
-                                    let source_text = format!("{:?}", emote.source);
-
-                                    let _response = ui
-                                        .add(image.sense(egui::Sense::click()))
-                                        .on_hover_text(format!("{} - {}", emote.name, source_text));
-                                }
+        match fragment {
+            MessageFragment::Text(text) if message.deleted => {
+                let text = truncate_chars(text, remaining);
+                ui.label(RichText::new(text).color(DELETED_COLOR).strikethrough());
+            }
+            MessageFragment::Text(text) => {
+                // Explicit wrapping lets egui break inside a word that is wider than
+                // the whole row instead of overflowing it.
+                for segment in
+                    parse_text_for_urls(truncate_chars(text, remaining), config.autolink_urls)
+                {
+                    match segment {
+                        TextOrUrl::Text(t) if message.is_action => {
+                            ui.add(
+                                egui::Label::new(RichText::new(t).italics().color(color)).wrap(),
+                            );
+                        }
+                        TextOrUrl::Text(t) => {
+                            ui.add(egui::Label::new(RichText::new(t)).wrap());
+                        }
+                        TextOrUrl::Url(u) => {
+                            if draw_link(ui, &u).clicked() {
+                                action = Some(MessageAction::OpenLink(u));
                             }
                         }
-                        // Restore the original spacing for the next message
-                        ui.spacing_mut().item_spacing.x = original_spacing_x;
-                    });
+                    }
                 }
-            });
+            }
+            MessageFragment::Mention(name) => {
+                let mention = RichText::new(format!("@{}", name)).strong();
+                let mention = if message.deleted {
+                    mention.color(DELETED_COLOR).strikethrough()
+                } else {
+                    mention.color(ui.visuals().hyperlink_color)
+                };
+                if ui
+                    .add(egui::Label::new(mention).sense(Sense::click()))
+                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                    .on_hover_text("Show only messages to and from this user")
+                    .clicked()
+                {
+                    action = Some(MessageAction::FilterUser(name.clone()));
+                }
+            }
+            MessageFragment::Cheermote { bits, emote, .. }
+                if config.emote_source_enabled(&emote.source)
+                    && (animate_emotes || !emote.lacks_still_image()) =>
+            {
+                let url = if animate_emotes {
+                    emote_image::emote_url_for_size(ui, emote, config.emote_size)
+                } else {
+                    emote_image::still_emote_url_for_size(ui, emote, config.emote_size)
+                };
+                let tint = message.deleted.then_some(DELETED_COLOR);
+                emote_image::draw_emote_image(ui, url, &emote.name, config.emote_size, tint)
+                    .on_hover_text(format!("{}{} - {} bits", emote.name, bits, bits));
+                let amount = RichText::new(bits.to_string()).strong();
+                ui.label(if message.deleted {
+                    amount.color(DELETED_COLOR).strikethrough()
+                } else {
+                    amount.color(CHEER_COLOR)
+                });
+            }
+            // Cheers without a known or drawable image show as text.
+            MessageFragment::Cheer { prefix, bits }
+            | MessageFragment::Cheermote { prefix, bits, .. } => {
+                let cheer = RichText::new(format!("{}{}", prefix, bits)).strong();
+                ui.label(if message.deleted {
+                    cheer.color(DELETED_COLOR).strikethrough()
+                } else {
+                    cheer.color(CHEER_COLOR)
+                });
+            }
+            // Disabled sources, and animated emotes that can't be paused, show as text.
+            MessageFragment::Emote(emote)
+                if !config.emote_source_enabled(&emote.source)
+                    || (!animate_emotes && emote.lacks_still_image()) =>
+            {
+                let code = RichText::new(&emote.name);
+                ui.label(if message.deleted {
+                    code.color(DELETED_COLOR).strikethrough()
+                } else {
+                    code
+                });
+            }
+            MessageFragment::Emote(emote) => {
+                let url = if animate_emotes {
+                    emote_image::emote_url_for_size(ui, emote, config.emote_size)
+                } else {
+                    emote_image::still_emote_url_for_size(ui, emote, config.emote_size)
+                };
+                let tint = message.deleted.then_some(DELETED_COLOR);
+
+                let caption = format!("{} - {:?}", emote.name, emote.source);
+                emote_image::draw_emote_image(ui, url, &emote.name, config.emote_size, tint)
+                    .on_hover_ui(|ui| emote_image::draw_emote_preview(ui, emote, &caption));
+            }
+        }
+        remaining = remaining.saturating_sub(display_len(fragment));
+    }
+    // Restore the original spacing for the next message
+    ui.spacing_mut().item_spacing.x = original_spacing_x;
+
+    if long {
+        let label = if expanded {
+            "show less"
+        } else {
+            "… show more"
+        };
+        if ui.link(label).clicked() {
+            ui.data_mut(|d| d.insert_temp(expanded_id, !expanded));
+        }
+    }
+
+    if message.deleted {
+        ui.label(RichText::new("(deleted)").italics().color(DELETED_COLOR));
+    }
+    action
+}
+
+fn draw_moderation_menu(ui: &mut egui::Ui, message: &ChatMessage) -> Option<MessageAction> {
+    let mut action = None;
+    let ban = |duration_secs| MessageAction::Ban {
+        user_id: message.sender_id.clone(),
+        user_name: message.sender_name.clone(),
+        duration_secs,
+        reason: String::new(),
+    };
+    ui.label(RichText::new(&message.sender_name).strong());
+    ui.separator();
+    ui.menu_button("Timeout", |ui| {
+        for (label, secs) in TIMEOUT_DURATIONS {
+            if ui.button(label).clicked() {
+                action = Some(ban(Some(secs)));
+                ui.close();
+            }
+        }
+    });
+    if ui.button("Ban").clicked() {
+        action = Some(ban(None));
+        ui.close();
+    }
+    action
+}
+
+/// Draws a link without opening it, so the caller decides what a click does. The
+/// full destination is shown on hover, and `http://` links are marked as insecure.
+fn draw_link(ui: &mut egui::Ui, url: &str) -> egui::Response {
+    let insecure = url.to_ascii_lowercase().starts_with("http://");
+    let mut text = RichText::new(url).underline();
+    if insecure {
+        text = text.color(INSECURE_LINK_COLOR);
+    }
+    let response = ui
+        .add(egui::Label::new(text).sense(Sense::click()).wrap())
+        .on_hover_cursor(egui::CursorIcon::PointingHand);
+    if insecure {
+        response.on_hover_text(format!("⚠ Insecure link: {}", url))
+    } else {
+        response.on_hover_text(url)
+    }
+}
+
+/// Roughly how many characters a fragment takes up when rendered.
+fn display_len(fragment: &MessageFragment) -> usize {
+    match fragment {
+        MessageFragment::Text(text) => text.chars().count(),
+        MessageFragment::Emote(emote) => emote.name.chars().count(),
+        MessageFragment::Mention(name) => name.chars().count() + 1,
+        MessageFragment::Cheer { prefix, bits }
+        | MessageFragment::Cheermote { prefix, bits, .. } => {
+            prefix.chars().count() + bits.to_string().len()
+        }
     }
 }
@@ -0,0 +1,63 @@
+use crate::models::emote::{Emote, EmoteScale};
+use eframe::egui::{self, Color32, Frame, Image, Margin, Response, RichText, Sense, Vec2};
+
+/// The URL for `emote` at the rendition that suits drawing it `size` points wide on
+/// this display, so large emote sizes and HiDPI screens don't get an upscaled 1x image.
+pub fn emote_url_for_size(ui: &egui::Ui, emote: &Emote, size: f32) -> String {
+    let scale = EmoteScale::for_size(size, ui.ctx().pixels_per_point());
+    emote.image_url(ui.visuals().dark_mode, scale)
+}
+
+/// Like [`emote_url_for_size`], but the first frame of animated Twitch emotes.
+pub fn still_emote_url_for_size(ui: &egui::Ui, emote: &Emote, size: f32) -> String {
+    let scale = EmoteScale::for_size(size, ui.ctx().pixels_per_point());
+    emote.still_image_url(ui.visuals().dark_mode, scale)
+}
+
+/// Draws an emote's image, or its name in a bordered box if the image failed to
+/// load (e.g. a CDN error or a third-party emote that has since been deleted), so
+/// the message stays readable instead of showing a broken-image glyph.
+///
+/// The returned response is clickable either way; callers add their own hover text.
+pub fn draw_emote_image(
+    ui: &mut egui::Ui,
+    url: String,
+    name: &str,
+    size: f32,
+    tint: Option<Color32>,
+) -> Response {
+    let size = Vec2::splat(size);
+    let mut image = Image::new(url).max_size(size);
+    if let Some(tint) = tint {
+        image = image.tint(tint);
+    }
+
+    match image.load_for_size(ui.ctx(), size) {
+        Ok(_) => ui.add(image.sense(Sense::click())),
+        Err(_) => draw_placeholder(ui, name, tint),
+    }
+}
+
+/// How big emotes are drawn in their hover preview, Twitch's largest size.
+const PREVIEW_SIZE: f32 = 112.0;
+
+/// The hover preview for an emote: its largest image above `caption`.
+pub fn draw_emote_preview(ui: &mut egui::Ui, emote: &Emote, caption: &str) {
+    ui.add(
+        Image::new(emote.preview_url(ui.visuals().dark_mode)).max_size(Vec2::splat(PREVIEW_SIZE)),
+    );
+    ui.label(caption);
+}
+
+fn draw_placeholder(ui: &mut egui::Ui, name: &str, tint: Option<Color32>) -> Response {
+    let color = tint.unwrap_or_else(|| ui.visuals().weak_text_color());
+    Frame::new()
+        .stroke((1.0, color))
+        .corner_radius(2)
+        .inner_margin(Margin::symmetric(3, 0))
+        .show(ui, |ui| {
+            ui.label(RichText::new(name).small().color(color));
+        })
+        .response
+        .interact(Sense::click())
+}
@@ -1,32 +1,215 @@
 use crate::app::config::Config;
 use crate::app::state::AppState;
-use eframe::egui::{self, Image, ScrollArea, Vec2};
+use crate::emotes::twitch_api::TwitchEmote;
+use crate::models::emote::{Emote, EmoteSource};
+use crate::ui::chat::{emote_image, empty_state};
+use eframe::egui::{self, RichText, ScrollArea};
 
-pub fn draw_emote_picker(ui: &mut egui::Ui, state: &mut AppState, config: &Config) {
+const MAX_RECENT_EMOTES: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EmotePickerTab {
+    Recent,
+    Channel,
+    #[default]
+    Global,
+    Bttv,
+    Ffz,
+    Stv,
+}
+
+impl EmotePickerTab {
+    const ALL: [EmotePickerTab; 6] = [
+        EmotePickerTab::Recent,
+        EmotePickerTab::Channel,
+        EmotePickerTab::Global,
+        EmotePickerTab::Bttv,
+        EmotePickerTab::Ffz,
+        EmotePickerTab::Stv,
+    ];
+
+    /// The emote source a tab is limited to, if any.
+    fn source(&self) -> Option<EmoteSource> {
+        match self {
+            EmotePickerTab::Recent => None,
+            EmotePickerTab::Channel | EmotePickerTab::Global => Some(EmoteSource::Twitch),
+            EmotePickerTab::Bttv => Some(EmoteSource::Bttv),
+            EmotePickerTab::Ffz => Some(EmoteSource::Ffz),
+            EmotePickerTab::Stv => Some(EmoteSource::Stv),
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            EmotePickerTab::Recent => "Recent",
+            EmotePickerTab::Channel => "Channel",
+            EmotePickerTab::Global => "Global",
+            EmotePickerTab::Bttv => "BTTV",
+            EmotePickerTab::Ffz => "FFZ",
+            EmotePickerTab::Stv => "7TV",
+        }
+    }
+}
+
+/// Picker UI state that outlives a single frame.
+#[derive(Default)]
+pub struct EmotePickerState {
+    pub tab: EmotePickerTab,
+    pub search: String,
+    /// Names of emotes picked this session, most recent first.
+    pub recent: Vec<String>,
+}
+
+impl EmotePickerState {
+    fn remember(&mut self, name: &str) {
+        self.recent.retain(|n| n != name);
+        self.recent.insert(0, name.to_string());
+        self.recent.truncate(MAX_RECENT_EMOTES);
+    }
+}
+
+pub fn draw_emote_picker(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    config: &Config,
+    picker: &mut EmotePickerState,
+) {
     if let AppState::LoggedIn {
         global_emotes,
+        global_emotes_loaded,
+        tabs,
+        active_tab,
         message_to_send,
         ..
     } = state
     {
+        let (channel_emotes, third_party_emotes) =
+            tabs.get(*active_tab).map_or((&[][..], &[][..]), |tab| {
+                (
+                    tab.channel_emotes.as_slice(),
+                    tab.third_party_emotes.as_slice(),
+                )
+            });
         ui.heading("Emotes");
+        if !*global_emotes_loaded {
+            empty_state::draw_empty_state(ui, "Loading emotes…", true);
+            return;
+        }
 
-        ScrollArea::vertical().show(ui, |ui| {
-            ui.horizontal_wrapped(|ui| {
-                for emote in global_emotes.iter() {
-                    let size = Vec2::new(config.emote_size, config.emote_size);
-                    let image = Image::new(emote.images.url_1x.as_str()).max_size(size);
-
-                    let response = ui
-                        .add(image.sense(egui::Sense::click()))
-                        .on_hover_text(format!("{} - {}", emote.name, "Twitch"));
+        let to_emotes = |emotes: &[TwitchEmote]| -> Vec<Emote> {
+            emotes
+                .iter()
+                .map(|e| e.to_emote())
+                .filter(|e| config.emote_source_enabled(&e.source))
+                .collect()
+        };
+        let channel = to_emotes(channel_emotes);
+        let global = to_emotes(global_emotes);
+        let third_party = third_party_emotes
+            .iter()
+            .filter(|e| config.emote_source_enabled(&e.source));
+        // Channel emotes come first so they lead search results.
+        let all_emotes: Vec<Emote> = channel
+            .iter()
+            .chain(&global)
+            .chain(third_party)
+            .cloned()
+            .collect();
 
-                    if response.clicked() {
-                        message_to_send.push_str(&emote.name);
-                        message_to_send.push(' ');
+        ui.add(
+            egui::TextEdit::singleline(&mut picker.search)
+                .hint_text("Search all emotes...")
+                .desired_width(f32::INFINITY),
+        );
+        let searching = !picker.search.trim().is_empty();
+        ui.add_enabled_ui(!searching, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for tab in EmotePickerTab::ALL {
+                    if tab.source().is_none_or(|s| config.emote_source_enabled(&s)) {
+                        ui.selectable_value(&mut picker.tab, tab, tab.label());
                     }
                 }
             });
         });
+        ui.separator();
+
+        let shown: Vec<&Emote> = if searching {
+            let query = picker.search.trim().to_lowercase();
+            all_emotes
+                .iter()
+                .filter(|e| e.name.to_lowercase().contains(&query))
+                .collect()
+        } else {
+            match picker.tab {
+                EmotePickerTab::Recent => picker
+                    .recent
+                    .iter()
+                    .filter_map(|name| all_emotes.iter().find(|e| &e.name == name))
+                    .collect(),
+                EmotePickerTab::Channel => channel.iter().collect(),
+                EmotePickerTab::Global => global.iter().collect(),
+                tab => {
+                    let source = tab.source();
+                    all_emotes
+                        .iter()
+                        .filter(|e| source.as_ref() == Some(&e.source))
+                        .collect()
+                }
+            }
+        };
+
+        if shown.is_empty() {
+            let message = if searching {
+                "No emotes match your search."
+            } else if picker.tab == EmotePickerTab::Recent {
+                "Emotes you pick will show up here."
+            } else if picker.tab == EmotePickerTab::Channel {
+                "This channel has no emotes of its own."
+            } else {
+                "No emotes from this source."
+            };
+            empty_state::draw_empty_state(ui, message, false);
+            return;
+        }
+
+        ui.label(RichText::new(format!("{} emotes", shown.len())).weak());
+
+        // Lay the emotes out in a fixed grid so only the rows scrolled into view
+        // create images; large channels can have thousands of emotes.
+        let spacing = ui.spacing().item_spacing;
+        let cell = config.emote_size + spacing.x;
+        let columns = ((ui.available_width() + spacing.x) / cell).floor().max(1.0) as usize;
+        let total_rows = shown.len().div_ceil(columns);
+        let mut picked = None;
+        ScrollArea::vertical()
+            .id_salt(("emote_picker_scroll", picker.tab, searching))
+            .show_rows(ui, config.emote_size, total_rows, |ui, rows| {
+                for row in rows {
+                    let start = row * columns;
+                    let end = (start + columns).min(shown.len());
+                    ui.horizontal(|ui| {
+                        for emote in &shown[start..end] {
+                            let response = emote_image::draw_emote_image(
+                                ui,
+                                emote_image::emote_url_for_size(ui, emote, config.emote_size),
+                                &emote.name,
+                                config.emote_size,
+                                None,
+                            )
+                            .on_hover_text(format!("{} - {:?}", emote.name, emote.source));
+
+                            if response.clicked() {
+                                picked = Some(emote.name.clone());
+                            }
+                        }
+                    });
+                }
+            });
+
+        if let Some(name) = picked {
+            message_to_send.push_str(&name);
+            message_to_send.push(' ');
+            picker.remember(&name);
+        }
     }
 }
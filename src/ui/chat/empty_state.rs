@@ -0,0 +1,13 @@
+use eframe::egui::{self, RichText};
+
+/// A centered placeholder for panels that have nothing to show yet, so an empty
+/// panel reads as "waiting" or "nothing here" rather than broken.
+pub fn draw_empty_state(ui: &mut egui::Ui, message: &str, loading: bool) {
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height().min(200.0) / 3.0);
+        if loading {
+            ui.spinner();
+        }
+        ui.label(RichText::new(message).weak());
+    });
+}
@@ -0,0 +1,183 @@
+//! The decisions behind how a chat row looks, kept apart from the drawing so they
+//! can be checked without an egui context.
+
+use crate::{
+    app::config::{Config, OwnMessageStyle},
+    models::message::{ChatMessage, MessageFragment, MessageKind, SystemCategory},
+};
+use eframe::egui::Color32;
+
+/// Twitch's default name colors, one of which is given to chatters who never
+/// picked their own.
+pub const DEFAULT_USER_COLORS: [Color32; 15] = [
+    Color32::from_rgb(0xff, 0x00, 0x00),
+    Color32::from_rgb(0x00, 0x00, 0xff),
+    Color32::from_rgb(0x00, 0x80, 0x00),
+    Color32::from_rgb(0xb2, 0x22, 0x22),
+    Color32::from_rgb(0xff, 0x7f, 0x50),
+    Color32::from_rgb(0x9a, 0xcd, 0x32),
+    Color32::from_rgb(0xff, 0x45, 0x00),
+    Color32::from_rgb(0x2e, 0x8b, 0x57),
+    Color32::from_rgb(0xda, 0xa5, 0x20),
+    Color32::from_rgb(0xd2, 0x69, 0x1e),
+    Color32::from_rgb(0x5f, 0x9e, 0xa0),
+    Color32::from_rgb(0x1e, 0x90, 0xff),
+    Color32::from_rgb(0xff, 0x69, 0xb4),
+    Color32::from_rgb(0x8a, 0x2b, 0xe2),
+    Color32::from_rgb(0x00, 0xff, 0x7f),
+];
+/// Consecutive messages from one sender are grouped only if sent this close together.
+const GROUP_WINDOW_SECS: i64 = 60;
+
+/// The background treatment of a whole row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowHighlight {
+    None,
+    /// A moderator is looking at a message from a user flagged as suspicious.
+    LowTrust,
+    /// A message from one of the user's own accounts.
+    Own(OwnMessageStyle),
+    /// Someone else's message containing one of the highlight keywords.
+    Keyword,
+}
+
+/// The color to draw `name` in: the one pinned to them in the settings, their own
+/// chat color, or the accent color for those who never picked one, if set.
+pub fn resolve_sender_color(name: &str, color: Option<(u8, u8, u8)>, config: &Config) -> Color32 {
+    if let Some([r, g, b]) = config.user_colors.get(&name.to_lowercase()) {
+        return Color32::from_rgb(*r, *g, *b);
+    }
+    match (color, config.accent_color) {
+        (None, Some([r, g, b])) => Color32::from_rgb(r, g, b),
+        _ => color_for_user(name, color),
+    }
+}
+
+/// `stored_color` if the user picked one, otherwise one of
+/// [`DEFAULT_USER_COLORS`] chosen by their name, so they keep it across messages
+/// and sessions.
+pub fn color_for_user(name: &str, stored_color: Option<(u8, u8, u8)>) -> Color32 {
+    if let Some((r, g, b)) = stored_color {
+        return Color32::from_rgb(r, g, b);
+    }
+    // FNV-1a, which unlike `DefaultHasher` gives the same result in every build.
+    let hash = name
+        .to_lowercase()
+        .bytes()
+        .fold(0x811c_9dc5_u32, |hash, byte| {
+            (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+        });
+    DEFAULT_USER_COLORS[hash as usize % DEFAULT_USER_COLORS.len()]
+}
+
+/// The color of a system message, using the user's pick for its category if any.
+pub fn resolve_system_color(category: SystemCategory, config: &Config, dark_mode: bool) -> Color32 {
+    match config.system_message_colors.get(category) {
+        Some([r, g, b]) => Color32::from_rgb(r, g, b),
+        None => default_system_color(category, dark_mode),
+    }
+}
+
+/// The color of a system message category the user hasn't customized, picked to
+/// stay readable on the current theme.
+pub fn default_system_color(category: SystemCategory, dark_mode: bool) -> Color32 {
+    let (dark, light) = match category {
+        SystemCategory::Subscription => ((190, 160, 255), (110, 60, 200)),
+        SystemCategory::Raid => ((255, 170, 90), (190, 90, 0)),
+        SystemCategory::Announcement => ((120, 190, 255), (20, 100, 180)),
+        SystemCategory::BitsBadge => ((255, 215, 90), (160, 120, 0)),
+        SystemCategory::Other => ((170, 170, 170), (90, 90, 90)),
+    };
+    let (r, g, b) = if dark_mode { dark } else { light };
+    Color32::from_rgb(r, g, b)
+}
+
+/// Whether `current` is drawn flush against the fragment before it, which only
+/// happens between two emotes when the user asked for emotes to be collapsed.
+pub fn should_collapse_spacing(
+    previous: Option<&MessageFragment>,
+    current: &MessageFragment,
+    config: &Config,
+) -> bool {
+    config.collapse_emotes
+        && matches!(previous, Some(MessageFragment::Emote(_)))
+        && matches!(current, MessageFragment::Emote(_))
+}
+
+/// Whether `message` is drawn without its sender header, as a continuation of
+/// `previous`.
+pub fn should_group(
+    previous: Option<&ChatMessage>,
+    message: &ChatMessage,
+    config: &Config,
+) -> bool {
+    config.group_consecutive && previous.is_some_and(|prev| continues(prev, message))
+}
+
+/// Whether `message` carries on from `prev` closely enough to skip its sender header.
+pub fn continues(prev: &ChatMessage, message: &ChatMessage) -> bool {
+    prev.kind == MessageKind::Chat
+        && message.kind == MessageKind::Chat
+        && prev.sender_name == message.sender_name
+        && (message.timestamp - prev.timestamp).num_seconds() < GROUP_WINDOW_SECS
+}
+
+/// The low-trust warning only shows with mod tools, and takes priority over the
+/// own-message highlight. Keywords never highlight the user's own messages.
+pub fn row_highlight(
+    message: &ChatMessage,
+    config: &Config,
+    show_mod_tools: bool,
+    own_user_ids: &[&str],
+) -> RowHighlight {
+    let own = own_user_ids.contains(&message.sender_id.as_str());
+    if show_mod_tools && message.low_trust.is_some() {
+        RowHighlight::LowTrust
+    } else if config.highlight_own_messages && own {
+        RowHighlight::Own(config.own_message_style)
+    } else if !own && matches_keywords(message, &config.highlight_keywords) {
+        RowHighlight::Keyword
+    } else {
+        RowHighlight::None
+    }
+}
+
+/// Whether the text or a mention in `message` contains one of `keywords`, ignoring
+/// case. Blank keywords match nothing.
+pub fn matches_keywords(message: &ChatMessage, keywords: &[String]) -> bool {
+    let keywords: Vec<String> = keywords
+        .iter()
+        .map(|keyword| keyword.trim().to_lowercase())
+        .filter(|keyword| !keyword.is_empty())
+        .collect();
+    if keywords.is_empty() {
+        return false;
+    }
+    message.fragments.iter().any(|fragment| {
+        let text = match fragment {
+            MessageFragment::Text(text) | MessageFragment::Mention(text) => text.to_lowercase(),
+            _ => return false,
+        };
+        keywords
+            .iter()
+            .any(|keyword| text.contains(keyword.as_str()))
+    })
+}
+
+/// Every row starts at the height of its tallest element so that text is aligned
+/// against emotes from the first fragment onwards, rather than only after an emote
+/// has stretched the row. Rows without emote images keep to the text height instead
+/// of reserving space for emotes they don't have.
+pub fn row_height(message: &ChatMessage, config: &Config, text_height: f32) -> f32 {
+    let has_emote_images = message.fragments.iter().any(|f| match f {
+        MessageFragment::Emote(emote) | MessageFragment::Cheermote { emote, .. } => {
+            config.emote_source_enabled(&emote.source)
+        }
+        _ => false,
+    });
+    if has_emote_images {
+        config.emote_size.max(text_height)
+    } else {
+        text_height
+    }
+}
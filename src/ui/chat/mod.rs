@@ -1,4 +1,10 @@
+pub mod autocomplete;
+pub mod automod_queue;
 pub mod chat_bar;
 pub mod chat_log;
+pub mod emote_image;
 pub mod emote_picker;
+pub mod empty_state;
+pub mod message_style;
+pub mod redemption_queue;
 pub mod user_list;
@@ -0,0 +1,67 @@
+use crate::{
+    app::{
+        config::Config,
+        state::{AppState, ChannelTab},
+    },
+    features::channel_points::Redemption,
+};
+use eframe::egui::{self, Color32, RichText, ScrollArea};
+
+#[derive(Debug)]
+pub enum RedemptionAction {
+    Fulfill(Redemption),
+    Reject(Redemption),
+}
+
+pub fn draw_redemption_queue(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    config: &Config,
+) -> Option<RedemptionAction> {
+    let mut action = None;
+
+    if let Some(ChannelTab { redemptions, .. }) = state.active_tab() {
+        ui.heading("Redemptions");
+        ScrollArea::vertical()
+            .id_salt("redemption_queue_scroll_area")
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                if redemptions.is_empty() {
+                    ui.label(RichText::new("No pending redemptions.").italics());
+                }
+
+                for redemption in redemptions.iter() {
+                    ui.group(|ui| {
+                        ui.label(
+                            RichText::new(format!(
+                                "{} ({})",
+                                redemption.reward_title, redemption.reward_cost
+                            ))
+                            .strong(),
+                        );
+                        ui.label(format!(
+                            "{} at {}",
+                            redemption.user_name,
+                            config.timezone.format(&redemption.redeemed_at, "%H:%M")
+                        ));
+                        if !redemption.user_input.is_empty() {
+                            ui.label(
+                                RichText::new(&redemption.user_input)
+                                    .color(Color32::from_gray(200)),
+                            );
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button("Fulfill").clicked() {
+                                action = Some(RedemptionAction::Fulfill(redemption.clone()));
+                            }
+                            if ui.button("Reject").clicked() {
+                                action = Some(RedemptionAction::Reject(redemption.clone()));
+                            }
+                        });
+                    });
+                }
+            });
+    }
+
+    action
+}
@@ -1,9 +1,16 @@
-use crate::app::state::AppState;
-use eframe::egui::{self, ScrollArea};
+use crate::{
+    app::state::{AppState, ChannelTab},
+    ui::chat::empty_state,
+};
+use eframe::egui::{self, RichText, ScrollArea};
 
 pub fn draw_user_list(ui: &mut egui::Ui, state: &mut AppState) {
-    if let AppState::LoggedIn { users, .. } = state {
-        ui.heading("Users");
+    if let Some(ChannelTab { users, .. }) = state.active_tab() {
+        ui.heading(format!("Users ({})", users.len()));
+        if users.is_empty() {
+            empty_state::draw_empty_state(ui, "No one has chatted yet.", false);
+            return;
+        }
         ScrollArea::vertical()
             .auto_shrink([false, false])
             .show(ui, |ui| {
@@ -11,7 +18,12 @@ pub fn draw_user_list(ui: &mut egui::Ui, state: &mut AppState) {
                 sorted_users.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
 
                 for user in sorted_users {
-                    ui.label(&user.name);
+                    if user.has_spoken {
+                        ui.label(&user.name);
+                    } else {
+                        ui.label(RichText::new(&user.name).weak())
+                            .on_hover_text("In chat, but hasn't said anything yet");
+                    }
                 }
             });
     }
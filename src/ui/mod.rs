@@ -1,6 +1,8 @@
 pub mod app_layout;
 pub mod channel_list;
+pub mod channel_switcher;
 pub mod chat;
 pub mod profiles;
+pub mod replay;
 pub mod status_bar;
 pub mod toolbar;
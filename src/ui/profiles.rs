@@ -6,6 +6,9 @@ pub enum ProfileManagerAction {
     Login(String),
     Add(String),
     Remove(String),
+    /// Load this profile's token to send messages as, without logging out.
+    AddIdentity(String),
+    RemoveIdentity(String),
 }
 
 pub fn draw_profiles_ui(
@@ -15,6 +18,8 @@ pub fn draw_profiles_ui(
     new_profile_name: &mut String,
     profile_to_remove_name: &mut String,
     error: &Option<String>,
+    // Profiles currently loaded as extra chat identities; `None` when not logged in.
+    identity_profiles: Option<&[String]>,
 ) -> Option<ProfileManagerAction> {
     let mut action = None;
 
@@ -36,6 +41,20 @@ pub fn draw_profiles_ui(
                 if ui.button("Switch").clicked() {
                     action = Some(ProfileManagerAction::Login(profile.name.clone()));
                 }
+                if let Some(identities) = identity_profiles {
+                    if identities.contains(&profile.name) {
+                        if ui.button("Stop Chatting As").clicked() {
+                            action =
+                                Some(ProfileManagerAction::RemoveIdentity(profile.name.clone()));
+                        }
+                    } else if ui
+                        .button("Chat As")
+                        .on_hover_text("Send messages as this profile while staying logged in")
+                        .clicked()
+                    {
+                        action = Some(ProfileManagerAction::AddIdentity(profile.name.clone()));
+                    }
+                }
             }
         });
     }
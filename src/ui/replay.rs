@@ -0,0 +1,149 @@
+use crate::{
+    app::config::Config,
+    core::replay,
+    models::message::ChatMessage,
+    ui::chat::chat_log::{self, MessageAction},
+};
+use chrono::Duration;
+use eframe::egui::{self, RichText, ScrollArea};
+use std::path::PathBuf;
+use tokio::sync::oneshot;
+
+/// State for the read-only replay window. It lives beside the live chat state so a
+/// past log can be reviewed without leaving the current channel.
+#[derive(Default)]
+pub struct ReplayState {
+    pub path_input: String,
+    pub messages: Vec<ChatMessage>,
+    /// Seconds after the first message up to which messages are revealed.
+    pub position_secs: f64,
+    pub error: Option<String>,
+    pending_load: Option<oneshot::Receiver<Result<Vec<ChatMessage>, String>>>,
+}
+
+impl ReplayState {
+    fn start_loading(&mut self) {
+        let path = PathBuf::from(self.path_input.trim());
+        let (tx, rx) = oneshot::channel();
+        self.pending_load = Some(rx);
+        self.error = None;
+        tokio::spawn(async move {
+            let result = replay::load_log(&path).await.map_err(|e| e.to_string());
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll_loading(&mut self) {
+        let Some(rx) = self.pending_load.as_mut() else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(messages)) => {
+                self.messages = messages;
+                self.position_secs = self.duration_secs();
+                self.pending_load = None;
+            }
+            Ok(Err(e)) => {
+                self.error = Some(e);
+                self.pending_load = None;
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+            Err(oneshot::error::TryRecvError::Closed) => {
+                self.error = Some("Loading the chat log was interrupted.".to_string());
+                self.pending_load = None;
+            }
+        }
+    }
+
+    fn duration_secs(&self) -> f64 {
+        match (self.messages.first(), self.messages.last()) {
+            (Some(first), Some(last)) => {
+                (last.timestamp - first.timestamp).num_milliseconds() as f64 / 1000.0
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+pub fn draw_replay_window(
+    ctx: &egui::Context,
+    replay: &mut ReplayState,
+    config: &Config,
+    open: &mut bool,
+) -> Option<MessageAction> {
+    replay.poll_loading();
+    let mut action = None;
+
+    egui::Window::new("Chat Replay")
+        .open(open)
+        .default_size([480.0, 520.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Log file:");
+                let response = ui.text_edit_singleline(&mut replay.path_input);
+                let enter_pressed =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let can_load =
+                    !replay.path_input.trim().is_empty() && replay.pending_load.is_none();
+                if (ui
+                    .add_enabled(can_load, egui::Button::new("Load"))
+                    .clicked()
+                    || enter_pressed)
+                    && can_load
+                {
+                    replay.start_loading();
+                }
+                if replay.pending_load.is_some() {
+                    ui.spinner();
+                }
+            });
+
+            if let Some(err) = &replay.error {
+                ui.colored_label(egui::Color32::RED, err);
+            }
+
+            let Some(first) = replay.messages.first() else {
+                ui.label(RichText::new("Load a chat log to replay it.").italics());
+                return;
+            };
+            let start = first.timestamp;
+            let duration = replay.duration_secs();
+
+            ui.add(
+                egui::Slider::new(&mut replay.position_secs, 0.0..=duration)
+                    .show_value(false)
+                    .text("Timeline"),
+            );
+            let cutoff = start + Duration::milliseconds((replay.position_secs * 1000.0) as i64);
+            let visible = replay
+                .messages
+                .iter()
+                .take_while(|m| m.timestamp <= cutoff)
+                .count();
+            ui.label(format!(
+                "{} — {} of {} messages",
+                config.timezone.format(&cutoff, "%Y-%m-%d %H:%M:%S"),
+                visible,
+                replay.messages.len()
+            ));
+            ui.separator();
+
+            ScrollArea::vertical()
+                .id_salt("replay_scroll_area")
+                .stick_to_bottom(true)
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    action = chat_log::draw_messages(
+                        ui,
+                        replay.messages[..visible].iter(),
+                        config,
+                        false,
+                        None,
+                        &[],
+                        true,
+                        false,
+                    );
+                });
+        });
+    action
+}
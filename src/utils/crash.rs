@@ -0,0 +1,76 @@
+use std::{backtrace::Backtrace, fs, panic, path::PathBuf, thread};
+
+/// Logs every panic with a backtrace and writes a crash report next to
+/// `livenac.log`. A panic on the main (UI) thread takes the app down, so it also
+/// gets a native error dialog pointing at the report; panics in background tasks
+/// are only recorded, since the app keeps running.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let thread = thread::current();
+        let thread_name = thread.name().unwrap_or("<unnamed>");
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        let backtrace = Backtrace::force_capture();
+
+        tracing::error!(
+            "Thread '{}' panicked at {}: {}\n{}",
+            thread_name,
+            location,
+            message,
+            backtrace
+        );
+
+        let report = format!(
+            "LiveNAC {} crashed at {}\n\nThread '{}' panicked at {}:\n{}\n\nBacktrace:\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            chrono::Local::now().to_rfc3339(),
+            thread_name,
+            location,
+            message,
+            backtrace
+        );
+        let report_path = write_crash_report(&report);
+
+        if thread_name == "main" {
+            let where_to_look = match &report_path {
+                Some(path) => format!("A crash report was saved to {}.", path.display()),
+                None => "Details were written to livenac.log.".to_string(),
+            };
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("LiveNAC crashed")
+                .set_description(format!(
+                    "LiveNAC ran into an unexpected error and has to close.\n\n{}\n\n{}",
+                    message, where_to_look
+                ))
+                .set_buttons(rfd::MessageButtons::Ok)
+                .show();
+        }
+
+        default_hook(info);
+    }));
+}
+
+/// Writes `report` to a timestamped file in the working directory, alongside the log.
+fn write_crash_report(report: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(format!(
+        "livenac-crash-{}.txt",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    match fs::write(&path, report) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            tracing::error!("Failed to write crash report {}: {}", path.display(), e);
+            None
+        }
+    }
+}
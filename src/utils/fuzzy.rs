@@ -0,0 +1,95 @@
+//! Fuzzy subsequence matching used to rank autocomplete candidates.
+//!
+//! The scoring follows the same idea as skim's matcher: every query character must
+//! appear in order in the candidate, consecutive runs and matches at word starts are
+//! rewarded, and gaps are penalised. It's a single linear pass per candidate so it
+//! stays cheap enough to run on every keystroke over a few thousand emote names.
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_CONSECUTIVE: i64 = 24;
+const BONUS_FIRST_CHAR: i64 = 32;
+const BONUS_WORD_START: i64 = 20;
+const BONUS_CASE_MATCH: i64 = 2;
+const PENALTY_GAP: i64 = 3;
+
+/// Scores `candidate` against `query`, or returns `None` if the query's characters
+/// don't all appear in order. Matching is case-insensitive; higher is better.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate_chars: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut search_from = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.chars() {
+        let offset = candidate_chars[search_from..]
+            .iter()
+            .position(|c| chars_eq_ignore_case(*c, query_char))?;
+        let index = search_from + offset;
+        let candidate_char = candidate_chars[index];
+
+        score += SCORE_MATCH;
+        if candidate_char == query_char {
+            score += BONUS_CASE_MATCH;
+        }
+        if index == 0 {
+            score += BONUS_FIRST_CHAR;
+        } else if is_word_start(candidate_chars[index - 1], candidate_char) {
+            score += BONUS_WORD_START;
+        }
+        match previous_match {
+            Some(previous) if previous + 1 == index => score += BONUS_CONSECUTIVE,
+            Some(previous) => score -= PENALTY_GAP * (index - previous - 1) as i64,
+            None => score -= PENALTY_GAP * index as i64,
+        }
+
+        previous_match = Some(index);
+        search_from = index + 1;
+    }
+
+    // Prefer shorter candidates when everything else is equal.
+    score -= (candidate_chars.len() - search_from) as i64;
+    Some(score)
+}
+
+/// Returns up to `limit` candidates matching `query`, best first. Ties keep prefix
+/// matches ahead of the rest and otherwise fall back to alphabetical order.
+pub fn rank<'a, I>(query: &str, candidates: I, limit: usize) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let query_lower = query.to_lowercase();
+    let mut scored: Vec<(i64, bool, &'a str)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let score = fuzzy_score(query, candidate)?;
+            let is_prefix = candidate.to_lowercase().starts_with(&query_lower);
+            Some((score, is_prefix, candidate))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(b.1.cmp(&a.1))
+            .then_with(|| a.2.to_lowercase().cmp(&b.2.to_lowercase()))
+    });
+    scored.dedup_by(|a, b| a.2 == b.2);
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+fn is_word_start(previous: char, current: char) -> bool {
+    !previous.is_alphanumeric()
+        || (previous.is_lowercase() && current.is_uppercase())
+        || (previous.is_alphabetic() && current.is_numeric())
+}
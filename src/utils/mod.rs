@@ -1,2 +1,4 @@
+pub mod crash;
+pub mod fuzzy;
 pub mod text_processing;
 pub mod time;
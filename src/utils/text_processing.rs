@@ -1,5 +1,24 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Which text in chat messages is turned into clickable links.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum AutolinkMode {
+    Off,
+    /// Only links written out with `http://` or `https://`.
+    HttpOnly,
+    /// Also bare domains like `example.com/page` and `www.example.com`.
+    #[default]
+    All,
+}
+
+/// Endings that make a bare `name.ext/...` a file or package name rather than a domain.
+const FILE_EXTENSIONS: &[&str] = &[
+    "js", "ts", "rs", "py", "rb", "go", "cpp", "java", "json", "toml", "yaml", "yml", "txt", "md",
+    "exe", "dll", "zip", "rar", "png", "jpg", "jpeg", "gif", "mp3", "mp4", "mkv", "log", "cfg",
+    "ini", "bat", "sh",
+];
 
 #[derive(Debug, PartialEq)]
 pub enum TextOrUrl {
@@ -11,11 +30,14 @@ static URL_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r#"(?i)\b((?:https?://|www\d{0,3}[.]|[a-z0-9.\-]+[.][a-z]{2,4}/)(?:[^\s()<>]+|\(([^\s()<>]+|(\([^\s()<>]+\)))*\))+(?:\(([^\s()<>]+|(\([^\s()<>]+\)))*\)|[^\s`!()\[\]{};:'".,<>?«»“”‘’]))"#).unwrap()
 });
 
-pub fn parse_text_for_urls(text: &str) -> Vec<TextOrUrl> {
+pub fn parse_text_for_urls(text: &str, mode: AutolinkMode) -> Vec<TextOrUrl> {
     let mut result = Vec::new();
     let mut last_end = 0;
 
-    for mat in URL_REGEX.find_iter(text) {
+    let matches = URL_REGEX
+        .find_iter(text)
+        .filter(|mat| mode != AutolinkMode::Off && is_link(mat.as_str(), mode));
+    for mat in matches {
         if mat.start() > last_end {
             result.push(TextOrUrl::Text(text[last_end..mat.start()].to_string()));
         }
@@ -33,3 +55,79 @@ pub fn parse_text_for_urls(text: &str) -> Vec<TextOrUrl> {
 
     result
 }
+
+fn has_scheme(candidate: &str) -> bool {
+    let lower = candidate.to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Post-filters regex matches, which are deliberately generous, so only text that is
+/// plainly meant as a link becomes one.
+fn is_link(candidate: &str, mode: AutolinkMode) -> bool {
+    if has_scheme(candidate) {
+        return true;
+    }
+    if mode == AutolinkMode::HttpOnly {
+        return false;
+    }
+    let lower = candidate.to_ascii_lowercase();
+    if lower.starts_with("www") {
+        return true;
+    }
+    let host = lower.split('/').next().unwrap_or_default();
+    let tld = host.rsplit('.').next().unwrap_or_default();
+    tld.chars().all(|c| c.is_ascii_alphabetic()) && !FILE_EXTENSIONS.contains(&tld)
+}
+
+static CLIP_URL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)https?://(?:clips\.twitch\.tv/[\w-]+|(?:www\.|m\.)?twitch\.tv/\w+/clip/[\w-]+)",
+    )
+    .unwrap()
+});
+
+/// Returns the first Twitch clip link in the text, if any.
+pub fn find_clip_url(text: &str) -> Option<&str> {
+    CLIP_URL_REGEX.find(text).map(|m| m.as_str())
+}
+
+/// Returns at most the first `max_chars` characters of `text`.
+pub fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// Returns true if the chat log would render any of `text` as a link under `mode`.
+pub fn contains_url(text: &str, mode: AutolinkMode) -> bool {
+    parse_text_for_urls(text, mode)
+        .iter()
+        .any(|segment| matches!(segment, TextOrUrl::Url(_)))
+}
+
+/// Cleans up text before it is sent to chat.
+///
+/// Control characters are dropped (tabs and newlines become spaces), along with the
+/// invisible zero-width and bidi-override characters commonly used to dodge filters.
+/// The zero-width joiner is kept since emoji sequences depend on it.
+pub fn sanitize_input(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\t' | '\n' | '\r' => Some(' '),
+            c if c.is_control() => None,
+            '\u{200B}' | '\u{200C}' | '\u{2060}' | '\u{FEFF}' | '\u{180E}' | '\u{00AD}' => None,
+            '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+/// The non-blank lines of `text`, each cleaned up with [`sanitize_input`], for
+/// sending one message per line.
+pub fn split_message_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(sanitize_input)
+        .filter(|line| !line.trim().is_empty())
+        .collect()
+}
@@ -1 +1,99 @@
+use chrono::{
+    DateTime, Local, TimeZone, Utc,
+    format::{Item, StrftimeItems},
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
 
+/// The strftime pattern chat timestamps use unless the config sets another.
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%H:%M:%S";
+/// Patterns offered in the settings next to the free-form input.
+pub const TIMESTAMP_PRESETS: [(&str, &str); 2] = [
+    ("24-hour", DEFAULT_TIMESTAMP_FORMAT),
+    ("12-hour", "%I:%M:%S %p"),
+];
+
+/// Whether chrono can format timestamps with `pattern`. Formatting with an invalid
+/// pattern panics, so patterns from the user must pass this first.
+pub fn is_valid_format(pattern: &str) -> bool {
+    !pattern.is_empty() && StrftimeItems::new(pattern).all(|item| !matches!(item, Item::Error))
+}
+
+/// `pattern`, or [`DEFAULT_TIMESTAMP_FORMAT`] when it isn't valid.
+pub fn valid_format_or_default(pattern: &str) -> &str {
+    if is_valid_format(pattern) {
+        pattern
+    } else {
+        DEFAULT_TIMESTAMP_FORMAT
+    }
+}
+
+/// The timezone timestamps are shown in. Messages always keep the instant they
+/// were sent; this only affects how that instant is formatted.
+///
+/// Stored in the config as `"Local"`, `"UTC"` or an IANA name like `"Europe/Berlin"`.
+/// An unknown name falls back to `Local` rather than failing the whole config load.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(from = "String", into = "String")]
+pub enum DisplayTimezone {
+    #[default]
+    Local,
+    Utc,
+    Named(Tz),
+}
+
+impl DisplayTimezone {
+    pub fn format<T: TimeZone>(&self, timestamp: &DateTime<T>, fmt: &str) -> String
+    where
+        T::Offset: fmt::Display,
+    {
+        match self {
+            DisplayTimezone::Local => timestamp.with_timezone(&Local).format(fmt).to_string(),
+            DisplayTimezone::Utc => timestamp.with_timezone(&Utc).format(fmt).to_string(),
+            DisplayTimezone::Named(tz) => timestamp.with_timezone(tz).format(fmt).to_string(),
+        }
+    }
+}
+
+impl FromStr for DisplayTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("local") {
+            Ok(DisplayTimezone::Local)
+        } else if s.eq_ignore_ascii_case("utc") {
+            Ok(DisplayTimezone::Utc)
+        } else {
+            s.parse::<Tz>()
+                .map(DisplayTimezone::Named)
+                .map_err(|_| format!("Unknown timezone '{}'", s))
+        }
+    }
+}
+
+impl From<String> for DisplayTimezone {
+    fn from(s: String) -> Self {
+        s.parse().unwrap_or_else(|e| {
+            tracing::warn!("{}, showing local time instead", e);
+            DisplayTimezone::Local
+        })
+    }
+}
+
+impl From<DisplayTimezone> for String {
+    fn from(tz: DisplayTimezone) -> Self {
+        tz.to_string()
+    }
+}
+
+impl fmt::Display for DisplayTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayTimezone::Local => write!(f, "Local"),
+            DisplayTimezone::Utc => write!(f, "UTC"),
+            DisplayTimezone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
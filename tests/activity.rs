@@ -0,0 +1,42 @@
+use livenac::features::activity::ActivityMeter;
+use std::time::{Duration, Instant};
+
+fn burst(meter: &mut ActivityMeter, start: Instant, count: u32) {
+    for i in 0..count {
+        meter.record(start + Duration::from_millis(u64::from(i)));
+    }
+}
+
+#[test]
+fn old_messages_stop_counting() {
+    let start = Instant::now();
+    let mut meter = ActivityMeter::default();
+    burst(&mut meter, start, 50);
+
+    assert_eq!(
+        meter.messages_per_second(start + Duration::from_secs(1)),
+        10.0
+    );
+    assert_eq!(
+        meter.messages_per_second(start + Duration::from_secs(10)),
+        0.0
+    );
+}
+
+#[test]
+fn animations_resume_only_once_chat_calms_down() {
+    let start = Instant::now();
+    let mut meter = ActivityMeter::default();
+    burst(&mut meter, start, 100);
+    assert!(meter.update_animations_paused(start + Duration::from_secs(1), 15.0));
+
+    // 14/s is below the threshold but not calm enough to resume yet.
+    let mut meter = ActivityMeter::default();
+    burst(&mut meter, start, 100);
+    meter.update_animations_paused(start, 15.0);
+    let later = start + Duration::from_secs(6);
+    burst(&mut meter, later, 70);
+    assert!(meter.update_animations_paused(later, 15.0));
+
+    assert!(!meter.update_animations_paused(later + Duration::from_secs(10), 15.0));
+}
@@ -0,0 +1,82 @@
+use livenac::{
+    ui::chat::autocomplete::{mention_matches, replace_word, suggestions, word_at},
+    utils::fuzzy::{fuzzy_score, rank},
+};
+
+#[test]
+fn the_word_runs_from_the_last_space_to_the_cursor() {
+    let word = word_at("hello Kapp there", 9).unwrap();
+    assert_eq!(word.typed, "Kap");
+    assert_eq!(word.range, 6..10);
+
+    assert!(word_at("hello ", 6).is_none());
+    assert_eq!(word_at("héllo wör", 9).unwrap().typed, "wör");
+}
+
+#[test]
+fn completing_replaces_the_whole_word_and_adds_a_space() {
+    let mut text = "hi Kap".to_string();
+    let cursor = replace_word(&mut text, 3..6, "Kappa");
+    assert_eq!(text, "hi Kappa ");
+    assert_eq!(cursor, 9);
+
+    let mut text = "hi Ka there".to_string();
+    let cursor = replace_word(&mut text, 3..5, "Kappa");
+    assert_eq!(text, "hi Kappa there");
+    assert_eq!(cursor, 9);
+}
+
+#[test]
+fn suggestions_need_a_started_word_with_something_left_to_complete() {
+    let emotes = ["Kappa", "KappaPride", "LUL", "PogChamp"];
+    assert_eq!(suggestions("kap", emotes), vec!["Kappa", "KappaPride"]);
+    assert!(suggestions("K", emotes).is_empty());
+    assert!(suggestions("LUL", emotes).is_empty());
+}
+
+#[test]
+fn mentions_match_chatters_by_prefix_in_the_order_given() {
+    let chatters = ["Parker", "someone", "parrot_fan", "xParx"];
+    assert_eq!(
+        mention_matches("@par", &chatters),
+        vec!["Parker", "parrot_fan"]
+    );
+    assert_eq!(mention_matches("@PARR", &chatters), vec!["parrot_fan"]);
+    assert!(mention_matches("@nobody", &chatters).is_empty());
+}
+
+#[test]
+fn a_misspelling_still_ranks_the_intended_emote_first() {
+    let names = ["PogChamp", "KappaPride", "Keepo", "Kappa", "KappaRoss"];
+    assert_eq!(rank("Kppa", names, 3), ["Kappa", "KappaRoss", "KappaPride"]);
+    assert_eq!(fuzzy_score("Kppa", "Keepo"), None);
+}
+
+#[test]
+fn equal_scores_put_prefix_matches_first_then_sort_alphabetically() {
+    // Both score the same: the word start after `_` makes up for the length.
+    assert_eq!(
+        fuzzy_score("ab", "_ab"),
+        fuzzy_score("ab", "abcdefghijklmnopq")
+    );
+    assert_eq!(
+        rank("ab", ["_ab", "abcdefghijklmnopq"], 5),
+        ["abcdefghijklmnopq", "_ab"]
+    );
+    assert_eq!(rank("ka", ["KaZZ", "KaAB"], 5), ["KaAB", "KaZZ"]);
+}
+
+#[test]
+fn ranking_thousands_of_names_keeps_only_the_limit() {
+    let names: Vec<String> = (0..5000)
+        .map(|i| format!("emote{}Name{}", i, i % 7))
+        .collect();
+    let ranked = rank("em3n", names.iter().map(String::as_str), 8);
+    assert_eq!(ranked.len(), 8);
+    let scores: Vec<_> = ranked
+        .iter()
+        .map(|name| fuzzy_score("em3n", name))
+        .collect();
+    assert!(scores.iter().all(Option::is_some));
+    assert!(scores.is_sorted_by(|a, b| a >= b));
+}
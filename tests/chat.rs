@@ -0,0 +1,43 @@
+use eyre::{Report, WrapErr, eyre};
+use livenac::core::chat::{has_status, is_unauthorized};
+use reqwest::StatusCode;
+use twitch_api::helix::{ClientRequestError, HelixRequestPostError};
+
+fn helix_error(status: StatusCode) -> Report {
+    Report::new(ClientRequestError::<reqwest::Error>::HelixRequestPostError(
+        HelixRequestPostError::Error {
+            error: status.canonical_reason().unwrap_or_default().to_string(),
+            status,
+            message: "Invalid OAuth token".to_string(),
+            uri: Default::default(),
+            body: Default::default(),
+        },
+    ))
+}
+
+#[test]
+fn a_helix_401_is_unauthorized_however_it_is_wrapped() {
+    assert!(is_unauthorized(&helix_error(StatusCode::UNAUTHORIZED)));
+    assert!(is_unauthorized(
+        &Err::<(), _>(helix_error(StatusCode::UNAUTHORIZED))
+            .wrap_err("Failed to send")
+            .unwrap_err()
+    ));
+}
+
+#[test]
+fn other_failures_are_not_unauthorized() {
+    assert!(!is_unauthorized(&helix_error(StatusCode::FORBIDDEN)));
+    // Only the status counts, not what the message happens to say.
+    assert!(!is_unauthorized(&eyre!(
+        "user said 401 Unauthorized in chat"
+    )));
+}
+
+#[test]
+fn a_refused_chatter_list_is_recognised_by_its_status() {
+    let forbidden = helix_error(StatusCode::FORBIDDEN);
+    assert!(has_status(&forbidden, StatusCode::FORBIDDEN));
+    assert!(!has_status(&forbidden, StatusCode::UNAUTHORIZED));
+    assert!(!has_status(&eyre!("403 Forbidden"), StatusCode::FORBIDDEN));
+}
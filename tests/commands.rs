@@ -0,0 +1,50 @@
+use livenac::core::commands::{ChatCommand, DEFAULT_TIMEOUT_SECS, parse_command, parse_duration};
+
+#[test]
+fn commands_parse_into_their_arguments() {
+    assert_eq!(
+        parse_command("hello /me"),
+        ChatCommand::Message("hello /me".to_string())
+    );
+    assert_eq!(
+        parse_command("/me waves"),
+        ChatCommand::Me("waves".to_string())
+    );
+    assert_eq!(
+        parse_command("/timeout @Spammer 10m stop that"),
+        ChatCommand::Timeout {
+            user: "spammer".to_string(),
+            duration_secs: 600,
+            reason: "stop that".to_string(),
+        }
+    );
+    assert_eq!(
+        parse_command("/timeout spammer stop that"),
+        ChatCommand::Timeout {
+            user: "spammer".to_string(),
+            duration_secs: DEFAULT_TIMEOUT_SECS,
+            reason: "stop that".to_string(),
+        }
+    );
+    assert_eq!(
+        parse_command("/w friend hi there"),
+        ChatCommand::Whisper {
+            recipient: "friend".to_string(),
+            message: "hi there".to_string(),
+        }
+    );
+    assert!(matches!(parse_command("/ban"), ChatCommand::Invalid(_)));
+    assert!(matches!(
+        parse_command("/frobnicate"),
+        ChatCommand::Invalid(_)
+    ));
+}
+
+#[test]
+fn durations_accept_unit_suffixes() {
+    assert_eq!(parse_duration("90"), Some(90));
+    assert_eq!(parse_duration("2h"), Some(7200));
+    assert_eq!(parse_duration("1w"), Some(604_800));
+    assert_eq!(parse_duration("10x"), None);
+    assert_eq!(parse_duration("m"), None);
+}
@@ -0,0 +1,27 @@
+//! Fixtures shared by the integration tests. Each test binary uses only some of them.
+#![allow(dead_code)]
+
+use chrono::Local;
+use livenac::models::message::{ChatMessage, MessageFragment, MessageKind};
+
+/// An ordinary chat message from `sender`, sent now. Tests override the fields they
+/// care about with struct update syntax.
+pub fn message(sender: &str, fragments: Vec<MessageFragment>) -> ChatMessage {
+    ChatMessage {
+        id: "1".to_string(),
+        sender_id: format!("id-{}", sender),
+        sender_name: sender.to_string(),
+        sender_color: None,
+        fragments,
+        timestamp: Local::now(),
+        deleted: false,
+        is_action: false,
+        kind: MessageKind::Chat,
+        low_trust: None,
+        reply_to: None,
+    }
+}
+
+pub fn text(t: &str) -> MessageFragment {
+    MessageFragment::Text(t.to_string())
+}
@@ -0,0 +1,108 @@
+use livenac::app::config::{
+    AppearanceOverrides, Config, LEGACY_CONFIG_FILE, Profile, migrate_legacy_config,
+};
+
+fn profile(name: &str, appearance: AppearanceOverrides) -> Profile {
+    Profile {
+        name: name.to_string(),
+        twitch_user_id: None,
+        open_channels: Vec::new(),
+        appearance,
+    }
+}
+
+#[test]
+fn active_profile_overrides_apply_on_top_of_global_settings() {
+    let mut config = Config {
+        font_size: 14.0,
+        show_timestamps: true,
+        profiles: vec![
+            profile(
+                "streaming",
+                AppearanceOverrides {
+                    font_size: Some(20.0),
+                    ..AppearanceOverrides::default()
+                },
+            ),
+            profile("lurking", AppearanceOverrides::default()),
+        ],
+        active_profile_name: Some("streaming".to_string()),
+        ..Config::default()
+    };
+
+    let effective = config.effective();
+    assert_eq!(effective.font_size, 20.0);
+    assert!(effective.show_timestamps);
+
+    config.active_profile_name = Some("lurking".to_string());
+    assert_eq!(config.effective().font_size, 14.0);
+}
+
+#[test]
+fn only_changed_settings_become_overrides() {
+    let before = Config::default();
+    let after = Config {
+        emote_size: 32.0,
+        ..before.clone()
+    };
+    let mut overrides = AppearanceOverrides::default();
+    overrides.capture_changes(&before, &after);
+
+    assert_eq!(
+        overrides,
+        AppearanceOverrides {
+            emote_size: Some(32.0),
+            ..AppearanceOverrides::default()
+        }
+    );
+}
+
+const LEGACY_CONFIG: &str = r#"{
+    "client_id": "abc123",
+    "font_size": 18.5,
+    "enable_cjk_font": true,
+    "device_code": "leftover-from-the-old-login"
+}"#;
+
+#[tokio::test]
+async fn legacy_json_settings_are_carried_over_once() {
+    let dir = std::env::temp_dir().join(format!("livenac-legacy-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let legacy_path = dir.join(LEGACY_CONFIG_FILE);
+    let config_path = dir.join("app_config.toml");
+    std::fs::write(&legacy_path, LEGACY_CONFIG).unwrap();
+
+    let mut config = Config::default();
+    assert!(
+        migrate_legacy_config(&legacy_path, &config_path, &mut config)
+            .await
+            .unwrap()
+    );
+    assert_eq!(config.client_id.as_deref(), Some("abc123"));
+    assert_eq!(config.font_size, 18.5);
+    assert!(config.enable_cjk_font);
+
+    let written: Config = toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(written.client_id.as_deref(), Some("abc123"));
+    assert_eq!(written.font_size, 18.5);
+    assert!(!legacy_path.exists());
+    assert!(dir.join("config.json.bak").exists());
+
+    assert!(
+        !migrate_legacy_config(&legacy_path, &config_path, &mut config)
+            .await
+            .unwrap()
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn ignored_users_match_regardless_of_case() {
+    let config = Config {
+        ignored_users: vec!["spambot".to_string()],
+        ..Config::default()
+    };
+    assert!(config.is_ignored("SpamBot"));
+    assert!(!config.is_ignored("spambot2"));
+}
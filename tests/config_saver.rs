@@ -0,0 +1,32 @@
+use livenac::app::{config::Config, config_saver::ConfigSaver};
+use parking_lot::Mutex;
+use std::{sync::Arc, time::Duration};
+
+#[tokio::test]
+async fn rapid_changes_are_written_once_per_interval_ending_with_the_latest() {
+    let written: Arc<Mutex<Vec<f32>>> = Arc::default();
+    let saver = {
+        let written = written.clone();
+        ConfigSaver::with_writer(Duration::from_millis(200), move |config| {
+            let written = written.clone();
+            async move {
+                written.lock().push(config.font_size);
+                Ok(())
+            }
+        })
+    };
+
+    // Like dragging the font size slider for a few frames.
+    for size in 10..40 {
+        saver.save(Config {
+            font_size: size as f32,
+            ..Config::default()
+        });
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let written = written.lock();
+    assert!(written.len() <= 3, "wrote {} times", written.len());
+    assert_eq!(written.last(), Some(&39.0));
+}
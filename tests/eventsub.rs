@@ -0,0 +1,54 @@
+use livenac::core::eventsub::{ANONYMOUS_SENDER, notification_sender, suspicious_message_id};
+use twitch_api::eventsub::channel::chat::notification::Chatter;
+
+#[test]
+fn notifications_name_their_sender_or_the_anonymous_gifter() {
+    let chatter: Chatter = serde_json::from_str(
+        r##"{
+            "chatter_user_id": "1234",
+            "chatter_user_name": "SomeGifter",
+            "chatter_user_login": "somegifter",
+            "color": "#FF4500",
+            "chatter_is_anonymous": false
+        }"##,
+    )
+    .unwrap();
+    assert_eq!(
+        notification_sender(&chatter),
+        (
+            "1234".to_string(),
+            "SomeGifter".to_string(),
+            Some((255, 69, 0))
+        )
+    );
+
+    let anonymous: Chatter = serde_json::from_str(
+        r#"{
+            "chatter_user_id": null,
+            "chatter_user_name": null,
+            "chatter_user_login": null,
+            "color": null,
+            "chatter_is_anonymous": true
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        notification_sender(&anonymous),
+        (String::new(), ANONYMOUS_SENDER.to_string(), None)
+    );
+}
+
+#[test]
+fn suspicious_messages_are_keyed_by_the_message_id_in_the_json() {
+    let raw = r#"{
+        "metadata": { "message_type": "notification" },
+        "payload": {
+            "event": {
+                "low_trust_status": "restricted",
+                "message": { "message_id": "abc-123", "text": "hi", "fragments": [] }
+            }
+        }
+    }"#;
+    assert_eq!(suspicious_message_id(raw).as_deref(), Some("abc-123"));
+    assert_eq!(suspicious_message_id(r#"{"payload": {"event": {}}}"#), None);
+}
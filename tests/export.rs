@@ -0,0 +1,52 @@
+mod common;
+
+use chrono::{Local, TimeZone};
+use livenac::{
+    core::export::{to_html, to_plaintext},
+    models::{
+        emote::{Emote, EmoteSource},
+        message::{ChatMessage, MessageFragment},
+    },
+};
+
+fn message(sender: &str, fragments: Vec<MessageFragment>) -> ChatMessage {
+    ChatMessage {
+        sender_color: Some((255, 0, 0)),
+        timestamp: Local.with_ymd_and_hms(2024, 5, 1, 20, 15, 3).unwrap(),
+        ..common::message(sender, fragments)
+    }
+}
+
+fn kappa() -> MessageFragment {
+    MessageFragment::Emote(Emote {
+        id: String::new(),
+        name: "Kappa".to_string(),
+        url: "https://example.com/kappa.png".to_string(),
+        source: EmoteSource::Bttv,
+        large_url: None,
+    })
+}
+
+#[test]
+fn plain_text_has_one_line_per_message_with_emote_names() {
+    let messages = [message(
+        "someone",
+        vec![MessageFragment::Text("hi ".to_string()), kappa()],
+    )];
+    assert_eq!(
+        to_plaintext(&messages),
+        "[2024-05-01 20:15:03] someone: hi Kappa\n"
+    );
+}
+
+#[test]
+fn html_shows_colors_and_emote_images_and_escapes_text() {
+    let messages = [message(
+        "someone",
+        vec![MessageFragment::Text("<b>hi</b> ".to_string()), kappa()],
+    )];
+    let html = to_html("somechannel", &messages);
+    assert!(html.contains("<b style=\"color: #ff0000\">someone</b>"));
+    assert!(html.contains("&lt;b&gt;hi&lt;/b&gt; "));
+    assert!(html.contains("<img src=\"https://example.com/kappa.png\" alt=\"Kappa\""));
+}
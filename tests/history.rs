@@ -0,0 +1,38 @@
+mod common;
+
+use livenac::{
+    core::history::ChatHistory,
+    models::message::{ChatMessage, MessageFragment},
+};
+
+fn message(id: &str) -> ChatMessage {
+    ChatMessage {
+        id: id.to_string(),
+        ..common::message(
+            "someone",
+            vec![MessageFragment::Text(format!("message {}", id))],
+        )
+    }
+}
+
+#[test]
+fn only_the_most_recent_messages_are_kept() {
+    let dir = std::env::temp_dir().join(format!("livenac-history-{}", std::process::id()));
+    let history = ChatHistory::new(dir.clone(), 3);
+    for id in 0..10 {
+        history
+            .append("somechannel", &message(&id.to_string()))
+            .unwrap();
+    }
+
+    let ids: Vec<_> = history
+        .load_tail("somechannel")
+        .unwrap()
+        .into_iter()
+        .map(|m| m.id)
+        .collect();
+    assert_eq!(ids, ["7", "8", "9"]);
+    assert!(history.load_tail("otherchannel").unwrap().is_empty());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
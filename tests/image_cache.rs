@@ -0,0 +1,60 @@
+use egui::{ImageSource, SizeHint, load::TexturePoll};
+use livenac::emotes::image_cache::{EmoteImageCache, EmoteImageLoader, sniff_mime};
+use std::{sync::Arc, thread, time::Duration};
+
+#[test]
+fn least_recently_used_images_are_evicted_first() {
+    let dir = std::env::temp_dir().join(format!("livenac-emote-cache-{}", std::process::id()));
+    let cache = EmoteImageCache::new(dir.clone(), 250);
+    let image = [0u8; 100];
+
+    cache.put("https://example.com/a", &image).unwrap();
+    thread::sleep(Duration::from_millis(20));
+    cache.put("https://example.com/b", &image).unwrap();
+    thread::sleep(Duration::from_millis(20));
+    assert!(cache.get("https://example.com/a").is_some());
+    thread::sleep(Duration::from_millis(20));
+    cache.put("https://example.com/c", &image).unwrap();
+
+    assert!(cache.get("https://example.com/a").is_some());
+    assert!(cache.get("https://example.com/b").is_none());
+    assert!(cache.get("https://example.com/c").is_some());
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn cached_images_are_recognized_by_their_header() {
+    assert_eq!(sniff_mime(b"GIF89a..."), Some("image/gif"));
+    assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+    assert_eq!(sniff_mime(b"<html>"), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn webp_emotes_decode_through_the_loaders() {
+    let dir = std::env::temp_dir().join(format!("livenac-emote-webp-{}", std::process::id()));
+    let cache = Arc::new(EmoteImageCache::new(dir.clone(), 1 << 20));
+    // 7TV serves only WebP, and BetterTTV does for some emotes.
+    let url = "https://cdn.7tv.app/emote/test/2x.webp";
+    cache
+        .put(url, include_bytes!("fixtures/emote.webp"))
+        .unwrap();
+
+    let ctx = egui::Context::default();
+    egui_extras::install_image_loaders(&ctx);
+    ctx.add_bytes_loader(Arc::new(EmoteImageLoader::new(cache)));
+    let mut size = None;
+    for _ in 0..500 {
+        match ImageSource::Uri(url.into()).load(&ctx, Default::default(), SizeHint::default()) {
+            Ok(TexturePoll::Ready { texture }) => {
+                size = Some(texture.size);
+                break;
+            }
+            Ok(TexturePoll::Pending { .. }) => tokio::time::sleep(Duration::from_millis(10)).await,
+            Err(e) => panic!("Failed to decode the WebP emote: {}", e),
+        }
+    }
+    assert_eq!(size, Some(egui::vec2(4.0, 4.0)));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
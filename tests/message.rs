@@ -0,0 +1,120 @@
+mod common;
+
+use common::{message, text};
+use livenac::models::{
+    emote::{Emote, EmoteSource},
+    message::{MessageFragment, MessageKind, SystemCategory},
+};
+
+#[test]
+fn text_is_kept_verbatim() {
+    let msg = message("someone", vec![text("hello  "), text("world")]);
+    assert_eq!(msg.to_plain_text(), "hello  world");
+}
+
+#[test]
+fn emotes_become_their_codes() {
+    let kappa = MessageFragment::Emote(Emote {
+        id: "25".to_string(),
+        name: "Kappa".to_string(),
+        url: "https://example.com/25".to_string(),
+        source: EmoteSource::Twitch,
+        large_url: None,
+    });
+    let msg = message("someone", vec![text("nice "), kappa, text(" one")]);
+    assert_eq!(msg.to_plain_text(), "nice Kappa one");
+}
+
+#[test]
+fn mentions_are_prefixed_with_at() {
+    let msg = message(
+        "someone",
+        vec![
+            MessageFragment::Mention("SomeStreamer".to_string()),
+            text(" hi"),
+        ],
+    );
+    assert_eq!(msg.to_plain_text(), "@SomeStreamer hi");
+}
+
+#[test]
+fn cheers_become_lowercase_prefix_and_amount() {
+    let msg = message(
+        "someone",
+        vec![
+            MessageFragment::Cheer {
+                prefix: "Cheer".to_string(),
+                bits: 100,
+            },
+            text(" gg"),
+        ],
+    );
+    assert_eq!(msg.to_plain_text(), "cheer100 gg");
+}
+
+#[test]
+fn actions_are_prefixed_with_an_asterisk() {
+    let mut msg = message("someone", vec![text("waves")]);
+    msg.is_action = true;
+    assert_eq!(msg.to_plain_text(), "* waves");
+}
+
+#[test]
+fn empty_message_is_empty_text() {
+    assert_eq!(message("someone", Vec::new()).to_plain_text(), "");
+}
+
+#[test]
+fn system_messages_start_with_the_notice() {
+    let mut msg = message("someone", vec![text("love the stream")]);
+    msg.kind = MessageKind::System {
+        category: SystemCategory::Subscription,
+        text: "someone subscribed for 12 months!".to_string(),
+    };
+    assert_eq!(
+        msg.to_plain_text(),
+        "someone subscribed for 12 months! love the stream"
+    );
+
+    msg.fragments.clear();
+    assert_eq!(msg.to_plain_text(), "someone subscribed for 12 months!");
+}
+
+#[test]
+fn messages_involve_their_sender_and_mentioned_users() {
+    let msg = message(
+        "someone",
+        vec![
+            text("hi "),
+            MessageFragment::Mention("Streamer".to_string()),
+        ],
+    );
+    assert!(msg.involves("SomeOne"));
+    assert!(msg.involves("streamer"));
+    assert!(!msg.involves("other"));
+}
+
+#[test]
+fn search_matches_sender_and_text_ignoring_case() {
+    let msg = message("someone", vec![text("Hello "), text("World")]);
+    assert!(msg.matches_search("o w"));
+    assert!(msg.matches_search("SOMEONE"));
+    assert!(!msg.matches_search("bye"));
+}
+
+#[test]
+fn replies_summarize_the_parent_message() {
+    let parent = message(
+        "someone",
+        vec![text("first line of a long message ".repeat(4).trim())],
+    );
+    let reply = parent.reply_info();
+    assert_eq!(reply.parent_id, "1");
+    assert_eq!(reply.parent_user_name, "someone");
+    let summary = reply.summary();
+    assert!(summary.starts_with("@someone: first line"));
+    assert!(summary.ends_with('…'));
+
+    let short = message("someone", vec![text("hi")]).reply_info();
+    assert_eq!(short.summary(), "@someone: hi");
+}
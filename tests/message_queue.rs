@@ -0,0 +1,102 @@
+use livenac::core::chat::{
+    MAX_QUEUE_DELAY, MessageQueue, RATE_LIMIT_WINDOW, RateLimited, USER_MESSAGES_PER_WINDOW,
+    parse_ratelimit_reset, rate_limit_error,
+};
+use reqwest::{
+    StatusCode,
+    header::{HeaderMap, HeaderValue},
+};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+#[test]
+fn sends_within_the_limit_go_out_right_away() {
+    let mut queue = MessageQueue::default();
+    let now = Instant::now();
+    for _ in 0..USER_MESSAGES_PER_WINDOW {
+        assert_eq!(
+            queue.reserve(now, USER_MESSAGES_PER_WINDOW),
+            Ok(Duration::ZERO)
+        );
+    }
+}
+
+#[test]
+fn a_burst_over_the_limit_is_spaced_out_then_refused() {
+    let mut queue = MessageQueue::default();
+    let now = Instant::now();
+    for _ in 0..USER_MESSAGES_PER_WINDOW {
+        queue.reserve(now, USER_MESSAGES_PER_WINDOW).unwrap();
+    }
+    let spacing = RATE_LIMIT_WINDOW / USER_MESSAGES_PER_WINDOW;
+    let first = queue.reserve(now, USER_MESSAGES_PER_WINDOW).unwrap();
+    let second = queue.reserve(now, USER_MESSAGES_PER_WINDOW).unwrap();
+    assert!(first.abs_diff(spacing) < Duration::from_millis(1));
+    assert!(second.abs_diff(spacing * 2) < Duration::from_millis(1));
+
+    let refused = std::iter::repeat_with(|| queue.reserve(now, USER_MESSAGES_PER_WINDOW))
+        .take(20)
+        .find_map(Result::err);
+    assert!(refused.is_some_and(|wait| wait > MAX_QUEUE_DELAY));
+}
+
+#[test]
+fn the_limit_refills_over_the_window() {
+    let mut queue = MessageQueue::default();
+    let now = Instant::now();
+    for _ in 0..USER_MESSAGES_PER_WINDOW {
+        queue.reserve(now, USER_MESSAGES_PER_WINDOW).unwrap();
+    }
+    assert_eq!(
+        queue.reserve(now + RATE_LIMIT_WINDOW, USER_MESSAGES_PER_WINDOW),
+        Ok(Duration::ZERO)
+    );
+}
+
+#[test]
+fn sends_wait_for_a_rate_limit_reset() {
+    let mut queue = MessageQueue::default();
+    let now = Instant::now();
+    queue.pause_until(now + Duration::from_secs(3));
+    assert_eq!(
+        queue.reserve(now, USER_MESSAGES_PER_WINDOW),
+        Ok(Duration::from_secs(3))
+    );
+    assert_eq!(
+        queue.reserve(now + Duration::from_secs(5), USER_MESSAGES_PER_WINDOW),
+        Ok(Duration::ZERO)
+    );
+}
+
+#[test]
+fn ratelimit_reset_header_is_a_unix_timestamp() {
+    let reset_at = parse_ratelimit_reset("1700000042").unwrap();
+    assert_eq!(reset_at, UNIX_EPOCH + Duration::from_secs(1_700_000_042));
+    assert_eq!(parse_ratelimit_reset("soon"), None);
+
+    let limited = RateLimited {
+        reset_at: Some(reset_at),
+    };
+    let now = UNIX_EPOCH + Duration::from_secs(1_700_000_040);
+    assert_eq!(limited.retry_after(now), Some(Duration::from_secs(2)));
+    assert_eq!(
+        limited.retry_after(reset_at + Duration::from_secs(1)),
+        Some(Duration::ZERO)
+    );
+}
+
+#[test]
+fn only_a_429_is_rate_limited_and_it_carries_the_reset_time() {
+    let mut headers = HeaderMap::new();
+    headers.insert("Ratelimit-Reset", HeaderValue::from_static("1700000042"));
+
+    let limited = rate_limit_error(StatusCode::TOO_MANY_REQUESTS, &headers).unwrap();
+    assert_eq!(
+        limited.reset_at,
+        Some(UNIX_EPOCH + Duration::from_secs(1_700_000_042))
+    );
+    assert!(rate_limit_error(StatusCode::OK, &headers).is_none());
+    assert!(rate_limit_error(StatusCode::BAD_REQUEST, &headers).is_none());
+
+    let without_reset = rate_limit_error(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new());
+    assert!(without_reset.is_some_and(|limited| limited.reset_at.is_none()));
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use chrono::Duration;
+use common::{message, text};
+use eframe::egui::Color32;
+use livenac::{
+    app::config::{Config, OwnMessageStyle},
+    models::{
+        emote::{Emote, EmoteSource},
+        message::{LowTrustStatus, MessageFragment, SystemCategory},
+    },
+    ui::chat::message_style::{
+        DEFAULT_USER_COLORS, RowHighlight, color_for_user, matches_keywords, resolve_sender_color,
+        resolve_system_color, row_height, row_highlight, should_collapse_spacing, should_group,
+    },
+};
+
+fn emote(source: EmoteSource) -> MessageFragment {
+    MessageFragment::Emote(Emote {
+        id: "25".to_string(),
+        name: "Kappa".to_string(),
+        url: "https://example.com/25".to_string(),
+        source,
+        large_url: None,
+    })
+}
+
+#[test]
+fn sender_color_falls_back_to_the_accent_or_an_automatic_color() {
+    let mut config = Config::default();
+    assert_eq!(
+        resolve_sender_color("someone", None, &config),
+        color_for_user("someone", None)
+    );
+    assert_eq!(
+        resolve_sender_color("someone", Some((255, 0, 0)), &config),
+        Color32::from_rgb(255, 0, 0)
+    );
+
+    config.accent_color = Some([0, 200, 100]);
+    assert_eq!(
+        resolve_sender_color("someone", None, &config),
+        Color32::from_rgb(0, 200, 100)
+    );
+    assert_eq!(
+        resolve_sender_color("someone", Some((255, 0, 0)), &config),
+        Color32::from_rgb(255, 0, 0)
+    );
+}
+
+#[test]
+fn automatic_colors_depend_only_on_the_name() {
+    let color = color_for_user("SomeOne", None);
+    assert!(DEFAULT_USER_COLORS.contains(&color));
+    assert_eq!(color_for_user("someone", None), color);
+    assert_eq!(
+        color_for_user("someone", Some((1, 2, 3))),
+        Color32::from_rgb(1, 2, 3)
+    );
+
+    let names = ["alice", "bob", "carol", "dave", "erin", "frank", "grace"];
+    assert!(
+        names
+            .iter()
+            .any(|name| color_for_user(name, None) != color_for_user(names[0], None))
+    );
+}
+
+#[test]
+fn pinned_user_colors_win_over_their_own() {
+    let mut config = Config {
+        accent_color: Some([0, 200, 100]),
+        ..Config::default()
+    };
+    config
+        .user_colors
+        .insert("someone".to_string(), [10, 20, 30]);
+    let pinned = Color32::from_rgb(10, 20, 30);
+    assert_eq!(
+        resolve_sender_color("SomeOne", Some((255, 0, 0)), &config),
+        pinned
+    );
+    assert_eq!(resolve_sender_color("someone", None, &config), pinned);
+    assert_ne!(resolve_sender_color("other", None, &config), pinned);
+}
+
+#[test]
+fn custom_system_color_overrides_the_theme() {
+    let mut config = Config::default();
+    let themed = resolve_system_color(SystemCategory::Raid, &config, true);
+    assert_ne!(
+        themed,
+        resolve_system_color(SystemCategory::Raid, &config, false)
+    );
+
+    config.system_message_colors.raid = Some([1, 2, 3]);
+    assert_eq!(
+        resolve_system_color(SystemCategory::Raid, &config, true),
+        Color32::from_rgb(1, 2, 3)
+    );
+}
+
+#[test]
+fn spacing_collapses_only_between_emotes_when_enabled() {
+    let mut config = Config::default();
+    let kappa = emote(EmoteSource::Twitch);
+    assert!(!should_collapse_spacing(Some(&kappa), &kappa, &config));
+
+    config.collapse_emotes = true;
+    assert!(should_collapse_spacing(Some(&kappa), &kappa, &config));
+    assert!(!should_collapse_spacing(Some(&text(" ")), &kappa, &config));
+    assert!(!should_collapse_spacing(Some(&kappa), &text("hi"), &config));
+    assert!(!should_collapse_spacing(None, &kappa, &config));
+}
+
+#[test]
+fn messages_group_only_for_the_same_recent_sender() {
+    let config = Config {
+        group_consecutive: true,
+        ..Config::default()
+    };
+    let first = message("someone", vec![text("hi")]);
+    let mut second = message("someone", vec![text("again")]);
+    assert!(should_group(Some(&first), &second, &config));
+    assert!(!should_group(None, &second, &config));
+    assert!(!should_group(
+        Some(&message("other", vec![])),
+        &second,
+        &config
+    ));
+
+    second.timestamp = first.timestamp + Duration::minutes(5);
+    assert!(!should_group(Some(&first), &second, &config));
+
+    let off = Config::default();
+    assert!(!should_group(
+        Some(&first),
+        &message("someone", vec![]),
+        &off
+    ));
+}
+
+#[test]
+fn low_trust_highlight_needs_mod_tools_and_beats_own_highlight() {
+    let config = Config {
+        highlight_own_messages: true,
+        own_message_style: OwnMessageStyle::Border,
+        ..Config::default()
+    };
+    let mut msg = message("me", vec![text("hi")]);
+    let own_ids = ["id-me"];
+    assert_eq!(
+        row_highlight(&msg, &config, false, &own_ids),
+        RowHighlight::Own(OwnMessageStyle::Border)
+    );
+    assert_eq!(row_highlight(&msg, &config, false, &[]), RowHighlight::None);
+
+    msg.low_trust = Some(LowTrustStatus::ActiveMonitoring);
+    assert_eq!(
+        row_highlight(&msg, &config, true, &own_ids),
+        RowHighlight::LowTrust
+    );
+    assert_eq!(
+        row_highlight(&msg, &config, false, &own_ids),
+        RowHighlight::Own(OwnMessageStyle::Border)
+    );
+}
+
+#[test]
+fn keywords_highlight_other_users_messages_ignoring_case() {
+    let config = Config {
+        highlight_keywords: vec!["Streamer".to_string(), " ".to_string()],
+        ..Config::default()
+    };
+    let msg = message("viewer", vec![text("hi STREAMER!")]);
+    assert!(matches_keywords(&msg, &config.highlight_keywords));
+    assert_eq!(
+        row_highlight(&msg, &config, false, &[]),
+        RowHighlight::Keyword
+    );
+    assert_eq!(
+        row_highlight(&msg, &config, false, &["id-viewer"]),
+        RowHighlight::None
+    );
+
+    let mention = message(
+        "viewer",
+        vec![MessageFragment::Mention("streamer".to_string())],
+    );
+    assert!(matches_keywords(&mention, &config.highlight_keywords));
+    assert!(!matches_keywords(
+        &message("viewer", vec![text("hello")]),
+        &config.highlight_keywords
+    ));
+}
+
+#[test]
+fn rows_reserve_emote_height_only_for_enabled_emotes() {
+    let config = Config {
+        emote_size: 32.0,
+        ..Config::default()
+    };
+    assert_eq!(
+        row_height(&message("a", vec![text("hi")]), &config, 14.0),
+        14.0
+    );
+    assert_eq!(
+        row_height(
+            &message("a", vec![emote(EmoteSource::Twitch)]),
+            &config,
+            14.0
+        ),
+        32.0
+    );
+    // Third-party emotes are off by default and render as their code.
+    assert_eq!(
+        row_height(&message("a", vec![emote(EmoteSource::Bttv)]), &config, 14.0),
+        14.0
+    );
+}
@@ -0,0 +1,810 @@
+mod common;
+
+use livenac::{
+    app::{
+        actions::Action,
+        config::{Config, Profile},
+        reducer::{DUPLICATE_LOOKBACK, reduce, reduce_batch},
+        state::{AppState, ConnectionStatus},
+    },
+    core::mock::{MOCK_USER_LOGIN, mock_token},
+    emotes::twitch_api::{EmoteImages, TwitchEmote},
+    events::app_event::{AppEvent, ChatEvent},
+    features::webhook::WebhookPayload,
+    models::{
+        emote::{Emote, EmoteSource},
+        message::{ChatMessage, LowTrustStatus, MessageFragment, MessageKind},
+    },
+};
+use std::{collections::VecDeque, sync::Arc};
+
+/// Logged in with a tab open for `somechannel`.
+fn logged_in() -> AppState {
+    let mut state = AppState::logged_in(Arc::new(mock_token()));
+    state.open_tab("somechannel");
+    state
+}
+
+fn message(id: &str, sender: &str, text: &str) -> ChatMessage {
+    ChatMessage {
+        id: id.to_string(),
+        ..common::message(sender, vec![common::text(text)])
+    }
+}
+
+fn chat(state: &mut AppState, config: &mut Config, event: ChatEvent) -> Vec<Action> {
+    reduce(state, AppEvent::Chat(event), config)
+}
+
+fn messages(state: &AppState) -> &VecDeque<ChatMessage> {
+    &state
+        .active_tab()
+        .expect("expected an open tab")
+        .chat_messages
+}
+
+#[test]
+fn config_loaded_moves_to_initializing() {
+    let mut state = AppState::Startup { task_spawned: true };
+    let mut config = Config::default();
+    let loaded = Config {
+        client_id: Some("abc".to_string()),
+        ..Config::default()
+    };
+
+    let actions = reduce(
+        &mut state,
+        AppEvent::ConfigLoaded(Ok(Box::new(loaded))),
+        &mut config,
+    );
+
+    assert!(actions.is_empty());
+    assert!(matches!(
+        state,
+        AppState::Initializing {
+            task_spawned: false
+        }
+    ));
+    assert_eq!(config.client_id.as_deref(), Some("abc"));
+}
+
+#[test]
+fn config_load_failure_moves_to_first_time_setup() {
+    let mut state = AppState::Startup { task_spawned: true };
+    let mut config = Config::default();
+
+    reduce(
+        &mut state,
+        AppEvent::ConfigLoaded(Err(eyre::eyre!("bad toml"))),
+        &mut config,
+    );
+
+    match state {
+        AppState::FirstTimeSetup { error, .. } => {
+            assert!(error.unwrap().contains("bad toml"));
+        }
+        _ => panic!("expected FirstTimeSetup"),
+    }
+}
+
+#[test]
+fn silent_login_failure_without_profiles_goes_to_first_time_setup() {
+    let mut state = AppState::Initializing { task_spawned: true };
+    let mut config = Config::default();
+
+    reduce(
+        &mut state,
+        AppEvent::SilentLoginComplete(Err(eyre::eyre!("no token"))),
+        &mut config,
+    );
+
+    assert!(matches!(
+        state,
+        AppState::FirstTimeSetup { error: None, .. }
+    ));
+}
+
+#[test]
+fn silent_login_failure_with_profiles_goes_to_logged_out() {
+    let mut state = AppState::Initializing { task_spawned: true };
+    let mut config = Config {
+        profiles: vec![Profile {
+            name: "main".to_string(),
+            twitch_user_id: None,
+            open_channels: Vec::new(),
+            appearance: Default::default(),
+        }],
+        ..Config::default()
+    };
+
+    reduce(
+        &mut state,
+        AppEvent::SilentLoginComplete(Err(eyre::eyre!("expired"))),
+        &mut config,
+    );
+
+    assert!(matches!(
+        state,
+        AppState::LoggedOut {
+            show_profile_manager_on_entry: true
+        }
+    ));
+}
+
+#[test]
+fn successful_login_creates_profile_and_requests_side_effects() {
+    let mut state = AppState::Initializing { task_spawned: true };
+    let mut config = Config {
+        client_id: Some("abc".to_string()),
+        ..Config::default()
+    };
+
+    let actions = reduce(
+        &mut state,
+        AppEvent::SilentLoginComplete(Ok(mock_token())),
+        &mut config,
+    );
+
+    assert!(matches!(state, AppState::LoggedIn { .. }));
+    assert_eq!(config.active_profile_name.as_deref(), Some(MOCK_USER_LOGIN));
+    assert_eq!(config.profiles.len(), 1);
+    assert!(
+        matches!(&actions[..], [Action::SaveConfig(saved), Action::FetchGlobalEmotes { .. }]
+            if saved.active_profile_name.as_deref() == Some(MOCK_USER_LOGIN))
+    );
+}
+
+#[test]
+fn successful_login_without_client_id_skips_emote_fetch() {
+    let mut state = AppState::Initializing { task_spawned: true };
+    let mut config = Config::default();
+
+    let actions = reduce(
+        &mut state,
+        AppEvent::SilentLoginComplete(Ok(mock_token())),
+        &mut config,
+    );
+
+    assert!(matches!(&actions[..], [Action::SaveConfig(_)]));
+}
+
+#[test]
+fn new_chat_message_appends_and_trims_trailing_whitespace() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", "someone", "hello   ")),
+    );
+
+    let log = messages(&state);
+    assert_eq!(log.len(), 1);
+    assert!(matches!(&log[0].fragments[0], MessageFragment::Text(t) if t == "hello"));
+}
+
+#[test]
+fn batched_messages_are_capped_once() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+
+    let events = (0..250).map(|i| {
+        AppEvent::Chat(ChatEvent::NewChatMessage(message(
+            &i.to_string(),
+            "someone",
+            "hi",
+        )))
+    });
+    reduce_batch(&mut state, events, &mut config);
+
+    let log = messages(&state);
+    assert_eq!(log.len(), 200);
+    assert_eq!(log[0].id, "50");
+    assert_eq!(log[199].id, "249");
+
+    config.max_chat_lines = 100;
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("250", "someone", "hi")),
+    );
+    let log = messages(&state);
+    assert_eq!(log.len(), 100);
+    assert_eq!(log[0].id, "151");
+}
+
+#[test]
+fn only_the_newest_messages_are_checked_for_duplicates() {
+    let mut state = logged_in();
+    let mut config = Config {
+        max_chat_lines: DUPLICATE_LOOKBACK * 4,
+        ..Config::default()
+    };
+    let mut push = |id: &str, text: &str| {
+        chat(
+            &mut state,
+            &mut config,
+            ChatEvent::NewChatMessage(message(id, "someone", text)),
+        );
+    };
+    push("old", "first");
+    for i in 0..DUPLICATE_LOOKBACK {
+        push(&i.to_string(), "hi");
+    }
+    // A copy of the newest message replaces it, one of a message further back
+    // than the lookback is taken as new instead of searching the whole log.
+    push(&(DUPLICATE_LOOKBACK - 1).to_string(), "edited");
+    push("old", "again");
+
+    let log = messages(&state);
+    assert_eq!(log.len(), DUPLICATE_LOOKBACK + 2);
+    assert!(
+        matches!(&log[DUPLICATE_LOOKBACK].fragments[0], MessageFragment::Text(t) if t == "edited")
+    );
+    assert_eq!(log.iter().filter(|m| m.id == "old").count(), 2);
+}
+
+#[test]
+fn chat_buffer_is_capped() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+
+    for i in 0..250 {
+        chat(
+            &mut state,
+            &mut config,
+            ChatEvent::NewChatMessage(message(&i.to_string(), "someone", "hi")),
+        );
+    }
+
+    let log = messages(&state);
+    assert_eq!(log.len(), 200);
+    assert_eq!(log[0].id, "50");
+    assert_eq!(log[199].id, "249");
+}
+
+#[test]
+fn deleted_messages_are_removed_or_marked() {
+    let mut config = Config::default();
+
+    let mut state = logged_in();
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", "someone", "hi")),
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::MessageDeleted("1".to_string()),
+    );
+    assert!(messages(&state).is_empty());
+
+    config.keep_deleted_messages = true;
+    let mut state = logged_in();
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", "someone", "hi")),
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::MessageDeleted("1".to_string()),
+    );
+    assert!(messages(&state)[0].deleted);
+}
+
+#[test]
+fn clearing_chat_leaves_only_a_notice() {
+    let mut config = Config::default();
+    let mut state = logged_in();
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", "someone", "hi")),
+    );
+    chat(&mut state, &mut config, ChatEvent::ChatCleared);
+    let remaining = messages(&state);
+    assert_eq!(remaining.len(), 1);
+    assert!(matches!(remaining[0].kind, MessageKind::System { .. }));
+}
+
+#[test]
+fn clearing_a_user_removes_only_their_messages() {
+    let mut config = Config::default();
+    let mut state = logged_in();
+    for (id, sender) in [("1", "spammer"), ("2", "someone"), ("3", "spammer")] {
+        chat(
+            &mut state,
+            &mut config,
+            ChatEvent::NewChatMessage(message(id, sender, "hi")),
+        );
+    }
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::UserMessagesCleared("id-spammer".to_string()),
+    );
+    let ids: Vec<&str> = messages(&state).iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["2"]);
+}
+
+#[test]
+fn chatter_list_replaces_silent_users_but_keeps_speakers() {
+    let mut config = Config::default();
+    let mut state = logged_in();
+    let names = |state: &AppState| {
+        let mut users: Vec<(String, bool)> = state
+            .active_tab()
+            .unwrap()
+            .users
+            .iter()
+            .map(|user| (user.name.clone(), user.has_spoken))
+            .collect();
+        users.sort();
+        users
+    };
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::ChattersLoaded(vec!["lurker".to_string(), "talker".to_string()]),
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", "talker", "hi")),
+    );
+    assert_eq!(
+        names(&state),
+        [("lurker".to_string(), false), ("talker".to_string(), true)]
+    );
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::ChattersLoaded(vec!["newcomer".to_string()]),
+    );
+    assert_eq!(
+        names(&state),
+        [
+            ("newcomer".to_string(), false),
+            ("talker".to_string(), true)
+        ]
+    );
+}
+
+#[test]
+fn message_sent_clears_input() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+    if let AppState::LoggedIn {
+        message_to_send,
+        send_in_progress,
+        unsent_message,
+        ..
+    } = &mut state
+    {
+        *message_to_send = "hello".to_string();
+        *send_in_progress = true;
+        *unsent_message = Some("hello".to_string());
+    }
+
+    chat(&mut state, &mut config, ChatEvent::MessageSent);
+
+    let AppState::LoggedIn {
+        message_to_send,
+        send_in_progress,
+        unsent_message,
+        ..
+    } = &state
+    else {
+        panic!("expected LoggedIn state");
+    };
+    assert!(message_to_send.is_empty());
+    assert!(!send_in_progress);
+    assert!(unsent_message.is_none());
+}
+
+#[test]
+fn message_sound_is_throttled_and_skips_own_messages() {
+    let mut state = logged_in();
+    let mut config = Config {
+        message_sound_enabled: true,
+        message_sound_min_interval_secs: 60.0,
+        chat_history_lines: 0,
+        ..Config::default()
+    };
+    let own = chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", MOCK_USER_LOGIN, "hi")),
+    );
+    let first = chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("2", "someone", "hi")),
+    );
+    let second = chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("3", "someone", "hi")),
+    );
+
+    assert!(own.is_empty());
+    assert!(matches!(&first[..], [Action::PlayMessageSound { .. }]));
+    assert!(second.is_empty());
+}
+
+#[test]
+fn ignored_users_neither_play_the_sound_nor_reach_the_webhook() {
+    let mut state = logged_in();
+    let mut config = Config {
+        message_sound_enabled: true,
+        ignored_users: vec!["Spammer".to_string()],
+        chat_history_lines: 0,
+        ..Config::default()
+    };
+    config.webhook.url = Some("http://localhost:8080/chat".to_string());
+
+    let actions = chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", "spammer", "buy followers")),
+    );
+
+    assert!(actions.is_empty());
+    // Still stored, so unignoring shows it again.
+    assert_eq!(messages(&state).len(), 1);
+}
+
+#[test]
+fn revoked_authorization_requires_reauth() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::SubscriptionRevoked("authorization_revoked".to_string()),
+    );
+
+    let AppState::LoggedIn {
+        reauth_required,
+        last_error,
+        ..
+    } = &state
+    else {
+        panic!("expected LoggedIn state");
+    };
+    assert!(*reauth_required);
+    assert!(last_error.is_some());
+}
+
+#[test]
+fn joined_channel_fetches_its_emotes_and_ignores_stale_results() {
+    let mut state = logged_in();
+    let mut config = Config {
+        client_id: Some("abc".to_string()),
+        ..Config::default()
+    };
+    let actions = chat(
+        &mut state,
+        &mut config,
+        ChatEvent::ChannelJoined {
+            channel: "somechannel".to_string(),
+            broadcaster_id: "123".to_string(),
+        },
+    );
+    assert!(matches!(
+        &actions[..],
+        [
+            Action::FetchChannelEmotes { broadcaster_id, .. },
+            Action::FetchCheermotes { .. },
+        ] if broadcaster_id == "123"
+    ));
+
+    let emote = |name: &str| TwitchEmote {
+        id: "1".to_string(),
+        name: name.to_string(),
+        images: EmoteImages {
+            url_1x: String::new(),
+            url_2x: String::new(),
+            url_4x: String::new(),
+        },
+        format: Vec::new(),
+        scale: Vec::new(),
+        theme_mode: Vec::new(),
+    };
+    for (channel, name) in [("otherchannel", "stale"), ("somechannel", "fresh")] {
+        reduce(
+            &mut state,
+            AppEvent::ChannelEmotesLoaded {
+                channel: channel.to_string(),
+                result: Ok(vec![emote(name)]),
+            },
+            &mut config,
+        );
+    }
+    let tab = state.active_tab().unwrap();
+    let names: Vec<_> = tab.channel_emotes.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["fresh"]);
+    assert_eq!(tab.broadcaster_id.as_deref(), Some("123"));
+}
+
+#[test]
+fn third_party_emotes_reach_their_tab_and_reset_on_rejoin() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+    let emote = |name: &str| Emote {
+        id: name.to_string(),
+        name: name.to_string(),
+        url: String::new(),
+        source: EmoteSource::Bttv,
+        large_url: None,
+    };
+    for (channel, name) in [("otherchannel", "stale"), ("somechannel", "catJAM")] {
+        reduce(
+            &mut state,
+            AppEvent::ThirdPartyEmotesLoaded {
+                channel: channel.to_string(),
+                emotes: vec![emote(name)],
+            },
+            &mut config,
+        );
+    }
+    let tab = state.active_tab().unwrap();
+    let names: Vec<_> = tab
+        .third_party_emotes
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect();
+    assert_eq!(names, ["catJAM"]);
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::ChannelJoined {
+            channel: "somechannel".to_string(),
+            broadcaster_id: "123".to_string(),
+        },
+    );
+    assert!(state.active_tab().unwrap().third_party_emotes.is_empty());
+}
+
+#[test]
+fn reconnect_banner_clears_when_reconnected_or_given_up() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+    let reconnecting = |state: &AppState| state.active_tab().unwrap().reconnecting;
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::Reconnecting {
+            attempt: 2,
+            max_attempts: 6,
+        },
+    );
+    assert_eq!(reconnecting(&state), Some((2, 6)));
+
+    chat(&mut state, &mut config, ChatEvent::Reconnected);
+    assert_eq!(reconnecting(&state), None);
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::Reconnecting {
+            attempt: 6,
+            max_attempts: 6,
+        },
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::EventSubError("gave up".to_string()),
+    );
+    assert_eq!(reconnecting(&state), None);
+}
+
+#[test]
+fn connection_status_follows_the_chat_connection() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+    let status = |state: &AppState| state.active_tab().unwrap().connection_status;
+    assert_eq!(status(&state), ConnectionStatus::Connecting);
+
+    chat(&mut state, &mut config, ChatEvent::Connected);
+    assert_eq!(status(&state), ConnectionStatus::Connected);
+
+    chat(&mut state, &mut config, ChatEvent::Disconnected);
+    assert_eq!(status(&state), ConnectionStatus::Disconnected);
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::Reconnecting {
+            attempt: 1,
+            max_attempts: 6,
+        },
+    );
+    assert_eq!(status(&state), ConnectionStatus::Connecting);
+}
+
+#[test]
+fn a_failed_channel_lookup_disconnects_only_that_tab() {
+    let mut state = logged_in();
+    state.open_tab("otherchannel");
+    let mut config = Config::default();
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::ChannelLookupFailed {
+            channel: "somechannel".to_string(),
+            error: "timed out".to_string(),
+        },
+    );
+
+    let AppState::LoggedIn {
+        tabs, last_error, ..
+    } = &state
+    else {
+        panic!("not logged in");
+    };
+    assert_eq!(tabs[0].connection_status, ConnectionStatus::Disconnected);
+    assert_eq!(tabs[1].connection_status, ConnectionStatus::Connecting);
+    assert!(
+        last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("#somechannel"))
+    );
+}
+
+#[test]
+fn a_failed_raid_cancel_keeps_the_raid_pending() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+    let pending = |state: &AppState| match state {
+        AppState::LoggedIn {
+            pending_raid,
+            last_error,
+            ..
+        } => (
+            pending_raid.as_ref().map(|raid| raid.target.clone()),
+            last_error.clone(),
+        ),
+        _ => panic!("not logged in"),
+    };
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::RaidStarted("friend".to_string()),
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::RaidCancelError("Failed to cancel raid: nope".to_string()),
+    );
+    let (target, error) = pending(&state);
+    assert_eq!(target.as_deref(), Some("friend"));
+    assert!(error.is_some());
+
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::RaidError("Failed to start raid: offline".to_string()),
+    );
+    assert_eq!(pending(&state).0, None);
+}
+
+#[test]
+fn webhook_receives_chat_but_not_unselected_deletions() {
+    let mut state = logged_in();
+    let mut config = Config {
+        chat_history_lines: 0,
+        ..Config::default()
+    };
+    config.webhook.url = Some("http://localhost:8080/chat".to_string());
+
+    let posted = chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(message("1", "someone", "hi")),
+    );
+    let deleted = chat(
+        &mut state,
+        &mut config,
+        ChatEvent::MessageDeleted("1".to_string()),
+    );
+
+    assert!(matches!(
+        &posted[..],
+        [Action::PostWebhook { payload: WebhookPayload::ChatMessage { text, .. }, .. }] if text == "hi"
+    ));
+    assert!(deleted.is_empty());
+}
+
+#[test]
+fn channel_events_go_to_their_own_tab() {
+    let mut state = logged_in();
+    let mut config = Config::default();
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::ChannelJoined {
+            channel: "somechannel".to_string(),
+            broadcaster_id: "123".to_string(),
+        },
+    );
+    state.open_tab("otherchannel");
+
+    reduce(
+        &mut state,
+        AppEvent::ChannelChat {
+            broadcaster_id: "123".to_string(),
+            event: ChatEvent::NewChatMessage(message("1", "someone", "hi")),
+        },
+        &mut config,
+    );
+    assert!(messages(&state).is_empty());
+
+    let AppState::LoggedIn { tabs, .. } = &state else {
+        panic!("expected LoggedIn state");
+    };
+    assert_eq!(tabs[0].chat_messages.len(), 1);
+    assert_eq!(tabs[0].unread, 1);
+
+    state.select_tab(0);
+    assert_eq!(state.current_channel(), Some("somechannel"));
+    assert_eq!(state.active_tab().unwrap().unread, 0);
+
+    state.close_tab(0);
+    assert_eq!(state.current_channel(), Some("otherchannel"));
+}
+
+#[test]
+fn suspicious_user_events_merge_with_the_regular_message_in_either_order() {
+    let mut config = Config::default();
+    let suspicious = |id: &str| ChatMessage {
+        low_trust: Some(LowTrustStatus::Restricted),
+        ..message(id, "someone", "plain text")
+    };
+    let regular = |id: &str| ChatMessage {
+        sender_color: Some((1, 2, 3)),
+        ..message(id, "someone", "with emotes")
+    };
+
+    let mut state = logged_in();
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(regular("a")),
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(suspicious("a")),
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(suspicious("b")),
+    );
+    chat(
+        &mut state,
+        &mut config,
+        ChatEvent::NewChatMessage(regular("b")),
+    );
+
+    let log = messages(&state);
+    assert_eq!(log.len(), 2);
+    for merged in log {
+        assert_eq!(merged.low_trust, Some(LowTrustStatus::Restricted));
+        assert_eq!(merged.sender_color, Some((1, 2, 3)));
+        assert_eq!(merged.to_plain_text(), "with emotes");
+    }
+}
@@ -0,0 +1,137 @@
+use livenac::{
+    core::{
+        commands::{ChatCommand, me_message, parse_command},
+        mock::{ChatApiCall, MockChatApi, mock_token},
+        send::{ChatSend, send_chat, send_whisper},
+    },
+    events::app_event::{AppEvent, ChatEvent},
+};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+fn chat_send(messages: &[&str]) -> ChatSend {
+    ChatSend {
+        channel: "SomeChannel".to_string(),
+        messages: messages
+            .iter()
+            .map(|message| (message.to_string(), Duration::ZERO))
+            .collect(),
+        start: Instant::now(),
+        reply_parent_id: None,
+        is_announcement: false,
+    }
+}
+
+fn sent(message: &str) -> ChatApiCall {
+    ChatApiCall::SendChatMessage {
+        broadcaster_id: "id-somechannel".to_string(),
+        message: message.to_string(),
+        reply_parent_id: None,
+    }
+}
+
+fn events(mut rx: mpsc::Receiver<AppEvent>) -> Vec<ChatEvent> {
+    let mut events = Vec::new();
+    while let Ok(AppEvent::Chat(event)) = rx.try_recv() {
+        events.push(event);
+    }
+    events
+}
+
+#[tokio::test]
+async fn a_plain_message_is_sent_to_the_channel() {
+    let api = MockChatApi::new();
+    let (tx, rx) = mpsc::channel(8);
+
+    send_chat(&api, &mock_token(), chat_send(&["hello chat"]), &tx).await;
+
+    assert_eq!(
+        api.calls(),
+        [
+            ChatApiCall::GetUserId("SomeChannel".to_string()),
+            sent("hello chat"),
+        ]
+    );
+    assert!(matches!(events(rx)[..], [ChatEvent::MessageSent]));
+}
+
+#[tokio::test]
+async fn me_is_sent_with_its_prefix() {
+    let api = MockChatApi::new();
+    let (tx, _rx) = mpsc::channel(8);
+    let ChatCommand::Me(text) = parse_command("/me waves") else {
+        panic!("not parsed as /me");
+    };
+
+    send_chat(&api, &mock_token(), chat_send(&[&me_message(&text)]), &tx).await;
+
+    assert_eq!(api.calls().last(), Some(&sent("/me waves")));
+}
+
+#[tokio::test]
+async fn a_whisper_goes_to_the_recipients_id() {
+    let api = MockChatApi::new();
+    let (tx, rx) = mpsc::channel(8);
+    let ChatCommand::Whisper { recipient, message } = parse_command("/w @Friend psst") else {
+        panic!("not parsed as a whisper");
+    };
+
+    send_whisper(&api, &mock_token(), &recipient, &message, &tx).await;
+
+    assert_eq!(
+        api.calls(),
+        [
+            ChatApiCall::GetUserId("friend".to_string()),
+            ChatApiCall::SendWhisper {
+                to_user_id: "id-friend".to_string(),
+                message: "psst".to_string(),
+            },
+        ]
+    );
+    assert!(matches!(events(rx)[..], [ChatEvent::MessageSent]));
+}
+
+#[tokio::test]
+async fn an_expired_token_on_the_lookup_is_reported_as_such() {
+    let mut api = MockChatApi::new();
+    api.expired_token = true;
+    let (tx, rx) = mpsc::channel(8);
+
+    send_chat(&api, &mock_token(), chat_send(&["hello chat"]), &tx).await;
+
+    assert_eq!(
+        api.calls(),
+        [ChatApiCall::GetUserId("SomeChannel".to_string())]
+    );
+    assert!(matches!(events(rx)[..], [ChatEvent::SessionExpired]));
+}
+
+#[tokio::test]
+async fn a_rate_limited_message_is_retried_once() {
+    let mut api = MockChatApi::new();
+    api.rate_limited_sends = 1;
+    let (tx, rx) = mpsc::channel(8);
+
+    send_chat(&api, &mock_token(), chat_send(&["hello chat"]), &tx).await;
+
+    assert_eq!(api.calls()[1..], [sent("hello chat"), sent("hello chat")]);
+    assert!(matches!(
+        events(rx)[..],
+        [ChatEvent::MessageQueued(_), ChatEvent::MessageSent]
+    ));
+}
+
+#[tokio::test]
+async fn a_message_still_rate_limited_after_the_retry_fails() {
+    let mut api = MockChatApi::new();
+    api.rate_limited_sends = 2;
+    let (tx, rx) = mpsc::channel(8);
+
+    send_chat(&api, &mock_token(), chat_send(&["one", "two"]), &tx).await;
+
+    assert_eq!(api.calls()[1..], [sent("one"), sent("one")]);
+    assert!(matches!(
+        events(rx)[..],
+        [ChatEvent::MessageQueued(_), ChatEvent::MessageSendError(_)]
+    ));
+}
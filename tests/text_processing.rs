@@ -0,0 +1,93 @@
+use livenac::utils::text_processing::{
+    AutolinkMode, TextOrUrl, contains_url, parse_text_for_urls, sanitize_input, split_message_lines,
+};
+
+fn urls(text: &str, mode: AutolinkMode) -> Vec<String> {
+    parse_text_for_urls(text, mode)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            TextOrUrl::Url(url) => Some(url),
+            TextOrUrl::Text(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn links_urls_with_a_scheme_in_every_mode_but_off() {
+    let text = "see https://example.com/page now";
+    assert_eq!(
+        urls(text, AutolinkMode::All),
+        vec!["https://example.com/page"]
+    );
+    assert_eq!(
+        urls(text, AutolinkMode::HttpOnly),
+        vec!["https://example.com/page"]
+    );
+    assert!(urls(text, AutolinkMode::Off).is_empty());
+}
+
+#[test]
+fn off_keeps_the_text_intact() {
+    assert_eq!(
+        parse_text_for_urls("see https://example.com", AutolinkMode::Off),
+        vec![TextOrUrl::Text("see https://example.com".to_string())]
+    );
+}
+
+#[test]
+fn bare_domains_are_only_linked_in_all_mode() {
+    assert_eq!(
+        urls("go to example.com/page", AutolinkMode::All),
+        vec!["http://example.com/page"]
+    );
+    assert_eq!(
+        urls("go to www.example.com", AutolinkMode::All),
+        vec!["http://www.example.com"]
+    );
+    assert!(urls("go to example.com/page", AutolinkMode::HttpOnly).is_empty());
+}
+
+#[test]
+fn version_numbers_are_not_links() {
+    for text in ["pi is 3.14", "update to 1.2.3", "v2.0/beta is out"] {
+        assert!(urls(text, AutolinkMode::All).is_empty(), "{}", text);
+    }
+}
+
+#[test]
+fn file_and_package_names_are_not_links() {
+    for text in [
+        "I love node.js",
+        "check node.js/docs",
+        "open main.rs/",
+        "config.toml/x",
+    ] {
+        assert!(urls(text, AutolinkMode::All).is_empty(), "{}", text);
+    }
+}
+
+#[test]
+fn only_text_shown_as_a_link_counts_as_containing_one() {
+    assert!(contains_url("see example.com/page", AutolinkMode::All));
+    assert!(!contains_url(
+        "see example.com/page",
+        AutolinkMode::HttpOnly
+    ));
+    assert!(!contains_url("see https://example.com", AutolinkMode::Off));
+    for text in [
+        "I love node.js",
+        "check node.js/docs",
+        "pi is 3.14",
+        "v2.0/beta",
+    ] {
+        assert!(!contains_url(text, AutolinkMode::All), "{}", text);
+    }
+}
+
+#[test]
+fn multi_line_input_is_joined_or_split_into_lines() {
+    let typed = "first line\r\n\n  \nsecond\tline\n";
+    assert_eq!(sanitize_input(typed).split_whitespace().count(), 4);
+    assert_eq!(split_message_lines(typed), ["first line", "second line"]);
+    assert_eq!(split_message_lines("just one"), ["just one"]);
+}
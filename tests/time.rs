@@ -0,0 +1,84 @@
+use chrono::{TimeZone, Utc};
+use livenac::utils::time::{
+    DEFAULT_TIMESTAMP_FORMAT, DisplayTimezone, TIMESTAMP_PRESETS, is_valid_format,
+    valid_format_or_default,
+};
+
+const FMT: &str = "%Y-%m-%d %H:%M:%S %z";
+
+fn tz(name: &str) -> DisplayTimezone {
+    name.parse().unwrap()
+}
+
+#[test]
+fn parses_local_utc_and_named_zones() {
+    assert_eq!(tz("Local"), DisplayTimezone::Local);
+    assert_eq!(tz("utc"), DisplayTimezone::Utc);
+    assert_eq!(tz(" Europe/Berlin ").to_string(), "Europe/Berlin");
+    assert!("Mars/Olympus_Mons".parse::<DisplayTimezone>().is_err());
+}
+
+#[test]
+fn utc_is_unaffected_by_dst() {
+    let before = Utc.with_ymd_and_hms(2024, 3, 31, 0, 59, 59).unwrap();
+    let after = Utc.with_ymd_and_hms(2024, 3, 31, 1, 0, 0).unwrap();
+
+    assert_eq!(
+        DisplayTimezone::Utc.format(&before, FMT),
+        "2024-03-31 00:59:59 +0000"
+    );
+    assert_eq!(
+        DisplayTimezone::Utc.format(&after, FMT),
+        "2024-03-31 01:00:00 +0000"
+    );
+}
+
+#[test]
+fn named_zone_skips_an_hour_when_dst_starts() {
+    let berlin = tz("Europe/Berlin");
+    let before = Utc.with_ymd_and_hms(2024, 3, 31, 0, 59, 59).unwrap();
+    let after = Utc.with_ymd_and_hms(2024, 3, 31, 1, 0, 0).unwrap();
+
+    assert_eq!(berlin.format(&before, FMT), "2024-03-31 01:59:59 +0100");
+    assert_eq!(berlin.format(&after, FMT), "2024-03-31 03:00:00 +0200");
+}
+
+#[test]
+fn named_zone_repeats_an_hour_when_dst_ends() {
+    let berlin = tz("Europe/Berlin");
+    let first = Utc.with_ymd_and_hms(2024, 10, 27, 0, 30, 0).unwrap();
+    let second = Utc.with_ymd_and_hms(2024, 10, 27, 1, 30, 0).unwrap();
+
+    // Both instants show the same wall clock time; only the offset differs.
+    assert_eq!(berlin.format(&first, FMT), "2024-10-27 02:30:00 +0200");
+    assert_eq!(berlin.format(&second, FMT), "2024-10-27 02:30:00 +0100");
+}
+
+#[test]
+fn southern_hemisphere_dst_runs_the_other_way() {
+    let sydney = tz("Australia/Sydney");
+    // DST ends on the first Sunday of April at 03:00 local (16:00 UTC the day before).
+    let before = Utc.with_ymd_and_hms(2024, 4, 6, 15, 59, 59).unwrap();
+    let after = Utc.with_ymd_and_hms(2024, 4, 6, 16, 0, 0).unwrap();
+
+    assert_eq!(sydney.format(&before, FMT), "2024-04-07 02:59:59 +1100");
+    assert_eq!(sydney.format(&after, FMT), "2024-04-07 02:00:00 +1000");
+}
+
+#[test]
+fn invalid_timestamp_formats_fall_back_to_the_default() {
+    for (_, pattern) in TIMESTAMP_PRESETS {
+        assert!(is_valid_format(pattern));
+    }
+    assert_eq!(valid_format_or_default("%H:%M"), "%H:%M");
+    for pattern in ["", "%H:%M:%", "%Q"] {
+        assert!(!is_valid_format(pattern));
+        assert_eq!(valid_format_or_default(pattern), DEFAULT_TIMESTAMP_FORMAT);
+    }
+
+    let afternoon = Utc.with_ymd_and_hms(2024, 3, 31, 13, 5, 0).unwrap();
+    assert_eq!(
+        DisplayTimezone::Utc.format(&afternoon, TIMESTAMP_PRESETS[1].1),
+        "01:05:00 PM"
+    );
+}
@@ -0,0 +1,378 @@
+use livenac::{
+    core::tokenize::{
+        EmotePrecedence, EmoteRegistry, RawFragment, normalize_whitespace, resolve_cheermotes,
+        tokenize,
+    },
+    emotes::{ffz_api::FfzSets, seventv_api::EmoteSet, twitch_api::Cheermote},
+    models::{
+        emote::{Emote, EmoteSource},
+        message::MessageFragment,
+    },
+};
+
+fn bttv(name: &str) -> Emote {
+    Emote {
+        id: name.to_lowercase(),
+        name: name.to_string(),
+        url: format!("https://example.com/{}", name),
+        source: EmoteSource::Bttv,
+        large_url: None,
+    }
+}
+
+fn registry() -> EmoteRegistry {
+    ["catJAM", "OMEGALUL"].into_iter().map(bttv).collect()
+}
+
+fn raw(text: &str) -> Vec<RawFragment> {
+    vec![RawFragment::Text(text.to_string())]
+}
+
+fn text(t: &str) -> MessageFragment {
+    MessageFragment::Text(t.to_string())
+}
+
+fn emote(name: &str) -> MessageFragment {
+    MessageFragment::Emote(bttv(name))
+}
+
+#[test]
+fn plain_text_is_one_fragment() {
+    assert_eq!(
+        tokenize(&raw("hello world"), &registry()),
+        vec![text("hello world")]
+    );
+}
+
+#[test]
+fn emote_at_start_and_end() {
+    assert_eq!(
+        tokenize(&raw("catJAM vibing OMEGALUL"), &registry()),
+        vec![emote("catJAM"), text(" vibing "), emote("OMEGALUL")]
+    );
+}
+
+#[test]
+fn adjacent_emotes_keep_the_space_between_them() {
+    assert_eq!(
+        tokenize(&raw("catJAM OMEGALUL"), &registry()),
+        vec![emote("catJAM"), text(" "), emote("OMEGALUL")]
+    );
+}
+
+#[test]
+fn codes_inside_words_are_not_emotes() {
+    assert_eq!(
+        tokenize(&raw("xcatJAM catJAMs catjam"), &registry()),
+        vec![text("xcatJAM catJAMs catjam")]
+    );
+}
+
+#[test]
+fn cjk_text_is_kept_intact() {
+    assert_eq!(
+        tokenize(&raw("こんにちは catJAM 世界"), &registry()),
+        vec![text("こんにちは "), emote("catJAM"), text(" 世界")]
+    );
+}
+
+#[test]
+fn urls_stay_text() {
+    assert_eq!(
+        tokenize(&raw("see https://example.com/catJAM catJAM"), &registry()),
+        vec![text("see https://example.com/catJAM "), emote("catJAM")]
+    );
+}
+
+#[test]
+fn twitch_fragments_are_converted_and_text_merged() {
+    let fragments = vec![
+        RawFragment::Text("hi ".to_string()),
+        RawFragment::Mention("someone".to_string()),
+        RawFragment::Text(" ".to_string()),
+        RawFragment::TwitchEmote {
+            id: "25".to_string(),
+            text: "Kappa".to_string(),
+        },
+        RawFragment::Text(" and ".to_string()),
+        RawFragment::Text("more ".to_string()),
+        RawFragment::Cheer {
+            prefix: "Cheer".to_string(),
+            bits: 100,
+        },
+    ];
+    let tokens = tokenize(&fragments, &EmoteRegistry::default());
+    assert_eq!(tokens.len(), 6);
+    assert_eq!(tokens[0], text("hi "));
+    assert_eq!(tokens[1], MessageFragment::Mention("someone".to_string()));
+    assert!(matches!(
+        &tokens[3],
+        MessageFragment::Emote(e) if e.id == "25" && e.name == "Kappa" && e.source == EmoteSource::Twitch
+    ));
+    assert_eq!(tokens[4], text(" and more "));
+    assert_eq!(
+        tokens[5],
+        MessageFragment::Cheer {
+            prefix: "Cheer".to_string(),
+            bits: 100
+        }
+    );
+}
+
+#[test]
+fn whitespace_runs_collapse_and_ends_are_trimmed() {
+    let fragments = vec![text("  hello \t  there   ")];
+    assert_eq!(
+        normalize_whitespace(fragments, false),
+        vec![text("hello there")]
+    );
+}
+
+#[test]
+fn spacing_around_emotes_becomes_single_spaces() {
+    let fragments = vec![
+        text(" "),
+        emote("catJAM"),
+        text("   so   "),
+        text(" good  "),
+        emote("OMEGALUL"),
+        text("  "),
+    ];
+    assert_eq!(
+        normalize_whitespace(fragments, false),
+        vec![emote("catJAM"), text(" so good "), emote("OMEGALUL")]
+    );
+}
+
+#[test]
+fn space_between_emotes_is_kept_unless_collapsing() {
+    let fragments = vec![emote("catJAM"), text("   "), emote("OMEGALUL")];
+    assert_eq!(
+        normalize_whitespace(fragments.clone(), false),
+        vec![emote("catJAM"), text(" "), emote("OMEGALUL")]
+    );
+    assert_eq!(
+        normalize_whitespace(fragments, true),
+        vec![emote("catJAM"), emote("OMEGALUL")]
+    );
+}
+
+#[test]
+fn collapsing_keeps_spaces_next_to_text() {
+    let fragments = vec![emote("catJAM"), text(" hi "), emote("OMEGALUL")];
+    assert_eq!(normalize_whitespace(fragments.clone(), true), fragments);
+}
+
+#[test]
+fn normalizing_is_idempotent() {
+    let fragments = vec![text(" a  "), emote("catJAM"), text("  b ")];
+    let once = normalize_whitespace(fragments, false);
+    assert_eq!(normalize_whitespace(once.clone(), false), once);
+}
+
+fn kappa() -> RawFragment {
+    RawFragment::TwitchEmote {
+        id: "25".to_string(),
+        text: "Kappa".to_string(),
+    }
+}
+
+fn registry_with_kappa(precedence: EmotePrecedence) -> EmoteRegistry {
+    let mut registry = EmoteRegistry::new(precedence);
+    registry.insert(bttv("Kappa"));
+    registry
+}
+
+#[test]
+fn tagged_twitch_emote_wins_a_collision_by_default() {
+    let tokens = tokenize(&[kappa()], &registry_with_kappa(EmotePrecedence::Twitch));
+    assert!(matches!(
+        &tokens[..],
+        [MessageFragment::Emote(e)] if e.id == "25" && e.source == EmoteSource::Twitch
+    ));
+}
+
+#[test]
+fn third_party_precedence_replaces_the_tagged_emote() {
+    let tokens = tokenize(
+        &[kappa()],
+        &registry_with_kappa(EmotePrecedence::ThirdParty),
+    );
+    assert_eq!(tokens, vec![emote("Kappa")]);
+}
+
+#[test]
+fn untagged_colliding_code_is_third_party_either_way() {
+    for precedence in [EmotePrecedence::Twitch, EmotePrecedence::ThirdParty] {
+        let tokens = tokenize(&raw("Kappa"), &registry_with_kappa(precedence));
+        assert_eq!(tokens, vec![emote("Kappa")]);
+    }
+}
+
+#[test]
+fn seventv_emotes_mix_with_twitch_and_bttv() {
+    let set: EmoteSet = serde_json::from_str(
+        r#"{
+            "emotes": [
+                {
+                    "id": "60ae958e229664e8667aea38",
+                    "name": "peepoHappy",
+                    "data": { "host": { "url": "//cdn.7tv.app/emote/60ae958e229664e8667aea38" } }
+                },
+                { "id": "60aecb385174a619dbc175be", "name": "Clap", "data": null }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let mut registry = registry();
+    registry.extend(set.to_emotes());
+
+    let fragments = vec![
+        RawFragment::Text("catJAM ".to_string()),
+        RawFragment::TwitchEmote {
+            id: "25".to_string(),
+            text: "Kappa".to_string(),
+        },
+        RawFragment::Text(" peepoHappy Clap".to_string()),
+    ];
+    let sources: Vec<_> = tokenize(&fragments, &registry)
+        .into_iter()
+        .filter_map(|fragment| match fragment {
+            MessageFragment::Emote(emote) => Some((emote.name, emote.source, emote.url)),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(sources.len(), 4);
+    assert_eq!(sources[0].1, EmoteSource::Bttv);
+    assert_eq!(sources[1].1, EmoteSource::Twitch);
+    assert_eq!(
+        sources[2],
+        (
+            "peepoHappy".to_string(),
+            EmoteSource::Stv,
+            "https://cdn.7tv.app/emote/60ae958e229664e8667aea38/2x.webp".to_string()
+        )
+    );
+    assert_eq!(
+        sources[3].2,
+        "https://cdn.7tv.app/emote/60aecb385174a619dbc175be/2x.webp"
+    );
+}
+
+#[test]
+fn ffz_emotes_come_from_the_default_sets_at_the_preferred_scale() {
+    let global: FfzSets = serde_json::from_str(
+        r#"{
+            "default_sets": [3],
+            "sets": {
+                "3": {
+                    "emoticons": [
+                        {
+                            "id": 25927,
+                            "name": "CatBag",
+                            "urls": {
+                                "1": "//cdn.frankerfacez.com/emote/25927/1",
+                                "2": "//cdn.frankerfacez.com/emote/25927/2",
+                                "4": "//cdn.frankerfacez.com/emote/25927/4"
+                            }
+                        },
+                        {
+                            "id": 720507,
+                            "name": "PartyCat",
+                            "urls": { "1": "https://cdn.frankerfacez.com/emote/720507/1" },
+                            "animated": { "1": "https://cdn.frankerfacez.com/emote/720507/animated/1" }
+                        },
+                        { "id": 1, "name": "NoImage", "urls": {} }
+                    ]
+                },
+                "4330": {
+                    "emoticons": [
+                        { "id": 2, "name": "SomeonesOwn", "urls": { "1": "https://example.com/2" } }
+                    ]
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    let emotes = global.to_emotes();
+    let urls: Vec<_> = emotes
+        .iter()
+        .map(|emote| (emote.name.as_str(), emote.url.as_str()))
+        .collect();
+    assert_eq!(
+        urls,
+        [
+            ("CatBag", "https://cdn.frankerfacez.com/emote/25927/2"),
+            (
+                "PartyCat",
+                "https://cdn.frankerfacez.com/emote/720507/animated/1"
+            ),
+        ]
+    );
+    assert!(emotes.iter().all(|emote| emote.source == EmoteSource::Ffz));
+    assert!(emotes[1].lacks_still_image());
+    // The 4x image is kept for the hover preview.
+    assert_eq!(
+        emotes[0].preview_url(true),
+        "https://cdn.frankerfacez.com/emote/25927/4"
+    );
+    assert_eq!(emotes[1].preview_url(true), emotes[1].url);
+
+    let room: FfzSets = serde_json::from_str(
+        r#"{
+            "room": { "set": 99 },
+            "sets": {
+                "99": { "emoticons": [{ "id": 3, "name": "catJAM", "urls": { "2": "https://example.com/3" } }] }
+            }
+        }"#,
+    )
+    .unwrap();
+    // Added after the BetterTTV globals, the channel's FFZ emote takes their code.
+    let mut registry = registry();
+    registry.extend(room.to_emotes());
+    assert_eq!(
+        registry.get("catJAM").map(|emote| emote.source.clone()),
+        Some(EmoteSource::Ffz)
+    );
+}
+
+#[test]
+fn cheers_use_the_highest_tier_they_reach() {
+    let tier = |min_bits: i64| {
+        format!(
+            r#"{{
+                "min_bits": {0},
+                "id": "{0}",
+                "images": {{
+                    "dark": {{ "animated": {{ "2": "https://example.com/dark/{0}.gif" }}, "static": {{}} }},
+                    "light": {{ "animated": {{}}, "static": {{}} }}
+                }}
+            }}"#,
+            min_bits
+        )
+    };
+    let cheermote: Cheermote = serde_json::from_str(&format!(
+        r#"{{ "prefix": "Cheer", "tiers": [{}, {}] }}"#,
+        tier(1),
+        tier(100)
+    ))
+    .unwrap();
+
+    let cheer = |prefix: &str, bits| MessageFragment::Cheer {
+        prefix: prefix.to_string(),
+        bits,
+    };
+    let mut fragments = vec![cheer("cheer", 250), cheer("Other", 5)];
+    resolve_cheermotes(&mut fragments, &[cheermote]);
+
+    match &fragments[0] {
+        MessageFragment::Cheermote { bits, emote, .. } => {
+            assert_eq!(*bits, 250);
+            assert_eq!(emote.url, "https://example.com/dark/100.gif");
+            assert_eq!(emote.source, EmoteSource::Cheer);
+        }
+        other => panic!("expected a cheermote, got {:?}", other),
+    }
+    assert_eq!(fragments[1], cheer("Other", 5));
+}